@@ -1,4 +1,16 @@
+/// `bundled-cjk-font` 内嵌的字体子集，仓库不附带，需自行生成（见 README）
+const CJK_FONT: &str = "assets/fonts/cjk-subset.otf";
+
 fn main() {
+    if std::env::var_os("CARGO_FEATURE_BUNDLED_CJK_FONT").is_some()
+        && !std::path::Path::new(CJK_FONT).is_file()
+    {
+        panic!(
+            "启用了 bundled-cjk-font 特性，但找不到字体文件 {CJK_FONT}；\
+             请按 README 用 pyftsubset 生成该文件，或去掉 --features bundled-cjk-font"
+        );
+    }
+
     if std::env::var("CARGO_CFG_TARGET_OS").unwrap_or_default() == "windows" {
         let mut res = winresource::WindowsResource::new();
         res.set_icon("assets/icon.ico");
@@ -1,38 +1,76 @@
-use chrono::{Local, NaiveTime};
+use chrono::{Local, NaiveTime, Timelike};
 use eframe::egui;
 use eframe::egui::{Align, Color32, FontFamily, FontId, RichText, Stroke, TextStyle, Ui};
 use rfd::FileDialog;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-use crate::config::save_config;
-use crate::engine::Engine;
+use crate::config::{MAX_BACKUP_COUNT, Snapshot, list_snapshots, read_config_file, save_config};
+use crate::engine::{Engine, Severity};
+use crate::feed;
+use crate::monitor;
+use crate::notifier::{self, MAX_GAIN_DB, suggest_gain_db, validate_sound};
 use crate::schedule;
-use crate::schedule::{AppConfig, BuiltinSound, Period, PeriodKind, ScheduleProfile, SoundSource};
-use crate::tray::TrayHandle;
+use crate::schedule::{
+    AppConfig, BuiltinSound, CloseAction, Density, GeoLocation, Period, PeriodKind, ScheduleMode,
+    ScheduleProfile, ScheduleSort, SolarEvent, SolarTime, SoundPack, SoundSlots, SoundSource,
+    TrayActivation, UI_SCALE_RANGE, UiState,
+};
+use crate::selftest::{self, CheckResult};
+use crate::tray::{self, TrayHandle};
 
 const MIN_CONTENT_WIDTH: f32 = 720.0;
-const PERIOD_ROW_MIN_HEIGHT: f32 = 38.0;
 const PERIOD_TIME_WIDTH: f32 = 96.0;
 const PERIOD_KIND_WIDTH: f32 = 80.0;
 const PERIOD_NAME_MIN_WIDTH: f32 = 120.0;
 const PERIOD_STATUS_WIDTH: f32 = 34.0;
 const PERIOD_DELETE_WIDTH: f32 = 56.0;
+const PERIOD_DUPLICATE_WIDTH: f32 = 48.0;
+const PERIOD_NOTE_WIDTH: f32 = 30.0;
+const PERIOD_SILENT_WIDTH: f32 = 30.0;
+/// 节点名与时间表名的最大字符数，过长的名称在任何布局下都难以完整显示
+const MAX_NAME_CHARS: usize = 40;
+/// 时间表名在下拉框与顶栏中的最大显示宽度，超出截断并在悬停时显示全名
+const SCHEDULE_NAME_MAX_WIDTH: f32 = 200.0;
+/// 时间轴视图每分钟对应的像素高度
+const TIMELINE_PX_PER_MINUTE: f32 = 1.2;
+const TIMELINE_AXIS_WIDTH: f32 = 56.0;
+/// 连续保存失败达到该次数后切换为仅内存模式
+const MAX_SAVE_FAILURES: u32 = 3;
+/// 修改配置后延迟同步给检测线程，连续拖动/输入时只克隆一次配置
+const ENGINE_SYNC_DEBOUNCE: Duration = Duration::from_millis(200);
+/// “测试全部音效”中上一段播完到下一段开始的间隔
+const SOUND_TEST_GAP: Duration = Duration::from_millis(800);
+/// 检测线程没有心跳持续该时长后才在状态栏报错
+const ENGINE_STALL_GRACE: Duration = Duration::from_secs(5);
+/// 当前节点变化后该行闪烁高亮的时长
+const CURRENT_PULSE: Duration = Duration::from_millis(1500);
 
 pub struct WcNoticeApp {
     engine: Arc<Engine>,
     config: AppConfig,
     tray: Option<TrayHandle>,
     status_msg: String,
+    status_severity: Severity,
     theme_applied: bool,
     show_exit_confirm_dialog: bool,
+    /// 关闭确认框中的“记住我的选择”
+    remember_close_choice: bool,
+    /// 等待二次确认删除的时间表 id
+    pending_delete_schedule: Option<u64>,
     allow_window_close: bool,
     viewport_was_minimized: bool,
     /// 正在从托盘恢复中：跳过本帧及下一帧的最小化检测，
     /// 避免 restore 命令异步生效前被 handle_window_lifecycle 再次最小化。
     /// 值为剩余需要跳过的帧数（通常设为 2）。
     restoring_from_tray_frames: u8,
+    /// 待执行的窗口位置检查：启动与从托盘恢复后确认窗口在屏幕内，
+    /// 或托盘菜单要求重置位置；等窗口位置可读（非最小化）时执行
+    position_check: Option<PositionCheck>,
+    /// 最近一次应用到任务栏按钮的状态；`None` 表示未知（按钮重建后需重新设置）
+    taskbar_state: Option<TaskbarState>,
     /// 任务栏按钮是否已被隐藏（避免每帧重复调用 Win32 API）
     taskbar_hidden: bool,
     last_active_schedule_id: Option<u64>,
@@ -41,22 +79,90 @@ pub struct WcNoticeApp {
     new_schedule_name: String,
     // 重命名当前时间表
     rename_schedule_name: String,
+    // 当前时间表时区（IANA 名称，留空为本地时间）
+    timezone_input: String,
 
     // 新增节点表单
     new_period_time: String,
     new_period_name: String,
+    new_period_note: String,
     new_period_kind: PeriodKind,
+    /// 新节点相对日出/日落；`None` 为固定时间
+    new_period_solar: Option<SolarTime>,
+
+    // 快速添加课节表单
+    class_block_start: String,
+    class_block_lesson: u32,
+    class_block_class_mins: u32,
+    class_block_break_mins: u32,
+
+    // 临时提醒表单
+    oneshot_minutes: u32,
+    /// 会议进程列表的编辑文本（逗号分隔），失去焦点时写回配置
+    meeting_processes_text: String,
+    oneshot_name: String,
+    oneshot_kind: PeriodKind,
 
     // 弹窗控制
-    show_schedule_window: bool,
-    show_new_schedule_window: bool,
-    show_sound_window: bool,
-    show_add_dialog: bool,
-    show_settings_window: bool,
+    windows: OpenWindows,
+    /// 批量编辑文本与最近一次应用时的无效行
+    bulk_edit_text: String,
+    bulk_edit_errors: Vec<String>,
+    /// 高级配置编辑器的 TOML 文本与最近一次应用的解析错误
+    config_editor_text: String,
+    config_editor_error: Option<String>,
+    /// 已读取、等待用户选择“合并”或“替换”的导入配置
+    pending_import: Option<PendingImport>,
+    /// 后台自检线程写入的结果；`None` 表示正在运行或尚未运行
+    selftest_results: Arc<Mutex<Option<Vec<CheckResult>>>>,
+    selftest_running: bool,
+    /// 打开“历史版本”窗口时读取的快照列表
+    snapshots: Vec<Snapshot>,
+    /// 首次启动引导中的选择：从空时间表开始（否则使用默认预设）
+    onboarding_empty_start: bool,
+    /// 本地音效检查结果缓存（按路径），打开音效窗口时清空重新检查
+    sound_checks: HashMap<String, Result<Option<Duration>, String>>,
+    /// 音效窗口正在编辑的时间表（打开时记录，切换当前时间表不影响）
+    sound_schedule_id: Option<u64>,
+    /// 正在进行的“测试全部音效”
+    sound_test: Option<SoundTest>,
+    /// “模拟一天”的推进步长（秒）与结果
+    simulate_step_secs: u64,
+    simulation: Option<Vec<(NaiveTime, String)>>,
+    /// 检测线程没有心跳时记录发现时间与是否已提示
+    engine_stall: Option<EngineStall>,
+    /// 上次换算相对日出/日落节点的时间（每分钟检查一次是否跨日）
+    solar_checked_at: Option<Instant>,
+    /// 正在显示的视觉提醒的开始时间
+    visual_alert_started: Option<Instant>,
+    /// 大屏展示模式（全屏显示当前节点与倒计时，Esc 退出）
+    kiosk_mode: bool,
+    /// 进入大屏模式前的窗口位置，退出时移回原显示器
+    kiosk_restore_pos: Option<egui::Pos2>,
+    /// 主区域以时间轴而非列表显示节点
+    timeline_view: bool,
+    /// 列表视图下一帧需要滚动到的节点下标（从时间轴点击跳转）
+    scroll_to_period: Option<usize>,
+    /// 引擎上报的当前节点（时间表 id, 时间, 名称），按内容而非下标比较，编辑其他节点不算变化
+    current_period_key: Option<(u64, String, String)>,
+    /// 刚成为当前节点的行下标与开始高亮的时间
+    current_pulse: Option<(usize, Instant)>,
+    /// 列表中勾选用于批量操作的节点下标（与“启用”无关）
+    selected_periods: HashSet<usize>,
+    /// 平移选中节点的分钟数（负数为提前）
+    batch_shift_minutes: i64,
 
     // 防抖：记录最后一次"脏"时刻，延迟写盘
     pending_save: Option<Instant>,
-    pending_save_msg: String,
+    pending_save_msg: (Severity, String),
+    /// 防抖：最后一次修改配置的时刻，到期后才把配置同步给检测线程
+    pending_engine_sync: Option<Instant>,
+    /// 正在输入但尚未提交的节点文本框，焦点离开时才算一次修改
+    uncommitted_text_edit: Option<egui::Id>,
+    /// 连续保存失败次数
+    save_failures: u32,
+    /// 配置目录不可写：只在内存中保留修改，等待用户“另存为”
+    memory_only: bool,
 }
 
 impl WcNoticeApp {
@@ -67,36 +173,112 @@ impl WcNoticeApp {
             .active_schedule()
             .map(|schedule| schedule.name.clone())
             .unwrap_or_default();
+        let timezone_input = config
+            .active_schedule()
+            .and_then(|schedule| schedule.timezone.clone())
+            .unwrap_or_default();
+        let ui_state = config.ui_state.clone();
+        let windows = OpenWindows::restored(&ui_state, config.restore_windows);
+        let meeting_processes_text = config.meeting_processes.join(", ");
 
         let app = Self {
             engine,
             config,
             tray,
             status_msg: "就绪".to_string(),
+            status_severity: Severity::Info,
             theme_applied: false,
             show_exit_confirm_dialog: false,
+            remember_close_choice: false,
+            pending_delete_schedule: None,
             allow_window_close: false,
             viewport_was_minimized: false,
             restoring_from_tray_frames: 0,
+            position_check: Some(PositionCheck::IfOffscreen),
+            taskbar_state: None,
             taskbar_hidden: false,
             last_active_schedule_id: active_id,
             new_schedule_name: String::new(),
             rename_schedule_name: rename,
+            timezone_input,
             new_period_time: "00:00:00".to_string(),
             new_period_name: "新节点".to_string(),
+            new_period_note: String::new(),
             new_period_kind: PeriodKind::Start,
-            show_schedule_window: false,
-            show_new_schedule_window: false,
-            show_sound_window: false,
-            show_add_dialog: false,
-            show_settings_window: false,
+            new_period_solar: None,
+            class_block_start: "08:00:00".to_string(),
+            class_block_lesson: 1,
+            class_block_class_mins: 45,
+            class_block_break_mins: 10,
+            oneshot_minutes: 10,
+            meeting_processes_text,
+            oneshot_name: "临时提醒".to_string(),
+            oneshot_kind: PeriodKind::Start,
+            windows,
+            bulk_edit_text: String::new(),
+            bulk_edit_errors: Vec::new(),
+            config_editor_text: String::new(),
+            config_editor_error: None,
+            pending_import: None,
+            selftest_results: Arc::new(Mutex::new(None)),
+            selftest_running: false,
+            snapshots: Vec::new(),
+            onboarding_empty_start: false,
+            sound_checks: HashMap::new(),
+            sound_schedule_id: None,
+            sound_test: None,
+            simulate_step_secs: 1,
+            simulation: None,
+            engine_stall: None,
+            solar_checked_at: None,
+            visual_alert_started: None,
+            kiosk_mode: false,
+            kiosk_restore_pos: None,
+            timeline_view: ui_state.timeline_view,
+            scroll_to_period: None,
+            current_period_key: None,
+            current_pulse: None,
+            selected_periods: HashSet::new(),
+            batch_shift_minutes: 5,
             pending_save: None,
-            pending_save_msg: String::new(),
+            pending_save_msg: (Severity::Success, String::new()),
+            pending_engine_sync: None,
+            uncommitted_text_edit: None,
+            save_failures: 0,
+            memory_only: false,
         };
         app.apply_autostart();
+        app.sync_tray();
         app
     }
 
+    /// 把托盘相关设置（单击/双击恢复、自定义图标）同步到托盘线程，
+    /// 并让点击通知走托盘的恢复窗口信号
+    fn sync_tray(&self) {
+        if let Some(tray) = &self.tray {
+            tray.set_activation(self.config.tray_activation);
+            tray.set_icon_path(self.config.tray_icon_path.as_deref());
+            notifier::set_click_handler(tray.show_requester());
+        }
+    }
+
+    fn pick_tray_icon(&mut self) {
+        let Some(file) = FileDialog::new()
+            .add_filter("图片", &["png", "ico"])
+            .pick_file()
+        else {
+            return;
+        };
+        // 先在主线程验证，避免保存一张托盘线程读不了的图片
+        if let Err(e) = image::open(&file) {
+            self.set_status(Severity::Error, format!("图片无法读取: {e}"));
+            return;
+        }
+        self.config.tray_icon_path = Some(make_abs_path(file).display().to_string());
+        self.sync_tray();
+        self.mark_dirty("托盘图标已更换");
+    }
+
     /// 同步开机自启状态到系统注册表（仅 Windows）
     fn apply_autostart(&self) {
         #[cfg(target_os = "windows")]
@@ -122,14 +304,35 @@ impl WcNoticeApp {
         }
     }
 
-    /// 标记数据已变更：立即同步到引擎，延迟 500ms 写盘（防抖）
+    /// 标记数据已变更：`ENGINE_SYNC_DEBOUNCE` 后同步到引擎，500ms 后写盘（均防抖，连续修改只各做一次）
     fn mark_dirty(&mut self, success_msg: impl Into<String>) {
+        self.mark_dirty_as(Severity::Success, success_msg);
+    }
+
+    /// 同 `mark_dirty`，写盘成功后以指定严重程度显示消息（如暂停提醒用警告色）
+    fn mark_dirty_as(&mut self, severity: Severity, msg: impl Into<String>) {
         self.config.ensure_active_schedule();
-        self.engine.update_config(self.config.clone());
-        self.pending_save_msg = success_msg.into();
+        self.pending_engine_sync = Some(Instant::now());
+        self.pending_save_msg = (severity, msg.into());
         self.pending_save = Some(Instant::now());
     }
 
+    /// 把配置同步给检测线程；`force` 为假时等防抖到期
+    fn flush_engine_sync(&mut self, force: bool) {
+        if self
+            .pending_engine_sync
+            .is_some_and(|t| force || t.elapsed() >= ENGINE_SYNC_DEBOUNCE)
+        {
+            self.pending_engine_sync = None;
+            self.engine.update_config(self.config.clone());
+        }
+    }
+
+    fn set_status(&mut self, severity: Severity, msg: impl Into<String>) {
+        self.status_severity = severity;
+        self.status_msg = msg.into();
+    }
+
     /// 在 update() 帧开头调用：到期则真正写盘
     fn flush_pending_save(&mut self) {
         if self
@@ -137,14 +340,85 @@ impl WcNoticeApp {
             .is_some_and(|t| t.elapsed() >= Duration::from_millis(500))
         {
             self.pending_save = None;
-            let msg = std::mem::take(&mut self.pending_save_msg);
+            let severity = self.pending_save_msg.0;
+            let msg = std::mem::take(&mut self.pending_save_msg.1);
+            if self.memory_only {
+                return;
+            }
             match save_config(&self.config) {
-                Ok(_) => self.status_msg = msg,
-                Err(e) => self.status_msg = format!("保存失败: {e}"),
+                Ok(_) => {
+                    self.save_failures = 0;
+                    if !msg.is_empty() {
+                        self.set_status(severity, msg);
+                    }
+                }
+                Err(e) => {
+                    self.save_failures += 1;
+                    log::warn!("保存配置失败（第 {} 次）: {}", self.save_failures, e);
+                    if self.save_failures >= MAX_SAVE_FAILURES {
+                        self.memory_only = true;
+                        self.set_status(Severity::Error, "配置无法写入，已切换为仅内存模式");
+                    } else {
+                        self.set_status(Severity::Error, format!("保存失败: {e}"));
+                    }
+                }
+            }
+        }
+    }
+
+    /// 仅内存模式下选择新的保存位置，成功后恢复正常保存
+    fn save_config_as(&mut self) {
+        let Some(path) = FileDialog::new()
+            .add_filter("TOML", &["toml"])
+            .set_file_name("schedule.toml")
+            .save_file()
+        else {
+            return;
+        };
+
+        match crate::config::save_config_as(path, &self.config) {
+            Ok(_) => {
+                self.memory_only = false;
+                self.save_failures = 0;
+                self.set_status(Severity::Success, "配置已另存，之后的修改保存到新位置");
             }
+            Err(e) => self.set_status(Severity::Error, format!("另存失败: {e}")),
+        }
+    }
+
+    /// 窗口开关/视图变化时记录界面状态并静默写盘（不提示）
+    fn sync_ui_state(&mut self) {
+        let ui_state = self
+            .windows
+            .to_ui_state(self.timeline_view, self.config.restore_windows);
+        if ui_state != self.config.ui_state {
+            self.config.ui_state = ui_state;
+            self.pending_save.get_or_insert_with(Instant::now);
         }
     }
 
+    /// 整体替换当前配置（恢复历史版本、导入替换、直接编辑 TOML）：
+    /// 修复后应用（暂停状态同步给检测线程），并清空按旧配置记录的界面状态（勾选的节点、高亮、音效窗口的时间表等）
+    fn replace_config(&mut self, mut config: AppConfig, msg: impl Into<String>) {
+        config.repair();
+        self.config = config;
+        self.meeting_processes_text = self.config.meeting_processes.join(", ");
+        // 置空后由 `sync_rename_name_from_active` 重新读取名称、时区并清空勾选
+        self.last_active_schedule_id = None;
+        self.selected_periods.clear();
+        self.current_period_key = None;
+        self.current_pulse = None;
+        self.scroll_to_period = None;
+        self.uncommitted_text_edit = None;
+        self.sound_schedule_id = None;
+        self.pending_delete_schedule = None;
+        self.simulation = None;
+        self.engine.set_enabled(self.config.enabled);
+        self.apply_autostart();
+        self.sync_tray();
+        self.mark_dirty(msg);
+    }
+
     fn sync_rename_name_from_active(&mut self) {
         if self.last_active_schedule_id != self.config.active_schedule_id {
             self.rename_schedule_name = self
@@ -152,7 +426,13 @@ impl WcNoticeApp {
                 .active_schedule()
                 .map(|schedule| schedule.name.clone())
                 .unwrap_or_default();
+            self.timezone_input = self
+                .config
+                .active_schedule()
+                .and_then(|schedule| schedule.timezone.clone())
+                .unwrap_or_default();
             self.last_active_schedule_id = self.config.active_schedule_id;
+            self.selected_periods.clear();
         }
     }
 
@@ -164,23 +444,43 @@ impl WcNoticeApp {
         self.config.active_schedule_mut()
     }
 
+    /// 当前时间表时区下的当前时刻（未设置时区时为本地时间），用于状态与倒计时
+    fn schedule_now(&self) -> NaiveTime {
+        let now = Local::now();
+        self.active_schedule()
+            .map_or(now.naive_local(), |schedule| schedule.zone_now(&now))
+            .time()
+    }
+
     fn handle_tray_events(&mut self, ctx: &egui::Context) {
         let mut show_requested = false;
         let mut exit_requested = false;
+        let mut reset_position_requested = false;
 
         if let Some(tray) = &self.tray {
             tray.bind_egui_ctx(ctx);
             show_requested = tray.take_show_request();
             exit_requested = tray.take_exit_request();
+            reset_position_requested = tray.take_reset_position_request();
         }
 
         if show_requested {
             self.restore_from_tray(ctx);
         }
 
-        if exit_requested {
+        if reset_position_requested {
             self.restore_from_tray(ctx);
-            self.show_exit_confirm_dialog = true;
+            self.position_check = Some(PositionCheck::Reset);
+        }
+
+        if exit_requested {
+            if self.config.confirm_on_close {
+                self.restore_from_tray(ctx);
+                self.show_exit_confirm_dialog = true;
+            } else {
+                self.allow_window_close = true;
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
         }
     }
 
@@ -193,15 +493,18 @@ impl WcNoticeApp {
         // Visible(false) 会让 eframe 停止渲染帧，update() 不再被调用，
         // 导致 handle_tray_events() 无法执行，托盘点击永远无响应。
         // Minimized(true) 保持 update() 继续运行，托盘事件可正常处理。
+        // 响铃不依赖 update()：检测与播放都在引擎的后台线程中完成。
         // hide_taskbar_button() 在下一帧窗口确认最小化后再调用（见 handle_window_lifecycle）。
         ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
         self.viewport_was_minimized = true;
-        self.status_msg = "已最小化到托盘，点击托盘图标可恢复".to_string();
+        self.set_status(Severity::Info, "已最小化到托盘，点击托盘图标可恢复");
     }
 
     fn restore_from_tray(&mut self, ctx: &egui::Context) {
         // 先恢复任务栏按钮样式，再发送 viewport 命令
         self.show_taskbar_button();
+        // 样式切换会重建任务栏按钮，进度需要重新设置
+        self.taskbar_state = None;
         ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
         ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
         self.viewport_was_minimized = false;
@@ -210,6 +513,72 @@ impl WcNoticeApp {
         self.restoring_from_tray_frames = 2;
     }
 
+    /// 执行待处理的窗口位置检查；窗口仍最小化、位置尚不可读时留到之后的帧
+    fn run_position_check(&mut self, ctx: &egui::Context) {
+        let Some(check) = self.position_check else {
+            return;
+        };
+        if self.restoring_from_tray_frames > 0 {
+            return;
+        }
+        if self.ensure_window_on_screen(ctx, check == PositionCheck::Reset) {
+            self.position_check = None;
+        }
+    }
+
+    /// 确认窗口仍在某个显示器上（笔记本拔掉扩展坞后可能落在屏幕外），否则收进最近显示器的工作区；
+    /// `reset` 为 `true` 时无条件移到主显示器工作区居中。
+    /// 无法枚举显示器的平台只处理重置（移到左上角附近）。返回 `false` 表示窗口位置尚不可读。
+    fn ensure_window_on_screen(&self, ctx: &egui::Context, reset: bool) -> bool {
+        let (minimized, outer) = ctx.input(|i| {
+            (
+                i.viewport().minimized.unwrap_or(false),
+                i.viewport().outer_rect,
+            )
+        });
+        // 仍处于最小化时 Windows 报告的是 (-32000, -32000) 占位坐标
+        let Some(outer) = outer.filter(|_| !minimized) else {
+            return false;
+        };
+
+        // 显示器坐标为物理像素，窗口位置使用逻辑坐标
+        let scale = ctx.native_pixels_per_point().unwrap_or(1.0);
+        let x = (outer.min.x * scale).round() as i32;
+        let y = (outer.min.y * scale).round() as i32;
+        let width = (outer.width() * scale).round() as i32;
+        let height = (outer.height() * scale).round() as i32;
+
+        let monitors = monitor::monitors();
+        let target = if reset {
+            monitors
+                .first()
+                .map_or((40, 40), |primary| primary.centered(width, height))
+        } else if monitors.is_empty() || monitor::is_reachable(&monitors, x, y) {
+            return true;
+        } else {
+            let Some(nearest) = monitor::nearest(&monitors, x, y) else {
+                return true;
+            };
+            nearest.clamp_into_work_area(x, y, width, height)
+        };
+
+        log::info!(
+            "窗口位置 ({x}, {y}) {}，已移到 ({}, {})",
+            if reset {
+                "已手动重置"
+            } else {
+                "不在任何显示器内"
+            },
+            target.0,
+            target.1
+        );
+        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(egui::pos2(
+            target.0 as f32 / scale,
+            target.1 as f32 / scale,
+        )));
+        true
+    }
+
     /// 隐藏任务栏按钮：通过 Win32 API 找到应用窗口，
     /// 移除 WS_EX_APPWINDOW，添加 WS_EX_TOOLWINDOW，使其从任务栏消失。
     /// 使用 SetWindowPos+SWP_FRAMECHANGED 刷新样式，不调用 ShowWindow 以免停止 eframe 渲染循环。
@@ -282,11 +651,38 @@ impl WcNoticeApp {
     #[cfg(not(target_os = "windows"))]
     fn show_taskbar_button(&self) {}
 
+    /// 按提醒状态更新任务栏按钮：暂停时显示黄色满格，运行中显示当前时段进度
+    fn sync_taskbar_progress(&mut self, now: NaiveTime) {
+        let paused = !self.engine.is_enabled()
+            || self
+                .active_schedule()
+                .is_some_and(|schedule| schedule.muted || schedule.archived);
+        let state = if !self.config.taskbar_progress {
+            TaskbarState::Hidden
+        } else if paused {
+            TaskbarState::Paused
+        } else {
+            self.active_schedule()
+                .and_then(|schedule| schedule.period_progress(&now))
+                .map_or(TaskbarState::Hidden, |progress| {
+                    TaskbarState::Progress((progress * 100.0).round() as u8)
+                })
+        };
+        if self.taskbar_state != Some(state) {
+            self.taskbar_state = Some(state);
+            apply_taskbar_state(state);
+        }
+    }
+
     fn handle_window_lifecycle(&mut self, ctx: &egui::Context) {
         if self.tray.is_some() {
             // 正在恢复中：跳过最小化检测，消耗一帧计数
             if self.restoring_from_tray_frames > 0 {
                 self.restoring_from_tray_frames -= 1;
+                if self.restoring_from_tray_frames == 0 {
+                    self.position_check
+                        .get_or_insert(PositionCheck::IfOffscreen);
+                }
                 return;
             }
 
@@ -307,8 +703,20 @@ impl WcNoticeApp {
         }
 
         if !self.allow_window_close && ctx.input(|i| i.viewport().close_requested()) {
-            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
-            self.show_exit_confirm_dialog = true;
+            match self.config.close_action {
+                CloseAction::Minimize if self.tray.is_some() => {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+                    self.minimize_to_tray(ctx);
+                }
+                CloseAction::Exit | CloseAction::Minimize if !self.config.confirm_on_close => {
+                    // 不拦截，本次关闭直接生效
+                    self.allow_window_close = true;
+                }
+                _ => {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+                    self.show_exit_confirm_dialog = true;
+                }
+            }
         }
     }
 
@@ -327,7 +735,7 @@ impl WcNoticeApp {
             .open(&mut open)
             .collapsible(false)
             .resizable(false)
-            .fixed_size([360.0, 0.0])
+            .fixed_size([fit_width(ctx, 360.0), 0.0])
             .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
             .show(ctx, |ui| {
                 ui.label(RichText::new("确定要关闭 WC Notice 吗？").strong());
@@ -338,7 +746,10 @@ impl WcNoticeApp {
                     );
                 }
 
-                ui.add_space(10.0);
+                ui.add_space(6.0);
+                ui.checkbox(&mut self.remember_close_choice, "记住我的选择")
+                    .on_hover_text("之后关闭时不再询问，可在设置的“点击关闭按钮”中改回");
+                ui.add_space(6.0);
                 ui.horizontal(|ui| {
                     if tray_enabled && ui.button("最小化到托盘").clicked() {
                         minimize_to_tray = true;
@@ -367,39 +778,203 @@ impl WcNoticeApp {
         if minimize_to_tray {
             self.show_exit_confirm_dialog = false;
             self.allow_window_close = false;
+            if std::mem::take(&mut self.remember_close_choice) {
+                self.config.close_action = CloseAction::Minimize;
+                self.mark_dirty("已记住：关闭窗口时最小化到托盘，可在设置中修改");
+            }
             self.minimize_to_tray(ctx);
         }
 
         if exit_app {
             self.show_exit_confirm_dialog = false;
             self.allow_window_close = true;
+            // 马上就要退出，等不到延迟保存，直接写盘
+            if std::mem::take(&mut self.remember_close_choice) {
+                self.config.close_action = CloseAction::Exit;
+                self.config.confirm_on_close = false;
+                if !self.memory_only
+                    && let Err(e) = save_config(&self.config)
+                {
+                    log::warn!("保存关闭方式失败: {}", e);
+                }
+            }
             ctx.send_viewport_cmd(egui::ViewportCommand::Close);
         }
     }
 
+    /// 首次启动引导：介绍托盘行为，选择开机自启与初始时间表，可跳过
+    fn show_onboarding_window(&mut self, ctx: &egui::Context) {
+        if self.config.onboarded || self.kiosk_mode {
+            return;
+        }
+
+        let mut open = true;
+        let mut finish = false;
+        let mut skip = false;
+        let mut autostart = self.config.autostart;
+        let tray_enabled = self.tray.is_some();
+
+        egui::Window::new("欢迎使用 WC Notice")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .fixed_size([fit_width(ctx, 380.0), 0.0])
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label("按时间表在上下课时响铃并发送桌面通知。");
+                if tray_enabled {
+                    ui.label(
+                        RichText::new("关闭窗口时可最小化到系统托盘，提醒会继续在后台运行。")
+                            .color(color_text_muted()),
+                    );
+                }
+
+                ui.add_space(10.0);
+                ui.checkbox(&mut autostart, "开机自动启动");
+
+                ui.add_space(6.0);
+                ui.label(RichText::new("初始时间表").strong());
+                ui.radio_value(
+                    &mut self.onboarding_empty_start,
+                    false,
+                    "使用默认预设（可再修改）",
+                );
+                ui.radio_value(&mut self.onboarding_empty_start, true, "从空时间表开始");
+
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.button("开始使用").clicked() {
+                        finish = true;
+                    }
+                    if ui.button("跳过").clicked() {
+                        skip = true;
+                    }
+                });
+            });
+
+        if autostart != self.config.autostart {
+            self.config.autostart = autostart;
+            self.apply_autostart();
+        }
+
+        if finish && self.onboarding_empty_start {
+            self.config.schedules.clear();
+            self.config.create_empty_schedule("我的时间表".to_string());
+        }
+
+        if finish || skip || !open {
+            self.config.onboarded = true;
+            self.mark_dirty("设置已保存");
+        }
+    }
+
+    fn show_delete_schedule_confirm_window(&mut self, ctx: &egui::Context) {
+        let Some(id) = self.pending_delete_schedule else {
+            return;
+        };
+        let Some((name, period_count)) = self
+            .config
+            .schedules
+            .iter()
+            .find(|schedule| schedule.id == id)
+            .map(|schedule| (schedule.name.clone(), schedule.periods.len()))
+        else {
+            self.pending_delete_schedule = None;
+            return;
+        };
+
+        let mut open = true;
+        let mut confirm = false;
+        let mut cancel = false;
+
+        egui::Window::new("删除时间表")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .fixed_size([fit_width(ctx, 360.0), 0.0])
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label(RichText::new(format!("确定要删除「{name}」吗？")).strong());
+                ui.label(
+                    RichText::new(format!(
+                        "该时间表包含 {period_count} 个节点，删除后无法恢复。"
+                    ))
+                    .color(color_text_muted()),
+                );
+
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui
+                        .add(
+                            egui::Button::new(RichText::new("删除").color(color_danger_text()))
+                                .fill(color_danger_fill())
+                                .stroke(Stroke::new(1.0, color_danger_border())),
+                        )
+                        .clicked()
+                    {
+                        confirm = true;
+                    }
+                    if ui.button("取消").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if !open || cancel {
+            self.pending_delete_schedule = None;
+        }
+
+        if confirm {
+            self.pending_delete_schedule = None;
+            if self.config.remove_schedule(id).is_some() {
+                self.sync_rename_name_from_active();
+                self.mark_dirty("时间表已删除");
+            }
+        }
+    }
+
     fn show_top_panel(&mut self, ctx: &egui::Context, now: NaiveTime) {
         let schedule_name = self
             .active_schedule()
-            .map(|schedule| schedule.name.clone())
-            .unwrap_or_else(|| "无活动时间表".to_string());
+            .map(|schedule| {
+                if schedule.archived {
+                    format!("{}（已归档，不响铃）", schedule.name)
+                } else if schedule.muted {
+                    format!("{}（已单独暂停）", schedule.name)
+                } else {
+                    schedule.name.clone()
+                }
+            })
+            .unwrap_or_else(|| self.config.labels.no_schedule.clone());
 
         let current_status = self
             .active_schedule()
-            .map(|schedule| schedule.current_status(&now))
+            .map(|schedule| schedule.current_status(&now, &self.config.labels.idle))
             .unwrap_or_else(|| "请新建时间表".to_string());
 
         let next_desc = self
             .active_schedule()
+            .and_then(|schedule| schedule.next_countdown(&now, self.config.countdown_rollover))
+            .map(|(name, diff)| format!("{} · {}", name, format_countdown(diff)))
+            .unwrap_or_else(|| self.config.labels.no_next.clone());
+
+        let remaining = self
+            .active_schedule()
+            .map(|schedule| format!("{} 个", schedule.remaining_count(&now)))
+            .unwrap_or_else(|| "-".to_string());
+
+        // 时间表按其他时区响铃时，本地时钟下方同时显示该时区时间
+        let zone_clock = self
+            .active_schedule()
+            .filter(|schedule| schedule.timezone_error().is_none())
             .and_then(|schedule| {
-                schedule
-                    .next_period(&now)
-                    .and_then(|period| period.naive_time().map(|time| (period.name.clone(), time)))
-            })
-            .map(|(name, time)| {
-                let diff = (time - now).num_seconds().max(0);
-                format!("{} · {}", name, format_countdown(diff))
-            })
-            .unwrap_or_else(|| "今日无后续节点".to_string());
+                let tz = schedule.timezone.as_ref()?;
+                Some(format!("{tz} {}", now.format("%H:%M:%S")))
+            });
+
+        let session = self
+            .active_schedule()
+            .and_then(|schedule| schedule.current_session(&now));
 
         egui::TopBottomPanel::top("top_panel")
             .frame(
@@ -420,11 +995,21 @@ impl WcNoticeApp {
                                 .strong()
                                 .color(color_text_strong()),
                         );
-                        ui.label(
-                            RichText::new(&schedule_name)
-                                .size(12.0)
-                                .color(color_text_muted()),
+                        if let Some(zone) = &zone_clock {
+                            ui.label(RichText::new(zone).size(12.0).color(color_text_muted()))
+                                .on_hover_text("当前时间表按该时区响铃，倒计时也按该时区计算");
+                        }
+                        let short = truncate_to_width(
+                            ui,
+                            &schedule_name,
+                            &egui::FontId::proportional(12.0),
+                            SCHEDULE_NAME_MAX_WIDTH,
                         );
+                        let response =
+                            ui.label(RichText::new(&short).size(12.0).color(color_text_muted()));
+                        if short != schedule_name {
+                            response.on_hover_text(&schedule_name);
+                        }
                     });
 
                     // ── 右栏（含中栏）：right_to_left 布局 ──
@@ -455,11 +1040,12 @@ impl WcNoticeApp {
                             .clicked()
                         {
                             let new_state = self.engine.toggle_enabled();
-                            self.status_msg = if new_state {
-                                "提醒已恢复".to_string()
+                            self.config.enabled = new_state;
+                            if new_state {
+                                self.mark_dirty("提醒已恢复");
                             } else {
-                                "提醒已暂停".to_string()
-                            };
+                                self.mark_dirty_as(Severity::Warning, "提醒已暂停");
+                            }
                         }
                         if ui
                             .add(
@@ -472,7 +1058,36 @@ impl WcNoticeApp {
                             .on_hover_text("音效设置")
                             .clicked()
                         {
-                            self.show_sound_window = true;
+                            self.sound_checks.clear();
+                            self.config.prune_recent_sounds();
+                            self.sound_schedule_id = self.config.active_schedule_id;
+                            self.windows.sound = true;
+                        }
+                        if ui
+                            .add(
+                                egui::Button::new(RichText::new("🖥").size(16.0))
+                                    .fill(color_chip())
+                                    .stroke(Stroke::new(1.0, color_border()))
+                                    .corner_radius(8)
+                                    .min_size(egui::vec2(32.0, 32.0)),
+                            )
+                            .on_hover_text("大屏模式（Esc 退出）")
+                            .clicked()
+                        {
+                            self.set_kiosk_mode(ctx, true);
+                        }
+                        if ui
+                            .add(
+                                egui::Button::new(RichText::new("⏰").size(16.0))
+                                    .fill(color_chip())
+                                    .stroke(Stroke::new(1.0, color_border()))
+                                    .corner_radius(8)
+                                    .min_size(egui::vec2(32.0, 32.0)),
+                            )
+                            .on_hover_text("临时提醒")
+                            .clicked()
+                        {
+                            self.windows.oneshot = true;
                         }
                         if ui
                             .add(
@@ -485,7 +1100,7 @@ impl WcNoticeApp {
                             .on_hover_text("新建时间表")
                             .clicked()
                         {
-                            self.show_new_schedule_window = true;
+                            self.windows.new_schedule = true;
                         }
                         if ui
                             .add(
@@ -498,7 +1113,7 @@ impl WcNoticeApp {
                             .on_hover_text("切换/重命名时间表")
                             .clicked()
                         {
-                            self.show_schedule_window = true;
+                            self.windows.schedule = true;
                         }
                         if ui
                             .add(
@@ -511,7 +1126,7 @@ impl WcNoticeApp {
                             .on_hover_text("设置")
                             .clicked()
                         {
-                            self.show_settings_window = true;
+                            self.windows.settings = true;
                         }
 
                         // 中栏：chip 居中（在 right_to_left 中，这部分在按钮左边）
@@ -533,41 +1148,195 @@ impl WcNoticeApp {
                                         color_warning_text(),
                                         180.0,
                                     );
+                                    summary_chip_truncated(
+                                        ui,
+                                        "今日剩余",
+                                        &remaining,
+                                        color_text_strong(),
+                                        90.0,
+                                    );
                                 });
                             },
                         );
                     });
                 });
+
+                // 当前时段进度：上课为开始→结束，课间为下课→下一节开始；不在任何时段时不显示
+                if let Some(session) = &session {
+                    let progress = session.progress(&now);
+                    let percent = (progress * 100.0).floor() as u32;
+                    let (text, fill) = if session.in_class {
+                        (
+                            format!("{} 已过 {percent}%", session.name),
+                            color_period_start_current_border(),
+                        )
+                    } else {
+                        (
+                            format!("课间 · 距“{}” 已过 {percent}%", session.name),
+                            color_period_end_current_border(),
+                        )
+                    };
+                    ui.add_space(6.0);
+                    ui.add(
+                        egui::ProgressBar::new(progress)
+                            .desired_height(14.0)
+                            .fill(fill)
+                            .text(RichText::new(text).size(11.0).color(color_text_strong())),
+                    )
+                    .on_hover_text(format!(
+                        "{} ~ {}",
+                        session.start.format("%H:%M:%S"),
+                        session.end.format("%H:%M:%S")
+                    ));
+                }
+            });
+    }
+
+    fn set_kiosk_mode(&mut self, ctx: &egui::Context, enabled: bool) {
+        self.kiosk_mode = enabled;
+        if enabled {
+            self.move_to_overlay_monitor(ctx);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(true));
+        } else {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(false));
+            if let Some(pos) = self.kiosk_restore_pos.take() {
+                ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(pos));
+            }
+        }
+    }
+
+    /// 全屏前把窗口移到设置的显示器上（全屏总是铺满窗口所在的显示器）；
+    /// 所选显示器已断开时回退到主显示器
+    fn move_to_overlay_monitor(&mut self, ctx: &egui::Context) {
+        let Some(index) = self.config.overlay_monitor else {
+            return;
+        };
+        let monitors = monitor::monitors();
+        let Some(target) = monitors.get(index).or_else(|| monitors.first()) else {
+            return;
+        };
+
+        self.kiosk_restore_pos = ctx.input(|i| i.viewport().outer_rect).map(|rect| rect.min);
+        // 显示器坐标为物理像素，窗口位置使用逻辑坐标
+        let scale = ctx.native_pixels_per_point().unwrap_or(1.0);
+        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(egui::pos2(
+            target.x as f32 / scale + 40.0,
+            target.y as f32 / scale + 40.0,
+        )));
+    }
+
+    /// 大屏模式：超大字号显示当前节点与距下一节点的倒计时，字号随窗口高度缩放
+    fn show_kiosk(&mut self, ctx: &egui::Context, now: NaiveTime) {
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.set_kiosk_mode(ctx, false);
+            return;
+        }
+
+        let current_status = self
+            .active_schedule()
+            .map(|schedule| schedule.current_status(&now, &self.config.labels.idle))
+            .unwrap_or_else(|| self.config.labels.no_schedule.clone());
+        let next = self
+            .active_schedule()
+            .and_then(|schedule| schedule.next_countdown(&now, self.config.countdown_rollover));
+
+        let height = ctx.screen_rect().height();
+        let title_size = (height * 0.16).clamp(32.0, 200.0);
+        let countdown_size = (height * 0.22).clamp(40.0, 280.0);
+        let caption_size = (height * 0.05).clamp(14.0, 64.0);
+
+        egui::CentralPanel::default()
+            .frame(egui::Frame::new().fill(color_background()))
+            .show(ctx, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(height * 0.08);
+                    ui.label(
+                        RichText::new(Local::now().format("%H:%M:%S").to_string())
+                            .monospace()
+                            .size(caption_size)
+                            .color(color_text_muted()),
+                    );
+                    ui.add_space(height * 0.04);
+                    ui.label(
+                        RichText::new(&current_status)
+                            .size(title_size)
+                            .strong()
+                            .color(color_text_strong()),
+                    );
+                    ui.add_space(height * 0.04);
+                    match &next {
+                        Some((name, diff)) => {
+                            ui.label(
+                                RichText::new(format_countdown(*diff))
+                                    .monospace()
+                                    .size(countdown_size)
+                                    .strong()
+                                    .color(color_warning_text()),
+                            );
+                            ui.label(
+                                RichText::new(format!("距 {name}"))
+                                    .size(caption_size)
+                                    .color(color_text_muted()),
+                            );
+                        }
+                        None => {
+                            ui.label(
+                                RichText::new(&self.config.labels.no_next)
+                                    .size(caption_size)
+                                    .color(color_text_muted()),
+                            );
+                        }
+                    }
+                });
             });
     }
 
     fn show_schedule_management(&mut self, ui: &mut Ui) {
         ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
-            let schedules: Vec<(u64, String)> = self
+            let schedules: Vec<(u64, String, bool)> = self
                 .config
-                .schedules
-                .iter()
-                .map(|schedule| (schedule.id, schedule.name.clone()))
+                .sorted_schedules()
+                .into_iter()
+                .map(|schedule| (schedule.id, schedule.name.clone(), schedule.archived))
                 .collect();
 
             ui.horizontal(|ui| {
                 ui.label(RichText::new("当前时间表").color(color_text_muted()));
 
                 let mut selected = self.config.active_schedule_id;
-                let selected_text = self
+                let font_id = egui::TextStyle::Button.resolve(ui.style());
+                let selected_name = self
                     .active_schedule()
                     .map(|schedule| schedule.name.as_str())
                     .unwrap_or("(无)");
+                let selected_text =
+                    truncate_to_width(ui, selected_name, &font_id, SCHEDULE_NAME_MAX_WIDTH);
 
                 egui::ComboBox::from_id_salt("active_schedule")
                     .selected_text(selected_text)
                     .width(220.0)
                     .show_ui(ui, |ui| {
-                        for (id, name) in &schedules {
-                            ui.selectable_value(&mut selected, Some(*id), name);
-                        }
-                    });
-
+                        for (id, name, archived) in &schedules {
+                            let short =
+                                truncate_to_width(ui, name, &font_id, SCHEDULE_NAME_MAX_WIDTH);
+                            let response = if *archived {
+                                ui.selectable_value(
+                                    &mut selected,
+                                    Some(*id),
+                                    RichText::new(format!("{short}（已归档）"))
+                                        .color(color_text_muted()),
+                                )
+                            } else {
+                                ui.selectable_value(&mut selected, Some(*id), short.as_str())
+                            };
+                            if short != *name {
+                                response.on_hover_text(name);
+                            }
+                        }
+                    })
+                    .response
+                    .on_hover_text(selected_name);
+
                 if selected != self.config.active_schedule_id {
                     self.config.set_active_schedule(selected);
                     self.sync_rename_name_from_active();
@@ -581,11 +1350,69 @@ impl WcNoticeApp {
                 );
             });
 
+            if let Some(schedule) = self.active_schedule() {
+                let (total, enabled, span) = schedule.stats();
+                let summary = match span {
+                    Some((first, last)) => format!(
+                        "共 {total} 个节点，启用 {enabled} 个，{}–{}",
+                        first.format("%H:%M"),
+                        last.format("%H:%M")
+                    ),
+                    None => format!("共 {total} 个节点，启用 {enabled} 个"),
+                };
+                ui.label(RichText::new(summary).size(12.0).color(color_text_muted()));
+            }
+
+            if let Some(mode) = self.active_schedule().map(|schedule| schedule.mode) {
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("用途").color(color_text_muted()));
+                    let mut selected = mode;
+                    for candidate in ScheduleMode::ALL {
+                        ui.selectable_value(&mut selected, candidate, candidate.label());
+                    }
+                    if selected != mode
+                        && let Some(schedule) = self.active_schedule_mut()
+                    {
+                        schedule.mode = selected;
+                        self.mark_dirty("时间表用途已更改");
+                    }
+                });
+                let hint = match mode {
+                    ScheduleMode::Daily => "每天按节点重复响铃，最后一个结束节点视为放学",
+                    ScheduleMode::Timer => {
+                        "节点只响一次，响过后自动移除；添加时可输入 +10 表示 10 分钟后"
+                    }
+                };
+                ui.label(RichText::new(hint).size(12.0).color(color_text_muted()));
+            }
+
+            // 未启用 schedule-timezone 特性时只在已有设置时显示，便于清除
+            let has_timezone = self
+                .active_schedule()
+                .is_some_and(|schedule| schedule.timezone.is_some());
+            if cfg!(feature = "schedule-timezone") || has_timezone {
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("时区").color(color_text_muted()));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.timezone_input)
+                            .desired_width(160.0)
+                            .hint_text(RichText::new("本地时间").color(color_hint_text())),
+                    )
+                    .on_hover_text("IANA 时区名，如 Asia/Shanghai；留空按本地时间响铃");
+                    if ui.button("√ 应用").clicked() {
+                        self.apply_schedule_timezone();
+                    }
+                });
+            }
+
             ui.add_space(6.0);
             ui.horizontal(|ui| {
                 ui.label(RichText::new("重命名").color(color_text_muted()));
                 ui.add(
                     egui::TextEdit::singleline(&mut self.rename_schedule_name)
+                        .char_limit(MAX_NAME_CHARS)
                         .desired_width(220.0)
                         .hint_text(RichText::new("当前时间表名称").color(color_hint_text())),
                 );
@@ -593,7 +1420,7 @@ impl WcNoticeApp {
                 if ui.button("√ 改名").clicked() {
                     let new_name = self.rename_schedule_name.trim().to_string();
                     if new_name.is_empty() {
-                        self.status_msg = "时间表名称不能为空".to_string();
+                        self.set_status(Severity::Error, "时间表名称不能为空");
                     } else if let Some(schedule) = self.active_schedule_mut() {
                         schedule.name = new_name;
                         self.sync_rename_name_from_active();
@@ -601,6 +1428,45 @@ impl WcNoticeApp {
                     }
                 }
 
+                let muted = self
+                    .active_schedule()
+                    .is_some_and(|schedule| schedule.muted);
+                if ui
+                    .button(if muted {
+                        "恢复响铃"
+                    } else {
+                        "暂停此表"
+                    })
+                    .on_hover_text("只暂停当前时间表，切换到其他时间表照常响铃；顶部的全局暂停优先")
+                    .clicked()
+                    && let Some(schedule) = self.active_schedule_mut()
+                {
+                    schedule.muted = !muted;
+                    if muted {
+                        self.mark_dirty("该时间表已恢复响铃");
+                    } else {
+                        self.mark_dirty_as(Severity::Warning, "该时间表已单独暂停");
+                    }
+                }
+
+                let archived = self
+                    .active_schedule()
+                    .is_some_and(|schedule| schedule.archived);
+                let archive_label = if archived { "取消归档" } else { "归档" };
+                if ui
+                    .button(archive_label)
+                    .on_hover_text("归档后保留时间表但不再响铃，也不会被自动选中")
+                    .clicked()
+                    && let Some(schedule) = self.active_schedule_mut()
+                {
+                    schedule.archived = !archived;
+                    self.mark_dirty(if archived {
+                        "已取消归档"
+                    } else {
+                        "时间表已归档"
+                    });
+                }
+
                 if ui
                     .add(
                         egui::Button::new(
@@ -611,21 +1477,39 @@ impl WcNoticeApp {
                     )
                     .clicked()
                 {
-                    if self.config.remove_active_schedule().is_some() {
-                        self.sync_rename_name_from_active();
-                        self.mark_dirty("时间表已删除");
-                    }
+                    self.pending_delete_schedule = self.config.active_schedule_id;
                 }
             });
         });
     }
 
+    /// 校验并保存当前时间表的时区；无法识别时保持原设置
+    fn apply_schedule_timezone(&mut self) {
+        let input = self.timezone_input.trim();
+        let timezone = (!input.is_empty()).then(|| input.to_string());
+        let Some(schedule) = self.active_schedule_mut() else {
+            return;
+        };
+        let previous = std::mem::replace(&mut schedule.timezone, timezone);
+        if let Some(error) = schedule.timezone_error() {
+            schedule.timezone = previous;
+            self.set_status(Severity::Error, error);
+            return;
+        }
+        let msg = match &schedule.timezone {
+            Some(tz) => format!("时间表已改为按 {tz} 时间响铃"),
+            None => "时间表已改为按本地时间响铃".to_string(),
+        };
+        self.mark_dirty(msg);
+    }
+
     fn show_new_schedule(&mut self, ui: &mut Ui) {
         ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
             ui.horizontal(|ui| {
                 ui.label(RichText::new("名称").color(color_text_muted()));
                 ui.add(
                     egui::TextEdit::singleline(&mut self.new_schedule_name)
+                        .char_limit(MAX_NAME_CHARS)
                         .desired_width(220.0)
                         .hint_text(RichText::new("输入新时间表名称").color(color_hint_text())),
                 );
@@ -647,223 +1531,1703 @@ impl WcNoticeApp {
         });
     }
 
-    fn show_sound_settings(&mut self, ui: &mut Ui) {
-        let mut changed = false;
-
+    fn show_oneshot_editor(&mut self, ui: &mut Ui) {
         ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
-            let active_name = self
-                .active_schedule()
-                .map(|schedule| schedule.name.clone())
-                .unwrap_or_else(|| "(无)".to_string());
-
-            ui.label(
-                RichText::new(format!("当前时间表: {active_name}"))
-                    .size(13.0)
-                    .color(color_text_muted()),
-            );
-
-            if let Some(schedule) = self.active_schedule_mut() {
-                changed |= draw_sound_source_editor(
-                    ui,
-                    "开始音效",
-                    &format!("sound_start_{}", schedule.id),
-                    &mut schedule.sound.start,
-                    PeriodKind::Start,
-                );
-                ui.add_space(6.0);
-                changed |= draw_sound_source_editor(
-                    ui,
-                    "结束音效",
-                    &format!("sound_end_{}", schedule.id),
-                    &mut schedule.sound.end,
-                    PeriodKind::End,
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut self.oneshot_minutes)
+                        .range(1..=720)
+                        .suffix(" 分钟后"),
                 );
-            }
-        });
-
-        if changed {
-            self.mark_dirty("音效设置已保存");
-        }
-    }
+                egui::ComboBox::from_id_salt("oneshot_kind")
+                    .selected_text(self.oneshot_kind.label())
+                    .width(72.0)
+                    .show_ui(ui, |ui| {
+                        for kind in PeriodKind::ALL.into_iter().filter(PeriodKind::fires) {
+                            ui.selectable_value(&mut self.oneshot_kind, kind, kind.label());
+                        }
+                    });
+            });
 
-    fn show_period_editor(&mut self, ui: &mut Ui, now: NaiveTime) {
-        let added = false;
-        let mut changed_existing = false;
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("名称").color(color_text_muted()));
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.oneshot_name)
+                        .desired_width(200.0)
+                        .hint_text(RichText::new("例如：喝水").color(color_hint_text())),
+                );
 
-        card_no_title(ui, |ui| {
-            // "+" 按钮居中，点击后打开弹窗
-            ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
-                if ui
-                    .add(
-                        egui::Button::new(
-                            RichText::new("  +  ")
-                                .size(20.0)
-                                .strong()
-                                .color(color_text_strong()),
-                        )
-                        .fill(color_chip())
-                        .stroke(Stroke::new(1.5, color_border()))
-                        .corner_radius(8)
-                        .min_size(egui::vec2(48.0, 36.0)),
-                    )
-                    .on_hover_text("添加时间节点")
-                    .clicked()
-                {
-                    self.show_add_dialog = true;
+                if ui.button("√ 添加").clicked() {
+                    let name = self.oneshot_name.trim();
+                    let name = if name.is_empty() {
+                        "临时提醒"
+                    } else {
+                        name
+                    };
+                    let at = Local::now().naive_local()
+                        + chrono::TimeDelta::minutes(self.oneshot_minutes as i64);
+                    self.engine
+                        .add_oneshot(at, name.to_string(), self.oneshot_kind);
+                    self.set_status(
+                        Severity::Success,
+                        format!("已添加临时提醒：{} 后", self.oneshot_minutes),
+                    );
                 }
             });
 
-            ui.add_space(8.0);
+            let pending = self.engine.pending_oneshots();
+            if pending.is_empty() {
+                return;
+            }
 
-            if let Some(schedule) = self.active_schedule_mut() {
-                if schedule.periods.is_empty() {
+            ui.add_space(6.0);
+            ui.label(RichText::new("待触发").color(color_text_muted()));
+            let today = Local::now().date_naive();
+            for oneshot in pending {
+                ui.horizontal(|ui| {
+                    let format = if oneshot.at.date() == today {
+                        "%H:%M:%S"
+                    } else {
+                        "明日 %H:%M:%S"
+                    };
                     ui.label(
-                        RichText::new("当前时间表没有节点，请先添加开始/结束节点")
-                            .color(color_text_muted()),
+                        RichText::new(oneshot.at.format(format).to_string())
+                            .monospace()
+                            .color(color_text_strong()),
                     );
-                    return;
-                }
-
-                let mut delete_index: Option<usize> = None;
-
-                for (idx, period) in schedule.periods.iter_mut().enumerate() {
-                    let (row_fill, row_border) = period_row_style(period, &now);
-                    egui::Frame::new()
-                        .fill(row_fill)
-                        .stroke(Stroke::new(1.0, row_border))
-                        .corner_radius(8)
-                        .inner_margin(egui::Margin::symmetric(8, 6))
-                        .show(ui, |ui| {
-                            let row_width = ui.available_width();
-                            ui.allocate_ui_with_layout(
-                                egui::vec2(row_width, PERIOD_ROW_MIN_HEIGHT),
-                                egui::Layout::left_to_right(egui::Align::Center)
-                                    .with_main_justify(false),
-                                |ui| {
-                                    if ui.checkbox(&mut period.enabled, "").changed() {
-                                        changed_existing = true;
-                                    }
+                    ui.label(format!("{} · {}", oneshot.name, oneshot.kind.label()));
+                    if ui.small_button("取消").clicked() && self.engine.cancel_oneshot(oneshot.id)
+                    {
+                        self.set_status(Severity::Info, "临时提醒已取消");
+                    }
+                });
+            }
+        });
+    }
 
-                                    let time_response = ui.add_sized(
-                                        [PERIOD_TIME_WIDTH, 24.0],
-                                        egui::TextEdit::singleline(&mut period.time),
-                                    );
-                                    if time_response.changed() {
-                                        changed_existing = true;
-                                    }
-                                    // 失去焦点时规范化时间格式
-                                    if time_response.lost_focus() {
-                                        if let Some(normalized) =
-                                            schedule::normalize_time_str(&period.time)
-                                        {
-                                            period.time = normalized;
-                                            changed_existing = true;
-                                        }
-                                        // 如果格式无效，保留原值（用户可继续编辑）
-                                    }
+    fn open_bulk_editor(&mut self) {
+        self.bulk_edit_text = self
+            .active_schedule()
+            .map(|schedule| schedule::periods_to_text(&schedule.periods))
+            .unwrap_or_default();
+        self.bulk_edit_errors.clear();
+        self.windows.bulk_edit = true;
+    }
 
-                                    let mut kind = period.kind;
-                                    egui::ComboBox::from_id_salt(format!(
-                                        "period_kind_{}_{}",
-                                        schedule.id, idx
-                                    ))
-                                    .selected_text(kind.label())
-                                    .width(PERIOD_KIND_WIDTH)
-                                    .show_ui(ui, |ui| {
-                                        ui.selectable_value(&mut kind, PeriodKind::Start, "开始");
-                                        ui.selectable_value(&mut kind, PeriodKind::End, "结束");
-                                    });
+    /// 批量编辑：每行一个节点，应用时整体替换当前时间表的节点
+    fn show_bulk_editor(&mut self, ui: &mut Ui) {
+        ui.label(
+            RichText::new(
+                "每行格式：HH:MM:SS 类型(开始/结束/预备/标记) 名称[ // 备注]；时间也可写相对日出日落（如 日落-30、日出+15）；行首 # 表示停用，! 表示静音（可连用：# ! …）。\n\
+                 也可直接粘贴从 Word/Excel 复制的表格（制表符或逗号分隔），按内容识别时间与类型列，其余列依次为名称、备注",
+            )
+            .size(12.0)
+            .color(color_text_muted()),
+        );
+        ui.add_space(6.0);
 
-                                    if kind != period.kind {
-                                        period.kind = kind;
-                                        changed_existing = true;
-                                    }
+        egui::ScrollArea::vertical()
+            .max_height(360.0)
+            .show(ui, |ui| {
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.bulk_edit_text)
+                        .font(TextStyle::Monospace)
+                        .desired_rows(12)
+                        .desired_width(f32::INFINITY),
+                );
+            });
 
-                                    let reserved_tail = PERIOD_STATUS_WIDTH
-                                        + PERIOD_DELETE_WIDTH
-                                        + ui.spacing().item_spacing.x * 2.0;
-                                    let name_width = (ui.available_width() - reserved_tail)
-                                        .max(PERIOD_NAME_MIN_WIDTH);
+        for error in &self.bulk_edit_errors {
+            ui.label(RichText::new(error).size(12.0).color(color_danger_text()));
+        }
 
-                                    if ui
-                                        .add_sized(
-                                            [name_width, 24.0],
-                                            egui::TextEdit::singleline(&mut period.name),
-                                        )
-                                        .changed()
-                                    {
-                                        changed_existing = true;
-                                    }
+        ui.add_space(6.0);
+        ui.horizontal(|ui| {
+            if ui.button("✔ 应用").clicked() {
+                match schedule::parse_periods_text(&self.bulk_edit_text) {
+                    Ok(periods) => {
+                        let count = periods.len();
+                        if let Some(schedule) = self.active_schedule_mut() {
+                            schedule.periods = periods;
+                            self.config.resolve_solar_times(&Local::now());
+                            self.selected_periods.clear();
+                            self.bulk_edit_errors.clear();
+                            self.windows.bulk_edit = false;
+                            self.mark_dirty(format!("节点已批量更新（{count} 个）"));
+                        }
+                    }
+                    Err(errors) => self.bulk_edit_errors = errors,
+                }
+            }
+            if ui.button("重新载入").clicked() {
+                self.open_bulk_editor();
+            }
+        });
+    }
 
-                                    ui.add_sized(
-                                        [PERIOD_STATUS_WIDTH, 24.0],
-                                        egui::Label::new(
-                                            RichText::new(period_runtime_state(period, &now))
-                                                .size(12.0)
-                                                .color(color_text_muted()),
-                                        ),
-                                    );
+    fn show_class_block_editor(&mut self, ui: &mut Ui) {
+        let use_spinner = self.config.time_spinner;
+        let snap_minutes = self.config.snap_minutes;
 
-                                    if ui
-                                        .add_sized(
-                                            [PERIOD_DELETE_WIDTH, 24.0],
-                                            egui::Button::new(
-                                                RichText::new("删除").color(color_danger_text()),
-                                            )
-                                            .fill(color_danger_fill())
-                                            .stroke(Stroke::new(1.0, color_danger_border())),
-                                        )
-                                        .clicked()
-                                    {
-                                        delete_index = Some(idx);
-                                    }
-                                },
-                            );
-                        });
-                    ui.add_space(4.0);
+        ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("开始时间").color(color_text_muted()));
+                if use_spinner {
+                    draw_time_spinner(ui, &mut self.class_block_start);
+                } else {
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.class_block_start).desired_width(90.0),
+                    );
+                    if response.lost_focus()
+                        && let Some(normalized) =
+                            schedule::normalize_time_snapped(&self.class_block_start, snap_minutes)
+                    {
+                        self.class_block_start = normalized;
+                    }
                 }
+                ui.label(RichText::new("第").color(color_text_muted()));
+                ui.add(egui::DragValue::new(&mut self.class_block_lesson).range(1..=30));
+                ui.label(RichText::new("节").color(color_text_muted()));
+            });
 
-                if let Some(idx) = delete_index {
-                    schedule.periods.remove(idx);
-                    changed_existing = true;
-                }
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("课长").color(color_text_muted()));
+                ui.add(
+                    egui::DragValue::new(&mut self.class_block_class_mins)
+                        .range(1..=240)
+                        .suffix(" 分钟"),
+                );
+                ui.label(RichText::new("课间").color(color_text_muted()));
+                ui.add(
+                    egui::DragValue::new(&mut self.class_block_break_mins)
+                        .range(0..=120)
+                        .suffix(" 分钟"),
+                );
+            });
 
-                if changed_existing {
-                    schedule.sort_periods();
+            ui.add_space(6.0);
+            if ui.button("√ 添加").clicked() {
+                let start = schedule::normalize_time_snapped(&self.class_block_start, snap_minutes)
+                    .and_then(|time| NaiveTime::parse_from_str(&time, "%H:%M:%S").ok());
+                let Some(start) = start else {
+                    self.set_status(
+                        Severity::Error,
+                        format!("时间格式错误: {}", self.class_block_start),
+                    );
+                    return;
+                };
+
+                let (class_mins, break_mins, lesson) = (
+                    self.class_block_class_mins,
+                    self.class_block_break_mins,
+                    self.class_block_lesson,
+                );
+                let next_start = self.active_schedule_mut().and_then(|schedule| {
+                    schedule.append_class_block(start, class_mins, break_mins, "第", lesson)
+                });
+                match next_start {
+                    Some(next_start) => {
+                        // 自动衔接下一节，连续点击即可排完半天课表
+                        self.class_block_start = next_start.format("%H:%M:%S").to_string();
+                        self.class_block_lesson += 1;
+                        self.mark_dirty(format!("已添加第{lesson}节"));
+                    }
+                    None => self.set_status(Severity::Error, "课节超出当天范围，未添加"),
                 }
             }
         });
-
-        if added {
-            self.mark_dirty("新节点已添加");
-        } else if changed_existing {
-            self.mark_dirty("时间节点已更新");
-        }
     }
-}
 
-impl eframe::App for WcNoticeApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    /// 只读查看当前内存中的配置（含尚未写盘的修改）
+    fn show_config_viewer(&mut self, ui: &mut Ui) {
+        let text = match toml::to_string_pretty(&self.config) {
+            Ok(text) => text,
+            Err(e) => format!("配置序列化失败: {e}"),
+        };
+
+        ui.horizontal(|ui| {
+            if ui.button("复制到剪贴板").clicked() {
+                ui.ctx().copy_text(text.clone());
+                self.set_status(Severity::Success, "配置已复制到剪贴板");
+            }
+            if self.pending_save.is_some() {
+                ui.label(
+                    RichText::new("含尚未保存的修改")
+                        .size(12.0)
+                        .color(color_warning_text()),
+                );
+            }
+        });
+        ui.add_space(6.0);
+
+        egui::ScrollArea::vertical()
+            .max_height(420.0)
+            .show(ui, |ui| {
+                ui.add(
+                    egui::TextEdit::multiline(&mut text.as_str())
+                        .font(TextStyle::Monospace)
+                        .desired_width(f32::INFINITY),
+                );
+            });
+    }
+
+    /// 打开“添加节点”对话框，并按当前时间表的最后一个节点预填时间、类型和名称
+    fn open_add_dialog(&mut self) {
+        self.new_period_note.clear();
+        self.new_period_solar = None;
+        if self.active_timer_mode() {
+            self.new_period_time = "+10".to_string();
+            self.new_period_kind = PeriodKind::Start;
+            self.new_period_name = "计时器".to_string();
+        } else if let Some(schedule) = self.active_schedule() {
+            let (time, kind, name) = schedule
+                .suggest_next_period(self.class_block_class_mins, self.class_block_break_mins);
+            self.new_period_time = time.format("%H:%M:%S").to_string();
+            self.new_period_kind = kind;
+            self.new_period_name = name;
+        }
+        self.windows.add_period = true;
+    }
+
+    /// 检测线程持续没有心跳时在状态栏报错，恢复后提示一次。
+    /// 系统休眠唤醒后线程要稍等片刻才醒来，持续 `ENGINE_STALL_GRACE` 才视为停止
+    fn check_engine_heartbeat(&mut self) {
+        if !self.engine.is_stalled() {
+            if self.engine_stall.take().is_some_and(|stall| stall.reported) {
+                self.set_status(Severity::Success, "检测线程已恢复");
+            }
+            return;
+        }
+        let stall = self.engine_stall.get_or_insert(EngineStall {
+            since: Instant::now(),
+            reported: false,
+        });
+        if !stall.reported && stall.since.elapsed() >= ENGINE_STALL_GRACE {
+            stall.reported = true;
+            log::error!("检测线程已停止响应");
+            self.set_status(
+                Severity::Error,
+                "检测线程已停止响应，提醒可能不会触发；请重启程序",
+            );
+        }
+    }
+
+    /// 引擎上报的当前节点变化时高亮该行，开启“跟随当前节点”时滚动到该行
+    fn follow_current_period(&mut self) {
+        let current = self
+            .engine
+            .current_period()
+            .and_then(|(schedule_id, index)| {
+                let schedule = self.active_schedule().filter(|s| s.id == schedule_id)?;
+                let period = schedule.periods.get(index)?;
+                Some((
+                    index,
+                    (schedule_id, period.time.clone(), period.name.clone()),
+                ))
+            });
+        let key = current.as_ref().map(|(_, key)| key.clone());
+        if key == self.current_period_key {
+            return;
+        }
+        self.current_period_key = key;
+        if let Some((index, _)) = current {
+            self.current_pulse = Some((index, Instant::now()));
+            if self.config.follow_current_period {
+                self.scroll_to_period = Some(index);
+            }
+        }
+    }
+
+    /// 跨日后重新换算相对日出/日落的节点（仅更新显示，引擎自行换算）
+    fn refresh_solar_times(&mut self) {
+        if self
+            .solar_checked_at
+            .is_some_and(|checked| checked.elapsed() < Duration::from_secs(60))
+        {
+            return;
+        }
+        self.solar_checked_at = Some(Instant::now());
+        self.config.resolve_solar_times(&Local::now());
+    }
+
+    /// 相对日出/日落的时间在当前时间表今天的换算结果，用于添加节点时预览
+    fn solar_preview(&self, solar: SolarTime) -> String {
+        let Some(location) = self.config.location else {
+            return "尚未设置位置（设置 → 日出日落位置）".to_string();
+        };
+        let Some(schedule) = self.active_schedule() else {
+            return String::new();
+        };
+        let now = Local::now();
+        solar
+            .resolve(schedule.zone_now(&now).date(), &location, |at| {
+                schedule.zone_now(&at.with_timezone(&Local))
+            })
+            .map_or_else(
+                || "今天无日出/日落，当天不触发".to_string(),
+                |time| format!("今天为 {}", time.format("%H:%M")),
+            )
+    }
+
+    /// 添加相对日出/日落的节点，时间立即按今天换算
+    fn add_solar_period(&mut self, solar: SolarTime) {
+        let name = self.new_period_name.trim().to_string();
+        if name.is_empty() {
+            self.set_status(Severity::Error, "节点名称不能为空");
+            return;
+        }
+        let Some(schedule) = self.config.active_schedule_mut() else {
+            return;
+        };
+        let mut period = Period::new(schedule::SOLAR_UNRESOLVED, self.new_period_kind, &name);
+        period.set_note(&self.new_period_note);
+        period.solar = Some(solar);
+        schedule.periods.push(period);
+        schedule.sort_periods();
+        self.config.resolve_solar_times(&Local::now());
+        self.windows.add_period = false;
+        if self.config.location.is_some() {
+            self.mark_dirty("新节点已添加");
+        } else {
+            self.mark_dirty_as(
+                Severity::Warning,
+                "新节点已添加，但尚未设置日出日落位置，暂不触发",
+            );
+        }
+    }
+
+    fn active_timer_mode(&self) -> bool {
+        self.active_schedule()
+            .is_some_and(|schedule| schedule.mode == ScheduleMode::Timer)
+    }
+
+    /// 移除计时器模式下已响过的节点
+    fn clear_fired_timers(&mut self) {
+        let removed = self
+            .engine
+            .take_fired_timers()
+            .iter()
+            .filter(|(schedule_id, fired)| self.config.remove_period(*schedule_id, fired))
+            .count();
+        if removed > 0 {
+            self.selected_periods.clear();
+            self.mark_dirty(format!("已移除 {removed} 个响过的计时器"));
+        }
+    }
+
+    /// 在后台线程运行自检，避免试响和通知阻塞界面
+    fn start_selftest(&mut self, ctx: &egui::Context) {
+        if self.selftest_running {
+            return;
+        }
+        self.selftest_running = true;
+        *self.selftest_results.lock().unwrap() = None;
+
+        let engine = Arc::clone(&self.engine);
+        let config = self.config.clone();
+        let results = Arc::clone(&self.selftest_results);
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let checks = selftest::run(&engine, &config);
+            *results.lock().unwrap() = Some(checks);
+            ctx.request_repaint();
+        });
+    }
+
+    fn show_selftest(&mut self, ui: &mut Ui) {
+        let results = self.selftest_results.lock().unwrap().clone();
+        if results.is_some() {
+            self.selftest_running = false;
+        }
+
+        match results {
+            None if self.selftest_running => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("正在检查…（会试响一次铃声并发送一条测试通知）");
+                });
+            }
+            None => {
+                ui.label(
+                    RichText::new("点击“开始自检”检查提醒是否能正常工作").color(color_text_muted()),
+                );
+            }
+            Some(checks) => {
+                for check in &checks {
+                    let (mark, color) = match check.passed {
+                        Some(true) => ("✔", color_success_text()),
+                        Some(false) => ("✖", color_danger_text()),
+                        None => ("–", color_text_muted()),
+                    };
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(mark).strong().color(color));
+                        ui.label(RichText::new(check.name).strong());
+                    });
+                    ui.label(
+                        RichText::new(&check.detail)
+                            .size(12.0)
+                            .color(color_text_muted()),
+                    );
+                    ui.add_space(4.0);
+                }
+            }
+        }
+
+        ui.add_space(6.0);
+        if ui
+            .add_enabled(!self.selftest_running, egui::Button::new("开始自检"))
+            .clicked()
+        {
+            self.start_selftest(ui.ctx());
+        }
+    }
+
+    /// 用引擎的匹配规则推演当前时间表的一天，列出触发时刻与内容
+    fn show_simulation(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("步长").color(color_text_muted()));
+            egui::ComboBox::from_id_salt("simulate_step")
+                .selected_text(format!("{} 秒", self.simulate_step_secs))
+                .show_ui(ui, |ui| {
+                    for secs in [1, 5, 10, 30, 60] {
+                        ui.selectable_value(
+                            &mut self.simulate_step_secs,
+                            secs,
+                            format!("{secs} 秒"),
+                        );
+                    }
+                })
+                .response
+                .on_hover_text(
+                    "步长越大推演越快，触发时刻最多晚一个步长（与检测线程的触发容差相同）",
+                );
+            if ui.button("▶ 开始模拟").clicked() {
+                self.simulation = self.active_schedule().map(|schedule| {
+                    Engine::simulate_day(
+                        schedule,
+                        Duration::from_secs(self.simulate_step_secs),
+                        self.config.day_start_hour,
+                    )
+                });
+            }
+        });
+        ui.add_space(6.0);
+
+        match &self.simulation {
+            None => {
+                let start = self.config.day_start_hour.min(23);
+                ui.label(
+                    RichText::new(format!(
+                        "点击“开始模拟”推演当前时间表从 {start:02}:00 起 24 小时内的触发情况"
+                    ))
+                    .color(color_text_muted()),
+                );
+            }
+            Some(hits) if hits.is_empty() => {
+                ui.label(RichText::new("这一天不会触发任何节点").color(color_text_muted()));
+            }
+            Some(hits) => {
+                ui.label(
+                    RichText::new(format!("共触发 {} 次", hits.len()))
+                        .size(12.0)
+                        .color(color_text_muted()),
+                );
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        for (time, description) in hits {
+                            ui.label(
+                                RichText::new(format!(
+                                    "{}  {description}",
+                                    time.format("%H:%M:%S")
+                                ))
+                                .font(FontId::monospace(12.0)),
+                            );
+                        }
+                    });
+            }
+        }
+    }
+
+    fn open_history_window(&mut self) {
+        self.snapshots = list_snapshots();
+        self.windows.history = true;
+    }
+
+    /// 历史版本列表：每次内容变化的保存都会留下一份快照，可整体恢复
+    fn show_history(&mut self, ui: &mut Ui) {
+        if self.snapshots.is_empty() {
+            ui.label(RichText::new("暂无历史版本").color(color_text_muted()));
+            return;
+        }
+
+        let mut restore: Option<Snapshot> = None;
+        egui::ScrollArea::vertical()
+            .max_height(360.0)
+            .show(ui, |ui| {
+                for snapshot in &self.snapshots {
+                    ui.horizontal(|ui| {
+                        ui.label(snapshot.taken_at.format("%Y-%m-%d %H:%M:%S").to_string());
+                        let summary = match snapshot.schedule_count {
+                            Some(count) => format!("{count} 个时间表"),
+                            None => "无法解析".to_string(),
+                        };
+                        ui.label(RichText::new(summary).size(12.0).color(color_text_muted()));
+                        ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
+                            if ui
+                                .add_enabled(
+                                    snapshot.schedule_count.is_some(),
+                                    egui::Button::new("恢复"),
+                                )
+                                .clicked()
+                            {
+                                restore = Some(snapshot.clone());
+                            }
+                        });
+                    });
+                }
+            });
+
+        if let Some(snapshot) = restore {
+            match read_config_file(&snapshot.path) {
+                Ok(config) => {
+                    self.replace_config(
+                        config,
+                        format!(
+                            "已恢复 {} 的版本",
+                            snapshot.taken_at.format("%Y-%m-%d %H:%M:%S")
+                        ),
+                    );
+                    self.windows.history = false;
+                }
+                Err(e) => self.set_status(Severity::Error, format!("恢复失败: {e}")),
+            }
+        }
+    }
+
+    fn pick_import_file(&mut self) {
+        let Some(file) = FileDialog::new().add_filter("TOML", &["toml"]).pick_file() else {
+            return;
+        };
+        match read_config_file(&file) {
+            Ok(config) if config.schedules.is_empty() => {
+                self.set_status(Severity::Warning, "导入的配置中没有时间表");
+            }
+            Ok(config) => {
+                let file_name = file.file_name().map_or_else(
+                    || file.display().to_string(),
+                    |name| name.to_string_lossy().into_owned(),
+                );
+                self.pending_import = Some(PendingImport { file_name, config });
+            }
+            Err(e) => self.set_status(Severity::Error, format!("导入失败: {e}")),
+        }
+    }
+
+    /// 导入配置：合并（追加时间表，重新分配 id）或替换（整体覆盖当前配置）
+    fn show_import_window(&mut self, ctx: &egui::Context) {
+        let Some(import) = &self.pending_import else {
+            return;
+        };
+
+        let mut open = true;
+        let mut merge = false;
+        let mut replace = false;
+        let mut cancel = false;
+        let count = import.config.schedules.len();
+        let current = self.config.schedules.len();
+        let file_name = import.file_name.clone();
+
+        egui::Window::new("导入配置")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .fixed_size([fit_width(ctx, 380.0), 0.0])
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label(
+                    RichText::new(format!("“{file_name}”中有 {count} 个时间表")).strong(),
+                );
+                ui.label(
+                    RichText::new(format!(
+                        "合并：追加到现有的 {current} 个时间表之后，其余设置不变；\n替换：用导入的配置覆盖全部时间表与设置。"
+                    ))
+                    .color(color_text_muted()),
+                );
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.button("合并").clicked() {
+                        merge = true;
+                    }
+                    if ui
+                        .add(
+                            egui::Button::new(RichText::new("替换").color(color_danger_text()))
+                                .fill(color_danger_fill())
+                                .stroke(Stroke::new(1.0, color_danger_border())),
+                        )
+                        .clicked()
+                    {
+                        replace = true;
+                    }
+                    if ui.button("取消").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if !open || cancel {
+            self.pending_import = None;
+            return;
+        }
+        if merge && let Some(import) = self.pending_import.take() {
+            let added = self.config.merge_from(import.config);
+            self.mark_dirty(format!("已合并导入 {added} 个时间表"));
+        } else if replace && let Some(import) = self.pending_import.take() {
+            self.replace_config(
+                import.config,
+                format!("已用导入的配置替换，共 {count} 个时间表"),
+            );
+        }
+    }
+
+    fn open_config_editor(&mut self) {
+        self.config_editor_text = toml::to_string_pretty(&self.config).unwrap_or_default();
+        self.config_editor_error = None;
+        self.windows.config_editor = true;
+    }
+
+    /// 高级：直接编辑 TOML，解析成功后整体替换当前配置
+    fn show_config_editor(&mut self, ui: &mut Ui) {
+        ui.label(
+            RichText::new("直接修改配置文本，点击“应用”后生效；格式错误时不会应用")
+                .size(12.0)
+                .color(color_text_muted()),
+        );
+        ui.add_space(6.0);
+
+        egui::ScrollArea::vertical()
+            .max_height(380.0)
+            .show(ui, |ui| {
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.config_editor_text)
+                        .font(TextStyle::Monospace)
+                        .code_editor()
+                        .desired_width(f32::INFINITY),
+                );
+            });
+
+        if let Some(error) = &self.config_editor_error {
+            ui.add_space(4.0);
+            ui.label(RichText::new(error).size(12.0).color(color_danger_text()));
+        }
+
+        ui.add_space(6.0);
+        ui.horizontal(|ui| {
+            if ui.button("✔ 应用").clicked() {
+                match toml::from_str::<AppConfig>(&self.config_editor_text) {
+                    Ok(config) => {
+                        self.replace_config(config, "配置已应用");
+                        self.config_editor_error = None;
+                        self.windows.config_editor = false;
+                    }
+                    Err(e) => self.config_editor_error = Some(format!("解析失败: {e}")),
+                }
+            }
+            if ui.button("重新载入当前配置").clicked() {
+                self.open_config_editor();
+            }
+        });
+    }
+
+    fn show_sound_settings(&mut self, ui: &mut Ui) {
+        let mut changed = false;
+
+        let schedule_id = self.sound_schedule_id;
+        let editing_exists =
+            schedule_id.is_some_and(|id| self.config.schedules.iter().any(|s| s.id == id));
+
+        ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
+            if schedule_id.is_none() {
+                ui.label(
+                    RichText::new("没有时间表")
+                        .size(13.0)
+                        .color(color_text_muted()),
+                );
+            } else if !editing_exists {
+                ui.label(
+                    RichText::new("⚠ 该时间表已被删除，无法再编辑其音效")
+                        .size(13.0)
+                        .color(color_danger_text()),
+                );
+            } else if schedule_id != self.config.active_schedule_id {
+                ui.label(
+                    RichText::new("不是当前时间表：修改在切换到该时间表后生效")
+                        .size(13.0)
+                        .color(color_text_muted()),
+                );
+            }
+
+            let sound_checks = &mut self.sound_checks;
+            // 与 schedules 的可变借用错开，绘制完再放回
+            let mut recent_sounds = std::mem::take(&mut self.config.recent_sounds);
+            if let Some(schedule) = self
+                .config
+                .schedules
+                .iter_mut()
+                .find(|schedule| Some(schedule.id) == schedule_id)
+            {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new("音效组合")
+                            .size(14.0)
+                            .strong()
+                            .color(color_text_strong()),
+                    );
+                    let current = SoundPack::detect(&schedule.sound);
+                    let mut selected = current;
+                    egui::ComboBox::from_id_salt(format!("sound_pack_{}", schedule.id))
+                        .selected_text(match &selected {
+                            Some(pack) => pack.label(),
+                            None => "自定义",
+                        })
+                        .show_ui(ui, |ui| {
+                            for pack in SoundPack::ALL {
+                                ui.selectable_value(&mut selected, Some(pack), pack.label());
+                            }
+                        })
+                        .response
+                        .on_hover_text("一次设置开始/结束/预备音效，之后仍可逐个修改");
+                    if selected != current
+                        && let Some(pack) = selected
+                    {
+                        pack.apply(&mut schedule.sound);
+                        changed = true;
+                    }
+                });
+                ui.add_space(6.0);
+                changed |= draw_sound_source_editor(
+                    ui,
+                    "开始音效",
+                    &format!("sound_start_{}", schedule.id),
+                    &mut schedule.sound.start,
+                    PeriodKind::Start,
+                    sound_checks,
+                    &mut recent_sounds,
+                );
+                ui.add_space(6.0);
+                changed |= draw_sound_source_editor(
+                    ui,
+                    "结束音效",
+                    &format!("sound_end_{}", schedule.id),
+                    &mut schedule.sound.end,
+                    PeriodKind::End,
+                    sound_checks,
+                    &mut recent_sounds,
+                );
+                ui.add_space(6.0);
+                changed |= draw_sound_source_editor(
+                    ui,
+                    "预备音效",
+                    &format!("sound_prepare_{}", schedule.id),
+                    &mut schedule.sound.prepare,
+                    PeriodKind::Prepare,
+                    sound_checks,
+                    &mut recent_sounds,
+                );
+                ui.add_space(6.0);
+                let mut use_day_end = schedule.sound.day_end.is_some();
+                if ui
+                    .checkbox(&mut use_day_end, "最后一个结束节点使用放学铃")
+                    .on_hover_text("今日最晚的启用结束节点改用单独的音效")
+                    .changed()
+                {
+                    schedule.sound.day_end =
+                        use_day_end.then(|| SoundSource::default_for_kind(PeriodKind::End));
+                    changed = true;
+                }
+                if let Some(day_end) = schedule.sound.day_end.as_mut() {
+                    changed |= draw_sound_source_editor(
+                        ui,
+                        "放学铃",
+                        &format!("sound_day_end_{}", schedule.id),
+                        day_end,
+                        PeriodKind::End,
+                        sound_checks,
+                        &mut recent_sounds,
+                    );
+                }
+            }
+
+            ui.add_space(6.0);
+            ui.separator();
+            let mut hourly = self.config.hourly_chime.is_some();
+            if ui
+                .checkbox(&mut hourly, "整点报时")
+                .on_hover_text("与时间表无关，每小时整点轻响一次，确认程序在运行；整点恰有节点时只响节点，暂停、锁屏与会议中同样不响，不发通知")
+                .changed()
+            {
+                self.config.hourly_chime = hourly.then_some(SoundSource::Builtin(BuiltinSound::Soft));
+                changed = true;
+            }
+            if let Some(chime) = self.config.hourly_chime.as_mut() {
+                changed |= draw_sound_source_editor(
+                    ui,
+                    "报时音效",
+                    "sound_hourly_chime",
+                    chime,
+                    PeriodKind::Start,
+                    sound_checks,
+                    &mut recent_sounds,
+                );
+            }
+            self.config.recent_sounds = recent_sounds;
+
+            ui.add_space(8.0);
+            if ui
+                .add_enabled(editing_exists, egui::Button::new("▶ 立即测试该时间表"))
+                .on_hover_text("按下一个节点（今日已无节点时取第一个）立即响铃并通知")
+                .clicked()
+                && let Some(id) = schedule_id
+            {
+                self.test_fire_schedule(id);
+            }
+            match &self.sound_test {
+                Some(test) => {
+                    let done = test.total - test.queue.len();
+                    if ui
+                        .button(format!("⏹ 停止测试（{done}/{}）", test.total))
+                        .clicked()
+                    {
+                        self.cancel_sound_test();
+                    }
+                }
+                None => {
+                    if ui
+                        .add_enabled(editing_exists, egui::Button::new("🔊 测试全部音效"))
+                        .on_hover_text("依次试听该时间表的每个音效，无法播放的会在状态栏列出")
+                        .clicked()
+                        && let Some(id) = schedule_id
+                    {
+                        self.start_sound_test(id);
+                    }
+                }
+            }
+        });
+
+        if changed {
+            self.mark_dirty("音效设置已保存");
+        }
+    }
+
+    fn start_sound_test(&mut self, schedule_id: u64) {
+        let Some(sound) = self
+            .config
+            .schedules
+            .iter()
+            .find(|schedule| schedule.id == schedule_id)
+            .map(|schedule| schedule.sound.clone())
+        else {
+            self.set_status(Severity::Info, "时间表已删除，无可测试的音效");
+            return;
+        };
+        let mut queue = VecDeque::from([
+            ("开始音效", sound.start),
+            ("结束音效", sound.end),
+            ("预备音效", sound.prepare),
+        ]);
+        queue.extend(sound.day_end.map(|day_end| ("放学铃", day_end)));
+        self.sound_test = Some(SoundTest {
+            total: queue.len(),
+            queue,
+            next_at: None,
+            failures: Vec::new(),
+        });
+    }
+
+    fn cancel_sound_test(&mut self) {
+        if self.sound_test.take().is_some() {
+            notifier::stop_playback();
+            self.set_status(Severity::Info, "音效测试已取消");
+        }
+    }
+
+    /// 开始一次视觉提醒，并让任务栏按钮闪动（窗口不在前台时也能注意到）
+    fn start_visual_alert(&mut self, ctx: &egui::Context) {
+        self.visual_alert_started = Some(Instant::now());
+        ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(
+            egui::UserAttentionType::Critical,
+        ));
+    }
+
+    /// 在所有窗口之上绘制视觉提醒的着色层：默认闪烁三次并逐渐淡出，
+    /// 开启“减少界面动画”时改为不闪烁的静态着色
+    fn show_visual_alert(&mut self, ctx: &egui::Context) {
+        let Some(started) = self.visual_alert_started else {
+            return;
+        };
+        let alert = &self.config.visual_alert;
+        let duration = Duration::from_millis(alert.duration_ms.max(100));
+        let elapsed = started.elapsed();
+        if elapsed >= duration {
+            self.visual_alert_started = None;
+            ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(
+                egui::UserAttentionType::Reset,
+            ));
+            return;
+        }
+        let progress = elapsed.as_secs_f32() / duration.as_secs_f32();
+        let strength = if self.config.reduce_motion {
+            1.0
+        } else {
+            let pulse = 0.5 + 0.5 * (progress * 3.0 * std::f32::consts::TAU).cos();
+            pulse * (1.0 - progress)
+        };
+        let [r, g, b] = alert.color;
+        let fill = Color32::from_rgba_unmultiplied(r, g, b, (strength * 140.0) as u8);
+        ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("visual_alert"),
+        ))
+        .rect_filled(ctx.screen_rect(), 0.0, fill);
+        ctx.request_repaint();
+    }
+
+    /// 每帧推进“测试全部音效”：等上一段播完并间隔片刻后试听下一段，结束时汇总结果
+    fn advance_sound_test(&mut self) {
+        let Some(test) = self.sound_test.as_mut() else {
+            return;
+        };
+        match test.next_at {
+            None => {
+                if !notifier::is_playing() {
+                    test.next_at = Some(Instant::now() + SOUND_TEST_GAP);
+                }
+                return;
+            }
+            Some(at) if Instant::now() < at => return,
+            Some(_) => {}
+        }
+
+        let Some((label, source)) = test.queue.pop_front() else {
+            let failures = std::mem::take(&mut test.failures);
+            self.sound_test = None;
+            if failures.is_empty() {
+                self.set_status(Severity::Success, "音效测试完成，全部可以播放");
+            } else {
+                self.set_status(
+                    Severity::Error,
+                    format!("音效测试完成，无法播放：{}", failures.join("；")),
+                );
+            }
+            return;
+        };
+
+        let progress = format!("{}/{}", test.total - test.queue.len(), test.total);
+        let (severity, msg) = match validate_sound(&source) {
+            Err(e) => {
+                log::warn!("音效测试：{} 不可用: {}", label, e);
+                test.failures.push(format!("{label}（{e}）"));
+                (Severity::Error, format!("音效测试 {progress}：{label} {e}"))
+            }
+            Ok(_) => {
+                let slots = SoundSlots {
+                    start: source,
+                    ..SoundSlots::default()
+                };
+                let outcome = notifier::play_sound_for_period(
+                    PeriodKind::Start,
+                    &slots,
+                    Duration::ZERO,
+                    false,
+                );
+                test.next_at = None;
+                match outcome.warning {
+                    Some(warning) => {
+                        test.failures.push(format!("{label}（{warning}）"));
+                        (
+                            Severity::Warning,
+                            format!("音效测试 {progress}：{label} {warning}"),
+                        )
+                    }
+                    None => (
+                        Severity::Info,
+                        format!("音效测试 {progress}：正在试听{label}"),
+                    ),
+                }
+            }
+        };
+        self.set_status(severity, msg);
+    }
+
+    fn test_fire_schedule(&mut self, schedule_id: u64) {
+        let target = self
+            .config
+            .schedules
+            .iter()
+            .find(|schedule| schedule.id == schedule_id)
+            .and_then(|schedule| {
+                let now = schedule.zone_now(&Local::now()).time();
+                schedule
+                    .next_period(&now)
+                    .or_else(|| {
+                        schedule
+                            .periods
+                            .iter()
+                            .find(|period| period.enabled && period.kind.fires())
+                    })
+                    .map(|period| (period.clone(), schedule.sound_for(period)))
+            });
+
+        match target {
+            Some((period, slots)) => {
+                // 试响使用检测线程的设置（音量、闪屏等），先同步刚做的修改
+                self.flush_engine_sync(true);
+                self.engine.fire_period(&period, &slots);
+                self.set_status(
+                    Severity::Success,
+                    format!("已试响: {} - {}", period.name, period.kind.label()),
+                );
+            }
+            None => self.set_status(Severity::Info, "该时间表没有可试响的节点"),
+        }
+    }
+
+    /// 时间轴视图：把开始→结束配对画成色块，纵轴从最早节点到最晚节点，红线为当前时间。
+    /// 点击色块切回列表并滚动到对应的开始节点。
+    fn show_timeline(&mut self, ui: &mut Ui, now: NaiveTime) {
+        let Some(schedule) = self.active_schedule() else {
+            return;
+        };
+        let blocks = timeline_blocks(schedule);
+        let Some((first, last)) = schedule.stats().2 else {
+            card_no_title(ui, |ui| {
+                ui.label(RichText::new("当前时间表没有可显示的节点").color(color_text_muted()));
+            });
+            return;
+        };
+
+        // 上下各留半小时，按整点对齐刻度
+        let axis_start = (minutes_of(first) - 30).max(0) / 60 * 60;
+        let axis_end = ((minutes_of(last) + 30).min(24 * 60) + 59) / 60 * 60;
+        let y_of = |rect: egui::Rect, minutes: f32| {
+            rect.top() + (minutes - axis_start as f32) * TIMELINE_PX_PER_MINUTE
+        };
+
+        let mut clicked = None;
+        card_no_title(ui, |ui| {
+            let height = (axis_end - axis_start) as f32 * TIMELINE_PX_PER_MINUTE;
+            let (rect, _) = ui.allocate_exact_size(
+                egui::vec2(ui.available_width(), height),
+                egui::Sense::hover(),
+            );
+            let painter = ui.painter_at(rect.expand(2.0));
+            let block_left = rect.left() + TIMELINE_AXIS_WIDTH;
+
+            for hour in (axis_start / 60)..=(axis_end / 60) {
+                let y = y_of(rect, (hour * 60) as f32);
+                painter.line_segment(
+                    [egui::pos2(block_left, y), egui::pos2(rect.right(), y)],
+                    Stroke::new(1.0, color_border()),
+                );
+                painter.text(
+                    egui::pos2(rect.left(), y),
+                    egui::Align2::LEFT_CENTER,
+                    format!("{hour:02}:00"),
+                    FontId::monospace(12.0),
+                    color_text_muted(),
+                );
+            }
+
+            for block in &blocks {
+                let top = y_of(rect, minutes_of_f32(block.start));
+                let bottom = y_of(rect, minutes_of_f32(block.end)).max(top + 4.0);
+                let block_rect = egui::Rect::from_min_max(
+                    egui::pos2(block_left + 4.0, top),
+                    egui::pos2(rect.right() - 4.0, bottom),
+                );
+                let is_current = block.start <= now && now < block.end;
+                let (fill, border) = if block.end <= now {
+                    (color_period_past_fill(), color_period_past_border())
+                } else if block.marker {
+                    (color_period_marker_fill(), color_period_marker_border())
+                } else if is_current {
+                    (
+                        color_period_start_current_fill(),
+                        color_period_start_current_border(),
+                    )
+                } else {
+                    (color_period_start_fill(), color_period_start_border())
+                };
+                painter.rect(
+                    block_rect,
+                    6,
+                    fill,
+                    Stroke::new(1.0, border),
+                    egui::StrokeKind::Inside,
+                );
+                painter.text(
+                    block_rect.left_top() + egui::vec2(8.0, 4.0),
+                    egui::Align2::LEFT_TOP,
+                    format!(
+                        "{}  {}–{}",
+                        block.name,
+                        block.start.format("%H:%M"),
+                        block.end.format("%H:%M")
+                    ),
+                    FontId::proportional(12.0),
+                    color_text_strong(),
+                );
+
+                let response = ui
+                    .interact(
+                        block_rect,
+                        ui.id().with(("timeline_block", block.index)),
+                        egui::Sense::click(),
+                    )
+                    .on_hover_text("点击在列表中定位");
+                if response.clicked() {
+                    clicked = Some(block.index);
+                }
+            }
+
+            let now_minutes = minutes_of_f32(now);
+            if now_minutes >= axis_start as f32 && now_minutes <= axis_end as f32 {
+                let y = y_of(rect, now_minutes);
+                painter.line_segment(
+                    [egui::pos2(block_left, y), egui::pos2(rect.right(), y)],
+                    Stroke::new(2.0, color_danger_text()),
+                );
+            }
+        });
+
+        if let Some(index) = clicked {
+            self.timeline_view = false;
+            self.scroll_to_period = Some(index);
+        }
+    }
+
+    fn show_period_editor(&mut self, ui: &mut Ui, now: NaiveTime) {
+        let mut duplicated = false;
+        let mut changed_existing = false;
+        let mut batch_msg: Option<(Severity, String)> = None;
+        let mut shift_failed = false;
+        let use_spinner = self.config.time_spinner;
+        let snap_minutes = self.config.snap_minutes;
+        let scroll_to = self.scroll_to_period.take();
+        let mut follow_current = self.config.follow_current_period;
+        let mut uncommitted_edit = self.uncommitted_text_edit;
+
+        card_no_title(ui, |ui| {
+            // "+" 按钮居中，点击后打开弹窗
+            ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+                if ui
+                    .add(
+                        egui::Button::new(
+                            RichText::new("  +  ")
+                                .size(20.0)
+                                .strong()
+                                .color(color_text_strong()),
+                        )
+                        .fill(color_chip())
+                        .stroke(Stroke::new(1.5, color_border()))
+                        .corner_radius(8)
+                        .min_size(egui::vec2(48.0, 36.0)),
+                    )
+                    .on_hover_text("添加时间节点")
+                    .clicked()
+                {
+                    self.open_add_dialog();
+                }
+                if ui
+                    .small_button("快速添加课节")
+                    .on_hover_text("按开始时间、课长和课间一次生成上课/下课/下一节上课节点")
+                    .clicked()
+                {
+                    self.windows.class_block = true;
+                }
+                if ui
+                    .small_button("批量编辑")
+                    .on_hover_text("以文本形式编辑全部节点，可直接粘贴多行")
+                    .clicked()
+                {
+                    self.open_bulk_editor();
+                }
+                if ui
+                    .small_button("模拟一天")
+                    .on_hover_text("快速推演当前时间表一天内会在何时触发哪些节点，不响铃")
+                    .clicked()
+                {
+                    self.simulation = None;
+                    self.windows.simulate = true;
+                }
+            });
+
+            ui.add_space(8.0);
+
+            let min_gap_minutes = self.config.min_gap_minutes;
+            let reduce_motion = self.config.reduce_motion;
+            let rows = RowMetrics::for_density(self.config.density);
+            let pulse = self.current_pulse.and_then(|(idx, started)| {
+                let progress = started.elapsed().as_secs_f32() / CURRENT_PULSE.as_secs_f32();
+                (progress < 1.0).then_some((idx, progress))
+            });
+            if pulse.is_some() {
+                ui.ctx().request_repaint();
+            }
+            let follow_current = &mut follow_current;
+            let selected_periods = &mut self.selected_periods;
+            let shift_minutes = &mut self.batch_shift_minutes;
+            if let Some(schedule) = self.config.active_schedule_mut() {
+                if schedule.periods.is_empty() {
+                    ui.label(
+                        RichText::new("当前时间表没有节点，请先添加开始/结束节点")
+                            .color(color_text_muted()),
+                    );
+                    return;
+                }
+
+                // 每帧按当前时间重新检查，编辑时间时即时更新
+                for warning in schedule.validate(min_gap_minutes) {
+                    ui.label(
+                        RichText::new(format!("⚠ {warning}"))
+                            .size(12.0)
+                            .color(color_warning_text()),
+                    );
+                }
+
+                selected_periods.retain(|idx| *idx < schedule.periods.len());
+                ui.horizontal(|ui| {
+                    let all_selected = selected_periods.len() == schedule.periods.len();
+                    if ui
+                        .button(if all_selected { "全不选" } else { "全选" })
+                        .clicked()
+                    {
+                        if all_selected {
+                            selected_periods.clear();
+                        } else {
+                            selected_periods.extend(0..schedule.periods.len());
+                        }
+                    }
+
+                    let has_selection = !selected_periods.is_empty();
+                    ui.label(
+                        RichText::new(format!("已选 {} 个", selected_periods.len()))
+                            .size(12.0)
+                            .color(color_text_muted()),
+                    );
+                    for (label, verb, enabled) in
+                        [("批量启用", "启用", true), ("批量停用", "停用", false)]
+                    {
+                        if ui
+                            .add_enabled(has_selection, egui::Button::new(label))
+                            .clicked()
+                        {
+                            for idx in selected_periods.iter() {
+                                schedule.periods[*idx].enabled = enabled;
+                            }
+                            batch_msg = Some((
+                                Severity::Success,
+                                format!("已{verb} {} 个节点", selected_periods.len()),
+                            ));
+                        }
+                    }
+                    if ui
+                        .add_enabled(
+                            has_selection,
+                            egui::Button::new(RichText::new("删除选中").color(color_danger_text()))
+                                .fill(color_danger_fill())
+                                .stroke(Stroke::new(1.0, color_danger_border())),
+                        )
+                        .clicked()
+                    {
+                        // 从大到小删除，避免下标前移
+                        let mut indices: Vec<usize> = selected_periods.drain().collect();
+                        indices.sort_unstable_by(|a, b| b.cmp(a));
+                        for idx in &indices {
+                            schedule.periods.remove(*idx);
+                        }
+                        batch_msg = Some((
+                            Severity::Success,
+                            format!("已删除 {} 个节点", indices.len()),
+                        ));
+                    }
+
+                    ui.add(
+                        egui::DragValue::new(shift_minutes)
+                            .range(-720..=720)
+                            .suffix(" 分钟"),
+                    )
+                    .on_hover_text("正数推后，负数提前");
+                    if ui
+                        .add_enabled(
+                            has_selection && *shift_minutes != 0,
+                            egui::Button::new("平移选中"),
+                        )
+                        .on_hover_text("只平移勾选的节点，如下午整体推迟而上午不变")
+                        .clicked()
+                    {
+                        let warnings_before = schedule.validate(min_gap_minutes).len();
+                        let indices: Vec<usize> = selected_periods.iter().copied().collect();
+                        match schedule.shift_indices(&indices, *shift_minutes) {
+                            Some(moved) => {
+                                // 排序后下标变化，勾选跟随被平移的节点，便于连续调整
+                                selected_periods.clear();
+                                selected_periods.extend(moved.iter().copied());
+                                let verb = if *shift_minutes > 0 {
+                                    "推后"
+                                } else {
+                                    "提前"
+                                };
+                                let msg = format!(
+                                    "已将 {} 个节点{verb} {} 分钟",
+                                    moved.len(),
+                                    shift_minutes.abs()
+                                );
+                                batch_msg = Some(
+                                    if schedule.validate(min_gap_minutes).len() > warnings_before {
+                                        (Severity::Warning, format!("{msg}，请检查列表上方的警告"))
+                                    } else {
+                                        (Severity::Success, msg)
+                                    },
+                                );
+                            }
+                            None => shift_failed = true,
+                        }
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
+                        ui.checkbox(follow_current, "跟随当前节点")
+                            .on_hover_text("当前节点变化时自动滚动到该行；手动浏览列表时可关闭");
+                    });
+                });
+                ui.add_space(6.0);
+
+                let mut delete_index: Option<usize> = None;
+                let mut duplicate_index: Option<usize> = None;
+
+                for (idx, period) in schedule.periods.iter_mut().enumerate() {
+                    let (row_fill, row_border) = period_row_style(period, &now);
+                    // 刚成为当前节点的行边框加粗闪烁（减少动画时保持加粗不闪烁）
+                    let border_width = match pulse {
+                        Some((pulse_idx, progress)) if pulse_idx == idx => {
+                            let strength = if reduce_motion {
+                                1.0
+                            } else {
+                                0.5 + 0.5 * (progress * 2.0 * std::f32::consts::TAU).cos()
+                            };
+                            1.0 + 2.0 * strength
+                        }
+                        _ => 1.0,
+                    };
+                    let row = egui::Frame::new()
+                        .fill(row_fill)
+                        .stroke(Stroke::new(border_width, row_border))
+                        .corner_radius(8)
+                        .inner_margin(rows.margin)
+                        .show(ui, |ui| {
+                            let row_width = ui.available_width();
+                            ui.allocate_ui_with_layout(
+                                egui::vec2(row_width, rows.min_height),
+                                egui::Layout::left_to_right(egui::Align::Center)
+                                    .with_main_justify(false),
+                                |ui| {
+                                    let mut selected = selected_periods.contains(&idx);
+                                    if ui
+                                        .checkbox(&mut selected, "")
+                                        .on_hover_text("选中（批量操作）")
+                                        .changed()
+                                    {
+                                        if selected {
+                                            selected_periods.insert(idx);
+                                        } else {
+                                            selected_periods.remove(&idx);
+                                        }
+                                    }
+
+                                    if ui.checkbox(&mut period.enabled, "启用").changed() {
+                                        changed_existing = true;
+                                    }
+
+                                    if let Some(solar) = period.solar {
+                                        // 相对日出/日落的节点时间每天换算，不能直接编辑
+                                        ui.add_sized(
+                                            [PERIOD_TIME_WIDTH, 24.0],
+                                            egui::Label::new(format!("☀ {}", period.time)),
+                                        )
+                                        .on_hover_text(
+                                            format!("{}，每天按日出日落时间换算", solar.describe()),
+                                        );
+                                    } else if use_spinner {
+                                        if draw_time_spinner(ui, &mut period.time) {
+                                            changed_existing = true;
+                                        }
+                                    } else {
+                                        // 输入无效时整格标红，提示用户尚未生效
+                                        let valid = schedule::is_valid_time(&period.time);
+                                        let mut time_edit =
+                                            egui::TextEdit::singleline(&mut period.time);
+                                        if !valid {
+                                            time_edit = time_edit
+                                                .text_color(color_danger_text())
+                                                .background_color(color_danger_fill());
+                                        }
+                                        let time_response =
+                                            ui.add_sized([PERIOD_TIME_WIDTH, 24.0], time_edit);
+                                        if time_response.changed() {
+                                            uncommitted_edit = Some(time_response.id);
+                                        }
+                                        // 失去焦点时规范化时间格式（并按设置吸附到分钟网格）
+                                        if time_response.lost_focus() {
+                                            if let Some(normalized) =
+                                                schedule::normalize_time_snapped(
+                                                    &period.time,
+                                                    snap_minutes,
+                                                )
+                                                && normalized != period.time
+                                            {
+                                                period.time = normalized;
+                                                changed_existing = true;
+                                            }
+                                            // 如果格式无效，保留原值（用户可继续编辑）
+                                        }
+                                    }
+
+                                    let mut kind = period.kind;
+                                    egui::ComboBox::from_id_salt(format!(
+                                        "period_kind_{}_{}",
+                                        schedule.id, idx
+                                    ))
+                                    .selected_text(kind.label())
+                                    .width(PERIOD_KIND_WIDTH)
+                                    .show_ui(ui, |ui| {
+                                        for option in PeriodKind::ALL {
+                                            ui.selectable_value(&mut kind, option, option.label());
+                                        }
+                                    });
+
+                                    if kind != period.kind {
+                                        period.kind = kind;
+                                        changed_existing = true;
+                                    }
+
+                                    let reserved_tail = PERIOD_NOTE_WIDTH
+                                        + PERIOD_SILENT_WIDTH
+                                        + PERIOD_STATUS_WIDTH
+                                        + PERIOD_DUPLICATE_WIDTH
+                                        + PERIOD_DELETE_WIDTH
+                                        + ui.spacing().item_spacing.x * 5.0;
+                                    let name_width = (ui.available_width() - reserved_tail)
+                                        .max(PERIOD_NAME_MIN_WIDTH);
+
+                                    let name_response = ui.add_sized(
+                                        [name_width, 24.0],
+                                        egui::TextEdit::singleline(&mut period.name)
+                                            .char_limit(MAX_NAME_CHARS),
+                                    );
+                                    if name_response.changed() {
+                                        uncommitted_edit = Some(name_response.id);
+                                    }
+                                    // 输入框只显示能放下的部分，悬停显示全名
+                                    if !name_response.has_focus()
+                                        && truncate_to_width(
+                                            ui,
+                                            &period.name,
+                                            &egui::TextStyle::Body.resolve(ui.style()),
+                                            name_width - 8.0,
+                                        ) != period.name
+                                    {
+                                        name_response.on_hover_text(&period.name);
+                                    }
+
+                                    let note_icon = if period.note.is_some() {
+                                        "📝"
+                                    } else {
+                                        "🗒"
+                                    };
+                                    ui.allocate_ui(egui::vec2(PERIOD_NOTE_WIDTH, 24.0), |ui| {
+                                        ui.menu_button(note_icon, |ui| {
+                                            ui.label(
+                                                RichText::new("备注（显示在通知中）")
+                                                    .size(12.0)
+                                                    .color(color_text_muted()),
+                                            );
+                                            let mut note = period.note.clone().unwrap_or_default();
+                                            let note_response = ui.add(
+                                                egui::TextEdit::singleline(&mut note)
+                                                    .desired_width(220.0),
+                                            );
+                                            if note_response.changed() {
+                                                period.note = (!note.is_empty()).then_some(note);
+                                                uncommitted_edit = Some(note_response.id);
+                                            }
+                                        });
+                                    });
+
+                                    let silent_icon = if period.silent { "🔇" } else { "🔔" };
+                                    if ui
+                                        .add_sized(
+                                            [PERIOD_SILENT_WIDTH, 24.0],
+                                            egui::SelectableLabel::new(period.silent, silent_icon),
+                                        )
+                                        .on_hover_text(if period.silent {
+                                            "静音节点：只发送通知，不响铃（点击恢复响铃）"
+                                        } else {
+                                            "点击设为静音节点：只发送通知，不响铃"
+                                        })
+                                        .clicked()
+                                    {
+                                        period.silent = !period.silent;
+                                        changed_existing = true;
+                                    }
+
+                                    ui.add_sized(
+                                        [PERIOD_STATUS_WIDTH, 24.0],
+                                        egui::Label::new(
+                                            RichText::new(period_runtime_state(period, &now))
+                                                .size(12.0)
+                                                .color(color_text_muted()),
+                                        ),
+                                    );
+
+                                    if ui
+                                        .add_sized(
+                                            [PERIOD_DUPLICATE_WIDTH, 24.0],
+                                            egui::Button::new("复制"),
+                                        )
+                                        .on_hover_text("在下方插入一个相同的节点，便于修改")
+                                        .clicked()
+                                    {
+                                        duplicate_index = Some(idx);
+                                    }
+
+                                    if ui
+                                        .add_sized(
+                                            [PERIOD_DELETE_WIDTH, 24.0],
+                                            egui::Button::new(
+                                                RichText::new("删除").color(color_danger_text()),
+                                            )
+                                            .fill(color_danger_fill())
+                                            .stroke(Stroke::new(1.0, color_danger_border())),
+                                        )
+                                        .clicked()
+                                    {
+                                        delete_index = Some(idx);
+                                    }
+                                },
+                            );
+                        });
+                    if scroll_to == Some(idx) {
+                        row.response.scroll_to_me(Some(Align::Center));
+                    }
+                    if let Some(note) = &period.note {
+                        row.response.on_hover_text(note);
+                    }
+                    ui.add_space(rows.gap);
+                }
+
+                if let Some(idx) = delete_index {
+                    schedule.periods.remove(idx);
+                    selected_periods.clear();
+                    changed_existing = true;
+                }
+
+                // 副本紧跟原节点插入（同一时间排序时保持在其后），默认启用
+                if let Some(idx) = duplicate_index {
+                    let mut copy = schedule.periods[idx].clone();
+                    copy.enabled = true;
+                    schedule.periods.insert(idx + 1, copy);
+                    selected_periods.clear();
+                    schedule.sort_periods();
+                    duplicated = true;
+                }
+
+                // 文本框逐字修改不立即保存，焦点离开（含关闭备注菜单）时才提交
+                if let Some(id) = uncommitted_edit
+                    && ui.ctx().memory(|memory| memory.focused()) != Some(id)
+                {
+                    uncommitted_edit = None;
+                    changed_existing = true;
+                }
+
+                if changed_existing {
+                    // 改时间导致重新排序时下标会变，清空勾选以免误操作
+                    let sorted = schedule
+                        .periods
+                        .windows(2)
+                        .all(|pair| pair[0].time <= pair[1].time);
+                    if !sorted {
+                        selected_periods.clear();
+                    }
+                    schedule.sort_periods();
+                }
+            }
+        });
+
+        self.uncommitted_text_edit = uncommitted_edit;
+        if follow_current != self.config.follow_current_period {
+            self.config.follow_current_period = follow_current;
+            self.mark_dirty("设置已保存");
+        }
+        if shift_failed {
+            self.set_status(Severity::Error, "有节点会跨过午夜，未平移");
+        }
+        if let Some((severity, msg)) = batch_msg {
+            self.mark_dirty_as(severity, msg);
+        } else if duplicated {
+            self.mark_dirty("节点已复制");
+        } else if changed_existing {
+            self.mark_dirty("时间节点已更新");
+        }
+    }
+}
+
+impl eframe::App for WcNoticeApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         if !self.theme_applied {
-            apply_theme(ctx);
+            apply_theme(ctx, self.config.reduce_motion, self.config.density);
+            ctx.set_zoom_factor(
+                self.config
+                    .ui_scale
+                    .clamp(*UI_SCALE_RANGE.start(), *UI_SCALE_RANGE.end()),
+            );
             self.theme_applied = true;
         }
 
+        self.flush_engine_sync(false);
         self.flush_pending_save();
         self.handle_tray_events(ctx);
         self.handle_window_lifecycle(ctx);
+        self.run_position_check(ctx);
 
-        for event in self.engine.take_status_events() {
-            self.status_msg = event;
+        for (severity, event) in self.engine.take_status_events() {
+            self.set_status(severity, event);
         }
+        self.check_engine_heartbeat();
+        self.follow_current_period();
+        if self.engine.take_visual_alert() {
+            self.start_visual_alert(ctx);
+        }
+        self.clear_fired_timers();
+        self.advance_sound_test();
+        self.refresh_solar_times();
 
         self.sync_rename_name_from_active();
 
-        let now = Local::now().naive_local().time();
+        let now = self.schedule_now();
+        self.sync_taskbar_progress(now);
+        self.show_visual_alert(ctx);
+
+        if self.kiosk_mode {
+            self.show_kiosk(ctx, now);
+            self.show_exit_confirm_window(ctx);
+            ctx.request_repaint_after(Duration::from_millis(200));
+            return;
+        }
+
         self.show_top_panel(ctx, now);
 
         // 底部状态栏（必须在 CentralPanel 之前声明）
         let status_msg_clone = self.status_msg.clone();
+        let status_severity = self.status_severity;
         let cfg_path = crate::config::config_path().display().to_string();
         egui::TopBottomPanel::bottom("status_bar")
             .frame(
@@ -875,11 +3239,16 @@ impl eframe::App for WcNoticeApp {
             .show(ctx, |ui| {
                 ui.horizontal(|ui| {
                     // 左侧：状态信息
-                    ui.label(
-                        RichText::new(&status_msg_clone)
+                    if !status_msg_clone.is_empty() {
+                        ui.label(
+                            RichText::new(format!(
+                                "{} {status_msg_clone}",
+                                severity_icon(status_severity)
+                            ))
                             .font(FontId::proportional(11.0))
-                            .color(status_color(&status_msg_clone)),
-                    );
+                            .color(status_color(status_severity)),
+                        );
+                    }
 
                     ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
                         // 右侧：配置路径（截短显示，hover 显示完整路径）
@@ -894,93 +3263,922 @@ impl eframe::App for WcNoticeApp {
                         }
                     });
                 });
-            });
+            });
+
+        // 切换/重命名时间表弹窗
+        let mut show_schedule_window = self.windows.schedule;
+        if show_schedule_window {
+            egui::Window::new("切换 / 重命名时间表")
+                .open(&mut show_schedule_window)
+                .fixed_size([fit_width(ctx, 480.0), 0.0])
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    self.show_schedule_management(ui);
+                });
+        }
+        self.windows.schedule = show_schedule_window;
+
+        // 新建时间表弹窗
+        let mut show_new_schedule_window = self.windows.new_schedule;
+        if show_new_schedule_window {
+            egui::Window::new("新建时间表")
+                .open(&mut show_new_schedule_window)
+                .fixed_size([fit_width(ctx, 400.0), 0.0])
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    self.show_new_schedule(ui);
+                });
+        }
+        self.windows.new_schedule = show_new_schedule_window;
+
+        // 音效设置弹窗
+        let mut show_sound_window = self.windows.sound;
+        if show_sound_window {
+            // 重启后恢复打开的窗口时尚未记录编辑对象，取当前时间表
+            if self.sound_schedule_id.is_none() {
+                self.sound_schedule_id = self.config.active_schedule_id;
+            }
+            let title = match self.sound_schedule_id {
+                Some(id) => match self.config.schedules.iter().find(|s| s.id == id) {
+                    Some(schedule) => format!("音效设置 - {}", schedule.name),
+                    None => "音效设置 - （时间表已删除）".to_string(),
+                },
+                None => "音效设置".to_string(),
+            };
+            // 标题随时间表名变化，固定 id 以免窗口位置重置
+            egui::Window::new(title)
+                .id(egui::Id::new("sound_window"))
+                .open(&mut show_sound_window)
+                .fixed_size([fit_width(ctx, 480.0), 0.0])
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    self.show_sound_settings(ui);
+                });
+        }
+        if !show_sound_window {
+            self.sound_schedule_id = None;
+        }
+        self.windows.sound = show_sound_window;
+
+        // 临时提醒弹窗
+        let mut show_oneshot_window = self.windows.oneshot;
+        if show_oneshot_window {
+            egui::Window::new("临时提醒")
+                .open(&mut show_oneshot_window)
+                .fixed_size([fit_width(ctx, 400.0), 0.0])
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    self.show_oneshot_editor(ui);
+                });
+        }
+        self.windows.oneshot = show_oneshot_window;
+
+        // 快速添加课节弹窗
+        let mut show_class_block_window = self.windows.class_block;
+        if show_class_block_window {
+            egui::Window::new("快速添加课节")
+                .open(&mut show_class_block_window)
+                .fixed_size([fit_width(ctx, 400.0), 0.0])
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    self.show_class_block_editor(ui);
+                });
+        }
+        self.windows.class_block = show_class_block_window;
 
-        // 切换/重命名时间表弹窗
-        let mut show_schedule_window = self.show_schedule_window;
-        if show_schedule_window {
-            egui::Window::new("切换 / 重命名时间表")
-                .open(&mut show_schedule_window)
-                .fixed_size([480.0, 0.0])
+        // 批量编辑弹窗（应用成功后由 show_bulk_editor 关闭）
+        if self.windows.bulk_edit {
+            let mut open = true;
+            egui::Window::new("批量编辑节点")
+                .open(&mut open)
+                .default_size([480.0, 440.0])
                 .collapsible(false)
                 .show(ctx, |ui| {
-                    self.show_schedule_management(ui);
+                    self.show_bulk_editor(ui);
                 });
+            if !open {
+                self.windows.bulk_edit = false;
+            }
         }
-        self.show_schedule_window = show_schedule_window;
 
-        // 新建时间表弹窗
-        let mut show_new_schedule_window = self.show_new_schedule_window;
-        if show_new_schedule_window {
-            egui::Window::new("新建时间表")
-                .open(&mut show_new_schedule_window)
-                .fixed_size([400.0, 0.0])
+        // 查看配置弹窗
+        let mut show_config_window = self.windows.config_viewer;
+        if show_config_window {
+            egui::Window::new("查看配置")
+                .open(&mut show_config_window)
+                .default_size([520.0, 460.0])
                 .collapsible(false)
                 .show(ctx, |ui| {
-                    self.show_new_schedule(ui);
+                    self.show_config_viewer(ui);
+                });
+        }
+        self.windows.config_viewer = show_config_window;
+
+        // 自检弹窗
+        let mut show_selftest_window = self.windows.selftest;
+        if show_selftest_window {
+            egui::Window::new("自检")
+                .open(&mut show_selftest_window)
+                .fixed_size([fit_width(ctx, 400.0), 0.0])
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    self.show_selftest(ui);
+                });
+        }
+        self.windows.selftest = show_selftest_window;
+
+        // 模拟一天弹窗
+        let mut show_simulate_window = self.windows.simulate;
+        if show_simulate_window {
+            egui::Window::new("模拟一天")
+                .open(&mut show_simulate_window)
+                .default_size([460.0, 420.0])
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    self.show_simulation(ui);
                 });
         }
-        self.show_new_schedule_window = show_new_schedule_window;
+        self.windows.simulate = show_simulate_window;
 
-        // 音效设置弹窗
-        let mut show_sound_window = self.show_sound_window;
-        if show_sound_window {
-            egui::Window::new("音效设置")
-                .open(&mut show_sound_window)
-                .fixed_size([480.0, 0.0])
+        // 历史版本弹窗（恢复成功后由 show_history 关闭）
+        if self.windows.history {
+            let mut open = true;
+            egui::Window::new("历史版本")
+                .open(&mut open)
+                .default_size([420.0, 400.0])
                 .collapsible(false)
                 .show(ctx, |ui| {
-                    self.show_sound_settings(ui);
+                    self.show_history(ui);
+                });
+            if !open {
+                self.windows.history = false;
+            }
+        }
+
+        // 高级配置编辑弹窗（应用成功后由 show_config_editor 关闭）
+        if self.windows.config_editor {
+            let mut open = true;
+            egui::Window::new("编辑配置（高级）")
+                .open(&mut open)
+                .default_size([560.0, 500.0])
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    self.show_config_editor(ui);
                 });
+            if !open {
+                self.windows.config_editor = false;
+            }
         }
-        self.show_sound_window = show_sound_window;
 
         // 设置窗口
-        if self.show_settings_window {
+        if self.windows.settings {
             let mut open = true;
             egui::Window::new("设置")
                 .open(&mut open)
                 .resizable(false)
                 .collapsible(false)
-                .fixed_size([300.0, 80.0])
+                .fixed_size([fit_width(ctx, 300.0), 0.0])
                 .show(ctx, |ui| {
                     ui.add_space(8.0);
                     ui.horizontal(|ui| {
                         ui.add_space(8.0);
-                        let mut autostart = self.config.autostart;
-                        if ui.checkbox(&mut autostart, "开机自动启动").changed() {
-                            self.config.autostart = autostart;
-                            self.apply_autostart();
+                        let mut autostart = self.config.autostart;
+                        if ui.checkbox(&mut autostart, "开机自动启动").changed() {
+                            self.config.autostart = autostart;
+                            self.apply_autostart();
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        let mut time_spinner = self.config.time_spinner;
+                        if ui
+                            .checkbox(&mut time_spinner, "时间使用数字微调输入")
+                            .on_hover_text("拖动或方向键调整时/分/秒，关闭后可直接输入 HH:MM:SS")
+                            .changed()
+                        {
+                            self.config.time_spinner = time_spinner;
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        let mut suppress = self.config.suppress_during_calls;
+                        if ui
+                            .checkbox(&mut suppress, "会议/通话中不响铃")
+                            .on_hover_text(
+                                "检测到会议进程，或有程序正在使用通话麦克风（Teams、腾讯会议等通话中）时只发通知（仅 Windows）",
+                            )
+                            .changed()
+                        {
+                            self.config.suppress_during_calls = suppress;
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    if self.config.suppress_during_calls {
+                        ui.horizontal(|ui| {
+                            ui.add_space(28.0);
+                            ui.label(RichText::new("会议进程").color(color_text_muted()));
+                            let response = ui
+                                .add(
+                                    egui::TextEdit::singleline(&mut self.meeting_processes_text)
+                                        .desired_width(280.0),
+                                )
+                                .on_hover_text(
+                                    "逗号分隔的进程名，只填会议期间才运行的进程；常驻后台的程序运行期间都会视为开会",
+                                );
+                            if response.lost_focus() {
+                                let processes: Vec<String> = self
+                                    .meeting_processes_text
+                                    .split([',', '，'])
+                                    .map(str::trim)
+                                    .filter(|name| !name.is_empty())
+                                    .map(str::to_string)
+                                    .collect();
+                                self.meeting_processes_text = processes.join(", ");
+                                if processes != self.config.meeting_processes {
+                                    self.config.meeting_processes = processes;
+                                    self.mark_dirty("会议进程列表已保存");
+                                }
+                            }
+                            if ui.small_button("恢复默认").clicked() {
+                                self.config.meeting_processes =
+                                    schedule::default_meeting_processes();
+                                self.meeting_processes_text =
+                                    self.config.meeting_processes.join(", ");
+                                self.mark_dirty("会议进程列表已恢复默认");
+                            }
+                        });
+                    }
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        let mut pause_when_locked = self.config.pause_when_locked;
+                        if ui
+                            .checkbox(&mut pause_when_locked, "锁屏时暂停")
+                            .on_hover_text("锁屏期间不响铃，解锁后恢复原状态（仅 Windows）")
+                            .changed()
+                        {
+                            self.config.pause_when_locked = pause_when_locked;
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        ui.label(RichText::new("时间表排序").color(color_text_muted()));
+                        let mut sort = self.config.schedule_sort;
+                        egui::ComboBox::from_id_salt("schedule_sort")
+                            .selected_text(sort.label())
+                            .width(120.0)
+                            .show_ui(ui, |ui| {
+                                for option in ScheduleSort::ALL {
+                                    ui.selectable_value(&mut sort, option, option.label());
+                                }
+                            });
+                        if sort != self.config.schedule_sort {
+                            self.config.schedule_sort = sort;
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        ui.label(RichText::new("铃声冷却").color(color_text_muted()));
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut self.config.sound_cooldown_ms)
+                                    .range(0..=10_000)
+                                    .speed(50)
+                                    .suffix(" 毫秒"),
+                            )
+                            .on_hover_text("间隔内重复的铃声跳过，不同铃声排队依次播放")
+                            .changed()
+                        {
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        ui.label(RichText::new("触发容差").color(color_text_muted()));
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut self.config.trigger_tolerance_secs)
+                                    .range(0..=300)
+                                    .suffix(" 秒"),
+                            )
+                            .on_hover_text("节点时间过后该秒数内仍会补响（系统时钟被校正时避免漏响），同一节点当天只响一次")
+                            .changed()
+                        {
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        ui.label(RichText::new("启动宽限").color(color_text_muted()));
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut self.config.startup_grace_secs)
+                                    .range(0..=120)
+                                    .suffix(" 秒"),
+                            )
+                            .on_hover_text("程序启动后该秒数内不响铃，期间到点的节点直接跳过")
+                            .changed()
+                        {
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        ui.label(RichText::new("每天从").color(color_text_muted()));
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut self.config.day_start_hour)
+                                    .range(0..=23)
+                                    .suffix(" 点开始"),
+                            )
+                            .on_hover_text("“同一节点当天只响一次”的记录在该时刻清空；有跨午夜的晚自习时可设为 4 点等，午夜后的节点仍算作前一天")
+                            .changed()
+                        {
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        let mut use_location = self.config.location.is_some();
+                        if ui
+                            .checkbox(&mut use_location, "日出日落位置")
+                            .on_hover_text("相对日出/日落的节点按该经纬度每天换算时间")
+                            .changed()
+                        {
+                            self.config.location = use_location.then_some(GeoLocation {
+                                latitude: 39.9,
+                                longitude: 116.4,
+                            });
+                            self.config.resolve_solar_times(&Local::now());
+                            self.mark_dirty("设置已保存");
+                        }
+                        if let Some(location) = &mut self.config.location {
+                            let mut changed = false;
+                            ui.label(RichText::new("纬度").color(color_text_muted()));
+                            changed |= ui
+                                .add(
+                                    egui::DragValue::new(&mut location.latitude)
+                                        .range(-90.0..=90.0)
+                                        .speed(0.1)
+                                        .fixed_decimals(2),
+                                )
+                                .on_hover_text("北纬为正，南纬为负")
+                                .changed();
+                            ui.label(RichText::new("经度").color(color_text_muted()));
+                            changed |= ui
+                                .add(
+                                    egui::DragValue::new(&mut location.longitude)
+                                        .range(-180.0..=180.0)
+                                        .speed(0.1)
+                                        .fixed_decimals(2),
+                                )
+                                .on_hover_text("东经为正，西经为负")
+                                .changed();
+                            if changed {
+                                self.config.resolve_solar_times(&Local::now());
+                                self.mark_dirty("设置已保存");
+                            }
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        let mut duck_others = self.config.duck_others;
+                        if ui
+                            .checkbox(&mut duck_others, "响铃时压低其他程序音量")
+                            .on_hover_text(
+                                "铃声播放期间把其他程序音量降到 30%，结束后恢复（仅 Windows）",
+                            )
+                            .changed()
+                        {
+                            self.config.duck_others = duck_others;
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        let mut check_volume = self.config.check_system_volume;
+                        if ui
+                            .checkbox(&mut check_volume, "检查系统音量")
+                            .on_hover_text("启动时及每分钟检查一次，系统静音或音量过低时在状态栏提示（仅 Windows）")
+                            .changed()
+                        {
+                            self.config.check_system_volume = check_volume;
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        let mut taskbar_progress = self.config.taskbar_progress;
+                        if ui
+                            .checkbox(&mut taskbar_progress, "任务栏显示进度")
+                            .on_hover_text("任务栏按钮显示当前时段已过去的比例，暂停时显示为黄色（仅 Windows）")
+                            .changed()
+                        {
+                            self.config.taskbar_progress = taskbar_progress;
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        let mut event_feed = self.config.event_feed;
+                        if ui
+                            .checkbox(&mut event_feed, "推送触发事件")
+                            .on_hover_text(format!(
+                                "节点触发时向 {} 逐行写入 JSON，供脚本订阅；重启后生效",
+                                feed::feed_address()
+                            ))
+                            .changed()
+                        {
+                            self.config.event_feed = event_feed;
+                            self.mark_dirty("设置已保存，重启后生效");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        ui.label(RichText::new("点击关闭按钮").color(color_text_muted()));
+                        let mut close_action = self.config.close_action;
+                        egui::ComboBox::from_id_salt("close_action")
+                            .selected_text(close_action.label())
+                            .width(120.0)
+                            .show_ui(ui, |ui| {
+                                for option in CloseAction::ALL {
+                                    ui.selectable_value(&mut close_action, option, option.label());
+                                }
+                            });
+                        if close_action != self.config.close_action {
+                            self.config.close_action = close_action;
+                            self.mark_dirty("设置已保存");
+                        }
+                        // 在关闭确认框中“记住我的选择”后，可在这里一键改回每次询问
+                        if (close_action != CloseAction::Ask || !self.config.confirm_on_close)
+                            && ui
+                                .small_button("恢复每次询问")
+                                .on_hover_text("关闭窗口与从托盘退出时都重新弹出确认框")
+                                .clicked()
+                        {
+                            self.config.close_action = CloseAction::Ask;
+                            self.config.confirm_on_close = true;
+                            self.mark_dirty("已恢复关闭时询问");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        let mut confirm_on_close = self.config.confirm_on_close;
+                        if ui
+                            .checkbox(&mut confirm_on_close, "退出前确认")
+                            .on_hover_text("关闭按钮设为“退出程序”或从托盘菜单退出时，先弹出确认框")
+                            .changed()
+                        {
+                            self.config.confirm_on_close = confirm_on_close;
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        let mut restore_windows = self.config.restore_windows;
+                        if ui
+                            .checkbox(&mut restore_windows, "重启后恢复打开的窗口")
+                            .on_hover_text("关闭后窗口开关只在本次运行内保留（最小化到托盘再恢复不受影响）")
+                            .changed()
+                        {
+                            self.config.restore_windows = restore_windows;
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        ui.label(RichText::new("界面缩放").color(color_text_muted()));
+                        let response = ui.add(
+                            egui::Slider::new(&mut self.config.ui_scale, UI_SCALE_RANGE)
+                                .step_by(0.1)
+                                .fixed_decimals(1)
+                                .suffix("×"),
+                        );
+                        // 拖动中缩放会改变滑块自身位置，松开后再应用
+                        if response.drag_stopped() || (response.changed() && !response.dragged())
+                        {
+                            self.theme_applied = false;
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        ui.label(RichText::new("界面密度").color(color_text_muted()));
+                        let mut density = self.config.density;
+                        egui::ComboBox::from_id_salt("ui_density")
+                            .selected_text(density.label())
+                            .show_ui(ui, |ui| {
+                                for option in Density::ALL {
+                                    ui.selectable_value(&mut density, option, option.label());
+                                }
+                            })
+                            .response
+                            .on_hover_text("紧凑模式缩小控件间距与节点行高，小屏幕上能看到更多节点");
+                        if density != self.config.density {
+                            self.config.density = density;
+                            self.theme_applied = false;
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    let monitors = monitor::monitors();
+                    if monitors.len() > 1 {
+                        ui.horizontal(|ui| {
+                            ui.add_space(8.0);
+                            ui.label(RichText::new("大屏显示器").color(color_text_muted()));
+                            let selected_text = match self.config.overlay_monitor {
+                                Some(index) => monitors
+                                    .get(index)
+                                    .map(|monitor| monitor.label(index))
+                                    .unwrap_or_else(|| "主显示器（所选已断开）".to_string()),
+                                None => "当前显示器".to_string(),
+                            };
+                            let mut overlay_monitor = self.config.overlay_monitor;
+                            egui::ComboBox::from_id_salt("overlay_monitor")
+                                .selected_text(selected_text)
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut overlay_monitor, None, "当前显示器");
+                                    for (index, monitor) in monitors.iter().enumerate() {
+                                        ui.selectable_value(
+                                            &mut overlay_monitor,
+                                            Some(index),
+                                            monitor.label(index),
+                                        );
+                                    }
+                                });
+                            if overlay_monitor != self.config.overlay_monitor {
+                                self.config.overlay_monitor = overlay_monitor;
+                                self.mark_dirty("设置已保存");
+                            }
+                        });
+                    }
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        let mut reduce_motion = self.config.reduce_motion;
+                        if ui.checkbox(&mut reduce_motion, "减少界面动画").changed() {
+                            self.config.reduce_motion = reduce_motion;
+                            self.theme_applied = false;
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        let mut enabled = self.config.visual_alert.enabled;
+                        if ui
+                            .checkbox(&mut enabled, "视觉提醒")
+                            .on_hover_text("提醒触发时窗口着色闪烁并闪动任务栏按钮，适合听障用户或静音教室")
+                            .changed()
+                        {
+                            self.config.visual_alert.enabled = enabled;
+                            self.mark_dirty("设置已保存");
+                        }
+                        if ui.button("预览").clicked() {
+                            self.start_visual_alert(ui.ctx());
+                        }
+                    });
+                    if self.config.visual_alert.enabled {
+                        ui.horizontal(|ui| {
+                            ui.add_space(24.0);
+                            ui.label(RichText::new("闪烁颜色").color(color_text_muted()));
+                            if ui
+                                .color_edit_button_srgb(&mut self.config.visual_alert.color)
+                                .changed()
+                            {
+                                self.mark_dirty("设置已保存");
+                            }
+                            ui.label(RichText::new("持续").color(color_text_muted()));
+                            if ui
+                                .add(
+                                    egui::DragValue::new(&mut self.config.visual_alert.duration_ms)
+                                        .range(300..=10_000)
+                                        .speed(50)
+                                        .suffix(" 毫秒"),
+                                )
+                                .changed()
+                            {
+                                self.mark_dirty("设置已保存");
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.add_space(24.0);
+                            let mut silent = self.config.visual_alert.silent;
+                            if ui
+                                .checkbox(&mut silent, "仅视觉提醒，不响铃")
+                                .on_hover_text("节点、临时提醒和整点报时都不再播放铃声，桌面通知照常发送")
+                                .changed()
+                            {
+                                self.config.visual_alert.silent = silent;
+                                self.mark_dirty("设置已保存");
+                            }
+                        });
+                    }
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        let mut rollover = self.config.countdown_rollover;
+                        if ui
+                            .checkbox(&mut rollover, "今日结束后倒计时到明天首个节点")
+                            .on_hover_text("适合住校等次日早上还有节点的场景，显示为“明日 …”")
+                            .changed()
+                        {
+                            self.config.countdown_rollover = rollover;
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        let mut show_banner = self.config.show_day_end_banner;
+                        if ui
+                            .checkbox(&mut show_banner, "今日节点结束后显示提示")
+                            .changed()
+                        {
+                            self.config.show_day_end_banner = show_banner;
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        ui.label(RichText::new("时间吸附").color(color_text_muted()));
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut self.config.snap_minutes)
+                                    .range(0..=60)
+                                    .custom_formatter(|n, _| {
+                                        if n == 0.0 {
+                                            "关闭".to_string()
+                                        } else {
+                                            format!("{n} 分钟")
+                                        }
+                                    }),
+                            )
+                            .on_hover_text("输入时间后四舍五入到该分钟网格，秒归零")
+                            .changed()
+                        {
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        ui.label(RichText::new("节点最小间隔").color(color_text_muted()));
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut self.config.min_gap_minutes)
+                                    .range(0..=60)
+                                    .custom_formatter(|n, _| {
+                                        if n == 0.0 {
+                                            "不检查".to_string()
+                                        } else {
+                                            format!("{n} 分钟")
+                                        }
+                                    }),
+                            )
+                            .on_hover_text("相邻两个响铃节点的间隔小于该值时在节点列表上方提示，仅作提醒")
+                            .changed()
+                        {
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        ui.label(RichText::new("保留备份").color(color_text_muted()));
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut self.config.backup_count)
+                                    .range(0..=MAX_BACKUP_COUNT)
+                                    .custom_formatter(|n, _| {
+                                        if n == 0.0 {
+                                            "不备份".to_string()
+                                        } else {
+                                            format!("{n} 份")
+                                        }
+                                    }),
+                            )
+                            .on_hover_text(
+                                "每次保存前把旧配置备份为 .bak.N，调小后多余的旧备份会被删除",
+                            )
+                            .changed()
+                        {
+                            self.mark_dirty("设置已保存");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        let tray_ok = self.tray.is_some();
+                        ui.label(
+                            RichText::new(if tray_ok {
+                                "托盘：运行中"
+                            } else {
+                                "托盘：未启用"
+                            })
+                            .color(color_text_muted()),
+                        );
+                        if ui
+                            .add_enabled(!tray_ok, egui::Button::new("重试托盘"))
+                            .on_disabled_hover_text("托盘已正常运行")
+                            .clicked()
+                        {
+                            self.tray = tray::spawn(self.config.tray_icon_path.clone());
+                            self.sync_tray();
+                            if self.tray.is_some() {
+                                self.set_status(Severity::Success, "托盘已启用");
+                            } else {
+                                self.set_status(Severity::Error, "托盘初始化失败");
+                            }
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        ui.label(RichText::new("托盘图标").color(color_text_muted()));
+                        let mut activation = self.config.tray_activation;
+                        egui::ComboBox::from_id_salt("tray_activation")
+                            .selected_text(activation.label())
+                            .width(120.0)
+                            .show_ui(ui, |ui| {
+                                for option in TrayActivation::ALL {
+                                    ui.selectable_value(&mut activation, option, option.label());
+                                }
+                            });
+                        if activation != self.config.tray_activation {
+                            self.config.tray_activation = activation;
+                            self.sync_tray();
                             self.mark_dirty("设置已保存");
                         }
+                        if ui
+                            .button("更换图片…")
+                            .on_hover_text(
+                                self.config
+                                    .tray_icon_path
+                                    .as_deref()
+                                    .unwrap_or("当前为默认图标；可选择 PNG/ICO，自动缩放"),
+                            )
+                            .clicked()
+                        {
+                            self.pick_tray_icon();
+                        }
+                        if ui
+                            .add_enabled(
+                                self.config.tray_icon_path.is_some(),
+                                egui::Button::new("恢复默认图标"),
+                            )
+                            .clicked()
+                        {
+                            self.config.tray_icon_path = None;
+                            self.sync_tray();
+                            self.mark_dirty("已恢复默认托盘图标");
+                        }
+                    });
+                    ui.horizontal_wrapped(|ui| {
+                        ui.add_space(8.0);
+                        if ui
+                            .button("自检")
+                            .on_hover_text("检查配置写入、音频、通知、检测线程与开机自启")
+                            .clicked()
+                        {
+                            self.windows.selftest = true;
+                            self.start_selftest(ctx);
+                        }
+                        if ui.button("查看配置").clicked() {
+                            self.windows.config_viewer = true;
+                        }
+                        if ui.button("编辑配置（高级）").clicked() {
+                            self.open_config_editor();
+                        }
+                        if ui
+                            .button("导入配置")
+                            .on_hover_text("从 TOML 文件导入，可与现有时间表合并或整体替换")
+                            .clicked()
+                        {
+                            self.pick_import_file();
+                        }
+                        if ui.button("历史版本").clicked() {
+                            self.open_history_window();
+                        }
                     });
+                    egui::CollapsingHeader::new("界面文字")
+                        .id_salt("settings_labels")
+                        .show(ui, |ui| {
+                            let mut changed = false;
+                            let labels = &mut self.config.labels;
+                            for (caption, value) in [
+                                ("首个节点前", &mut labels.idle),
+                                ("无后续节点", &mut labels.no_next),
+                                ("无时间表", &mut labels.no_schedule),
+                                ("全部结束", &mut labels.day_over),
+                            ] {
+                                ui.horizontal(|ui| {
+                                    ui.label(RichText::new(caption).color(color_text_muted()));
+                                    changed |= ui
+                                        .add(egui::TextEdit::singleline(value).desired_width(160.0))
+                                        .changed();
+                                });
+                            }
+                            if ui.button("恢复默认文字").clicked() {
+                                *labels = schedule::Labels::default();
+                                changed = true;
+                            }
+                            if changed {
+                                self.mark_dirty("设置已保存");
+                            }
+                        });
                     ui.add_space(8.0);
                 });
             if !open {
-                self.show_settings_window = false;
+                self.windows.settings = false;
             }
         }
 
         // 新增节点弹窗
-        if self.show_add_dialog {
+        if self.windows.add_period {
             let mut open = true;
             let mut do_add = false;
             let mut do_cancel = false;
+            let timer_mode = self.active_timer_mode();
+            let time_valid = self.new_period_solar.is_some()
+                || self.config.time_spinner
+                || schedule::is_valid_time(&self.new_period_time)
+                || (timer_mode
+                    && schedule::parse_relative_minutes(&self.new_period_time).is_some());
+            let name_valid = !self.new_period_name.trim().is_empty();
 
             egui::Window::new("添加时间节点")
                 .open(&mut open)
-                .fixed_size([380.0, 0.0])
+                .fixed_size([fit_width(ctx, 380.0), 0.0])
                 .collapsible(false)
                 .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
                 .show(ctx, |ui| {
                     ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
+                        if !timer_mode {
+                            ui.horizontal(|ui| {
+                                ui.label(RichText::new("基准").color(color_text_muted()));
+                                let mut solar_mode = self.new_period_solar.is_some();
+                                ui.radio_value(&mut solar_mode, false, "固定时间");
+                                ui.radio_value(&mut solar_mode, true, "相对日出/日落");
+                                if solar_mode != self.new_period_solar.is_some() {
+                                    self.new_period_solar = solar_mode.then_some(SolarTime {
+                                        event: SolarEvent::Sunset,
+                                        offset_mins: 0,
+                                    });
+                                }
+                            });
+                            ui.add_space(4.0);
+                        }
+
                         ui.horizontal(|ui| {
                             ui.label(RichText::new("时间").color(color_text_muted()));
-                            ui.add(
-                                egui::TextEdit::singleline(&mut self.new_period_time)
-                                    .desired_width(100.0)
-                                    .hint_text(RichText::new("HH:MM:SS").color(color_hint_text())),
-                            );
+                            if let Some(mut solar) = self.new_period_solar {
+                                egui::ComboBox::from_id_salt("dialog_solar_event")
+                                    .selected_text(solar.event.label())
+                                    .width(60.0)
+                                    .show_ui(ui, |ui| {
+                                        for event in SolarEvent::ALL {
+                                            ui.selectable_value(
+                                                &mut solar.event,
+                                                event,
+                                                event.label(),
+                                            );
+                                        }
+                                    });
+                                ui.add(
+                                    egui::DragValue::new(&mut solar.offset_mins)
+                                        .range(-720..=720)
+                                        .suffix(" 分钟"),
+                                )
+                                .on_hover_text("负数为之前，正数为之后");
+                                self.new_period_solar = Some(solar);
+                                ui.label(
+                                    RichText::new(self.solar_preview(solar))
+                                        .color(color_text_muted()),
+                                );
+                            } else if self.config.time_spinner {
+                                draw_time_spinner(ui, &mut self.new_period_time);
+                            } else {
+                                let time_response = ui
+                                    .scope(|ui| {
+                                        tint_validity(ui, time_valid);
+                                        ui.add(
+                                            egui::TextEdit::singleline(&mut self.new_period_time)
+                                                .desired_width(100.0)
+                                                .hint_text(
+                                                    RichText::new(if timer_mode {
+                                                        "HH:MM:SS 或 +分钟"
+                                                    } else {
+                                                        "HH:MM:SS"
+                                                    })
+                                                    .color(color_hint_text()),
+                                                ),
+                                        )
+                                    })
+                                    .inner;
+                                if time_response.lost_focus()
+                                    && let Some(normalized) = schedule::normalize_time_snapped(
+                                        &self.new_period_time,
+                                        self.config.snap_minutes,
+                                    )
+                                {
+                                    self.new_period_time = normalized;
+                                }
+                            }
                         });
 
                         ui.add_space(4.0);
@@ -991,16 +4189,13 @@ impl eframe::App for WcNoticeApp {
                                 .selected_text(self.new_period_kind.label())
                                 .width(100.0)
                                 .show_ui(ui, |ui| {
-                                    ui.selectable_value(
-                                        &mut self.new_period_kind,
-                                        PeriodKind::Start,
-                                        "开始",
-                                    );
-                                    ui.selectable_value(
-                                        &mut self.new_period_kind,
-                                        PeriodKind::End,
-                                        "结束",
-                                    );
+                                    for kind in PeriodKind::ALL {
+                                        ui.selectable_value(
+                                            &mut self.new_period_kind,
+                                            kind,
+                                            kind.label(),
+                                        );
+                                    }
                                 });
                         });
 
@@ -1008,11 +4203,30 @@ impl eframe::App for WcNoticeApp {
 
                         ui.horizontal(|ui| {
                             ui.label(RichText::new("名称").color(color_text_muted()));
+                            ui.scope(|ui| {
+                                tint_validity(ui, name_valid);
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut self.new_period_name)
+                                        .char_limit(MAX_NAME_CHARS)
+                                        .desired_width(240.0)
+                                        .hint_text(
+                                            RichText::new("例如：第1节开始")
+                                                .color(color_hint_text()),
+                                        ),
+                                );
+                            });
+                        });
+
+                        ui.add_space(4.0);
+
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new("备注").color(color_text_muted()));
                             ui.add(
-                                egui::TextEdit::singleline(&mut self.new_period_name)
+                                egui::TextEdit::singleline(&mut self.new_period_note)
                                     .desired_width(240.0)
                                     .hint_text(
-                                        RichText::new("例如：第1节开始").color(color_hint_text()),
+                                        RichText::new("可选，显示在通知中，如：记得收作业")
+                                            .color(color_hint_text()),
                                     ),
                             );
                         });
@@ -1020,7 +4234,14 @@ impl eframe::App for WcNoticeApp {
                         ui.add_space(10.0);
 
                         ui.horizontal(|ui| {
-                            if ui.button("✔ 确认添加").clicked() {
+                            if ui
+                                .add_enabled(
+                                    time_valid && name_valid,
+                                    egui::Button::new("✔ 确认添加"),
+                                )
+                                .on_disabled_hover_text("请填写有效的时间和名称")
+                                .clicked()
+                            {
                                 do_add = true;
                             }
                             if ui.button("✖ 取消").clicked() {
@@ -1031,28 +4252,40 @@ impl eframe::App for WcNoticeApp {
                 });
 
             if !open || do_cancel {
-                self.show_add_dialog = false;
+                self.windows.add_period = false;
             }
 
-            if do_add {
-                let time = self.new_period_time.trim().to_string();
+            if do_add && let Some(solar) = self.new_period_solar {
+                self.add_solar_period(solar);
+            } else if do_add {
+                let mut time = self.new_period_time.trim().to_string();
                 let name = self.new_period_name.trim().to_string();
                 let kind = self.new_period_kind;
 
-                match schedule::normalize_time_str(&time) {
+                // 计时器模式下 "+N" 表示从现在起 N 分钟后
+                if timer_mode && let Some(minutes) = schedule::parse_relative_minutes(&time) {
+                    let now = self.schedule_now();
+                    if let Some(at) = schedule::add_minutes_same_day(now, minutes) {
+                        time = at.format("%H:%M:%S").to_string();
+                    }
+                }
+
+                match schedule::normalize_time_snapped(&time, self.config.snap_minutes) {
                     None => {
-                        self.status_msg =
-                            "时间格式错误，请使用 HH:MM:SS（时0-23，分/秒0-59）".to_string();
+                        self.set_status(
+                            Severity::Error,
+                            "时间格式错误，请使用 HH:MM:SS（时0-23，分/秒0-59）",
+                        );
                     }
                     Some(normalized_time) => {
                         if name.is_empty() {
-                            self.status_msg = "节点名称不能为空".to_string();
-                        } else if let Some(schedule) = self.active_schedule_mut() {
-                            schedule
-                                .periods
-                                .push(Period::new(&normalized_time, kind, &name));
+                            self.set_status(Severity::Error, "节点名称不能为空");
+                        } else if let Some(schedule) = self.config.active_schedule_mut() {
+                            let mut period = Period::new(&normalized_time, kind, &name);
+                            period.set_note(&self.new_period_note);
+                            schedule.periods.push(period);
                             schedule.sort_periods();
-                            self.show_add_dialog = false;
+                            self.windows.add_period = false;
                             self.mark_dirty("新节点已添加");
                         }
                     }
@@ -1076,8 +4309,65 @@ impl eframe::App for WcNoticeApp {
                     .show(ui, |ui| {
                         ui.set_min_width(ui.available_width().max(MIN_CONTENT_WIDTH));
 
+                        if self.memory_only {
+                            egui::Frame::new()
+                                .fill(color_danger_fill())
+                                .stroke(Stroke::new(1.0, color_danger_border()))
+                                .corner_radius(10)
+                                .inner_margin(egui::Margin::symmetric(12, 10))
+                                .show(ui, |ui| {
+                                    ui.set_width(ui.available_width());
+                                    ui.horizontal(|ui| {
+                                        ui.label(
+                                            RichText::new(
+                                                "⚠ 配置文件无法写入，修改只保存在内存中，退出后会丢失",
+                                            )
+                                            .strong()
+                                            .color(color_danger_text()),
+                                        );
+                                        if ui.button("另存为…").clicked() {
+                                            self.save_config_as();
+                                        }
+                                    });
+                                });
+                            ui.add_space(8.0);
+                        }
+
+                        let day_over = self.config.show_day_end_banner
+                            && self
+                                .active_schedule()
+                                .is_some_and(|schedule| schedule.is_day_over(&now));
+                        if day_over {
+                            egui::Frame::new()
+                                .fill(color_success_fill())
+                                .stroke(Stroke::new(1.0, color_period_start_border()))
+                                .corner_radius(10)
+                                .inner_margin(egui::Margin::symmetric(12, 10))
+                                .show(ui, |ui| {
+                                    ui.set_width(ui.available_width());
+                                    ui.vertical_centered(|ui| {
+                                        ui.label(
+                                            RichText::new(&self.config.labels.day_over)
+                                                .size(16.0)
+                                                .strong()
+                                                .color(color_success_text()),
+                                        );
+                                    });
+                                });
+                            ui.add_space(8.0);
+                        }
+
                         if self.active_schedule().is_some() {
-                            self.show_period_editor(ui, now);
+                            ui.horizontal(|ui| {
+                                ui.selectable_value(&mut self.timeline_view, false, "列表");
+                                ui.selectable_value(&mut self.timeline_view, true, "时间轴");
+                            });
+                            ui.add_space(6.0);
+                            if self.timeline_view {
+                                self.show_timeline(ui, now);
+                            } else {
+                                self.show_period_editor(ui, now);
+                            }
                         } else {
                             card(ui, "空状态", |ui| {
                                 ui.label(
@@ -1090,10 +4380,17 @@ impl eframe::App for WcNoticeApp {
                     });
             });
 
+        self.show_delete_schedule_confirm_window(ctx);
+        self.show_onboarding_window(ctx);
+        self.show_import_window(ctx);
         self.show_exit_confirm_window(ctx);
+        self.sync_ui_state();
 
-        // 有 pending 时用 200ms 刷新确保防抖及时触发，否则 1s 刷新即可
-        let repaint_delay = if self.pending_save.is_some() {
+        // 有 pending 或正在测试音效时用 200ms 刷新确保及时推进，否则 1s 刷新即可
+        let repaint_delay = if self.pending_save.is_some()
+            || self.pending_engine_sync.is_some()
+            || self.sound_test.is_some()
+        {
             Duration::from_millis(200)
         } else {
             Duration::from_secs(1)
@@ -1108,6 +4405,8 @@ fn draw_sound_source_editor(
     id_base: &str,
     source: &mut SoundSource,
     kind: PeriodKind,
+    sound_checks: &mut HashMap<String, Result<Option<Duration>, String>>,
+    recent_sounds: &mut Vec<String>,
 ) -> bool {
     let mut changed = false;
 
@@ -1129,6 +4428,7 @@ fn draw_sound_source_editor(
         if ui.selectable_label(!is_builtin, "本地").clicked() && is_builtin {
             *source = SoundSource::Local {
                 path: String::new(),
+                gain_db: 0.0,
             };
             changed = true;
         }
@@ -1138,11 +4438,11 @@ fn draw_sound_source_editor(
         SoundSource::Builtin(sound) => {
             let mut selected = *sound;
             egui::ComboBox::from_id_salt(format!("{}_builtin", id_base))
-                .selected_text(selected.label())
+                .selected_text(selected.display_name())
                 .width(180.0)
                 .show_ui(ui, |ui| {
                     for builtin in BuiltinSound::ALL {
-                        ui.selectable_value(&mut selected, builtin, builtin.label());
+                        ui.selectable_value(&mut selected, builtin, builtin.display_name());
                     }
                 });
 
@@ -1151,7 +4451,7 @@ fn draw_sound_source_editor(
                 changed = true;
             }
         }
-        SoundSource::Local { path } => {
+        SoundSource::Local { path, .. } => {
             if ui
                 .add(
                     egui::TextEdit::singleline(path)
@@ -1173,15 +4473,190 @@ fn draw_sound_source_editor(
                 {
                     let abs = make_abs_path(file);
                     *path = abs.display().to_string();
+                    schedule::remember_recent_sound(recent_sounds, path);
                     changed = true;
                 }
             }
         }
     });
 
+    // 本地音效增益：手动调节，或与同类内置铃声比较响度后自动匹配
+    if let SoundSource::Local { path, gain_db } = source
+        && !path.trim().is_empty()
+    {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("增益").size(12.0).color(color_text_muted()));
+            if ui
+                .add(
+                    egui::Slider::new(gain_db, -MAX_GAIN_DB..=MAX_GAIN_DB)
+                        .step_by(0.5)
+                        .suffix(" dB"),
+                )
+                .changed()
+            {
+                changed = true;
+            }
+            if ui
+                .button("分析并匹配")
+                .on_hover_text("比较与内置铃声的响度，自动设置增益")
+                .clicked()
+            {
+                match suggest_gain_db(path, kind.default_builtin_sound()) {
+                    Ok(suggested) => {
+                        *gain_db = (suggested * 2.0).round() / 2.0;
+                        changed = true;
+                    }
+                    // 文件不可用的原因已在下方检查结果中显示
+                    Err(e) => log::warn!("响度分析失败（{}）: {}", path, e),
+                }
+            }
+        });
+    }
+
+    // 最近用过的本地音效：一键选用，不必再打开文件对话框
+    if let SoundSource::Local { path, .. } = source {
+        let picks: Vec<String> = recent_sounds
+            .iter()
+            .filter(|recent| std::path::Path::new(recent.as_str()).is_file())
+            .cloned()
+            .collect();
+        if !picks.is_empty() {
+            ui.horizontal_wrapped(|ui| {
+                ui.label(RichText::new("最近").size(12.0).color(color_text_muted()));
+                for recent in picks {
+                    let file_name = std::path::Path::new(&recent)
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_else(|| recent.clone());
+                    if ui
+                        .selectable_label(*path == recent, file_name)
+                        .on_hover_text(&recent)
+                        .clicked()
+                        && *path != recent
+                    {
+                        schedule::remember_recent_sound(recent_sounds, &recent);
+                        *path = recent;
+                        changed = true;
+                    }
+                }
+            });
+        }
+    }
+
+    // 本地音效：显示读取/解码检查结果（含具体错误）与时长，按路径缓存避免每帧解码
+    if let SoundSource::Local { path, .. } = source
+        && !path.trim().is_empty()
+    {
+        let check = sound_checks
+            .entry(path.clone())
+            .or_insert_with(|| validate_sound(source));
+        match check {
+            Ok(duration) => ui.label(
+                RichText::new(format!(
+                    "✔ 文件可用，时长 {}",
+                    duration.map_or_else(|| "未知".to_string(), format_sound_duration)
+                ))
+                .size(12.0)
+                .color(color_success_text()),
+            ),
+            Err(e) => ui.label(
+                RichText::new(format!("✖ {e}（播放时将回退默认音效）"))
+                    .size(12.0)
+                    .color(color_danger_text()),
+            ),
+        };
+    }
+
     changed
 }
 
+/// 音效时长显示为“分:秒”，不足一秒按一秒计
+fn format_sound_duration(duration: Duration) -> String {
+    let secs = duration.as_secs_f32().ceil() as u64;
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+/// 时/分/秒三段数字微调输入（可拖动或键盘上下调整）。
+/// 分/秒越过 59 或低于 0 时自动向上一单位进位/借位，结果直接写回规范化的 HH:MM:SS。
+/// 无法解析的时间（文本模式下输入的无效值）原样标红显示，只有点击“重设”才会覆盖。
+fn draw_time_spinner(ui: &mut Ui, time: &mut String) -> bool {
+    use chrono::Timelike;
+
+    let Some(parsed) = schedule::normalize_time_str(time)
+        .and_then(|normalized| NaiveTime::parse_from_str(&normalized, "%H:%M:%S").ok())
+    else {
+        let mut reset = false;
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(time.as_str()).color(color_danger_text()))
+                .on_hover_text("时间格式无效，尚未生效");
+            reset = ui
+                .small_button("重设")
+                .on_hover_text("改为 00:00:00 后用微调输入修改")
+                .clicked();
+        });
+        if reset {
+            *time = schedule::compose_time_str(0, 0, 0);
+        }
+        return reset;
+    };
+    let mut hour = parsed.hour() as i64;
+    let mut minute = parsed.minute() as i64;
+    let mut second = parsed.second() as i64;
+    let mut changed = false;
+
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = 2.0;
+        let two_digits = |n: f64, _: std::ops::RangeInclusive<usize>| format!("{:02}", n as i64);
+
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut hour)
+                    .range(0..=23)
+                    .speed(0.1)
+                    .custom_formatter(two_digits),
+            )
+            .changed();
+        ui.label(":");
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut minute)
+                    .range(-1..=60)
+                    .speed(0.2)
+                    .custom_formatter(two_digits),
+            )
+            .changed();
+        ui.label(":");
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut second)
+                    .range(-1..=60)
+                    .speed(0.2)
+                    .custom_formatter(two_digits),
+            )
+            .changed();
+    });
+
+    let composed = schedule::compose_time_str(hour, minute, second);
+    if changed && *time != composed {
+        *time = composed;
+        return true;
+    }
+    false
+}
+
+/// 按输入是否有效把当前作用域内输入框的边框染成绿色/红色
+fn tint_validity(ui: &mut Ui, valid: bool) {
+    let color = if valid {
+        color_success_text()
+    } else {
+        color_danger_text()
+    };
+    let visuals = ui.visuals_mut();
+    visuals.widgets.inactive.bg_stroke = Stroke::new(1.0, color);
+    visuals.widgets.hovered.bg_stroke = Stroke::new(1.0, color);
+    visuals.selection.stroke = Stroke::new(1.5, color);
+}
+
 fn make_abs_path(path: PathBuf) -> PathBuf {
     if path.is_absolute() {
         return path;
@@ -1209,6 +4684,212 @@ fn period_runtime_state(period: &Period, now: &NaiveTime) -> &'static str {
     "未到"
 }
 
+/// 任务栏按钮上的状态显示
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TaskbarState {
+    Hidden,
+    Paused,
+    /// 当前时段已过去的百分比
+    Progress(u8),
+}
+
+/// 通过 `ITaskbarList3` 设置任务栏按钮的进度条（仅 Windows）
+#[cfg(target_os = "windows")]
+fn apply_taskbar_state(state: TaskbarState) {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::System::Com::{
+        CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED, CoCreateInstance, CoInitializeEx,
+    };
+    use windows::Win32::UI::Shell::{
+        ITaskbarList3, TBPF_NOPROGRESS, TBPF_NORMAL, TBPF_PAUSED, TaskbarList,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::FindWindowW;
+
+    fn apply(hwnd: HWND, state: TaskbarState) -> windows::core::Result<()> {
+        unsafe {
+            // 界面线程已由 winit 初始化为 STA，忽略重复初始化的返回值
+            let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+
+            let taskbar: ITaskbarList3 =
+                CoCreateInstance(&TaskbarList, None, CLSCTX_INPROC_SERVER)?;
+            taskbar.HrInit()?;
+            match state {
+                TaskbarState::Hidden => taskbar.SetProgressState(hwnd, TBPF_NOPROGRESS),
+                TaskbarState::Paused => {
+                    taskbar.SetProgressState(hwnd, TBPF_PAUSED)?;
+                    taskbar.SetProgressValue(hwnd, 100, 100)
+                }
+                TaskbarState::Progress(percent) => {
+                    taskbar.SetProgressState(hwnd, TBPF_NORMAL)?;
+                    taskbar.SetProgressValue(hwnd, u64::from(percent), 100)
+                }
+            }
+        }
+    }
+
+    // 窗口标题与 main.rs 中 with_title() 保持一致
+    let title: Vec<u16> = "WC Notice - 作息提醒\0".encode_utf16().collect();
+    let hwnd = unsafe { FindWindowW(std::ptr::null(), title.as_ptr()) };
+    if hwnd.is_null() {
+        return;
+    }
+    if let Err(e) = apply(HWND(hwnd as isize), state) {
+        log::warn!("设置任务栏进度失败: {}", e);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn apply_taskbar_state(_state: TaskbarState) {}
+
+/// 窗口位置检查的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PositionCheck {
+    /// 仅在窗口不在任何显示器内时收进最近的显示器
+    IfOffscreen,
+    /// 托盘菜单“重置窗口位置”：移到主显示器居中
+    Reset,
+}
+
+/// 各弹窗是否打开。从托盘恢复、进出大屏模式都不改动这些标志；
+/// 其中不依赖临时数据的窗口可随 `UiState` 在重启后恢复（见 `restore_windows`）
+#[derive(Debug, Clone, Copy, Default)]
+struct OpenWindows {
+    schedule: bool,
+    new_schedule: bool,
+    sound: bool,
+    add_period: bool,
+    settings: bool,
+    oneshot: bool,
+    class_block: bool,
+    bulk_edit: bool,
+    config_viewer: bool,
+    config_editor: bool,
+    history: bool,
+    selftest: bool,
+    simulate: bool,
+}
+
+impl OpenWindows {
+    fn restored(ui_state: &UiState, restore: bool) -> Self {
+        if !restore {
+            return Self::default();
+        }
+        Self {
+            schedule: ui_state.show_schedule_window,
+            new_schedule: ui_state.show_new_schedule_window,
+            sound: ui_state.show_sound_window,
+            settings: ui_state.show_settings_window,
+            oneshot: ui_state.show_oneshot_window,
+            class_block: ui_state.show_class_block_window,
+            ..Self::default()
+        }
+    }
+
+    /// 需要持久化的界面状态；批量编辑、历史版本等窗口打开时才加载内容，不记录
+    fn to_ui_state(self, timeline_view: bool, restore: bool) -> UiState {
+        if !restore {
+            return UiState {
+                timeline_view,
+                ..UiState::default()
+            };
+        }
+        UiState {
+            show_schedule_window: self.schedule,
+            show_new_schedule_window: self.new_schedule,
+            show_sound_window: self.sound,
+            show_settings_window: self.settings,
+            show_oneshot_window: self.oneshot,
+            show_class_block_window: self.class_block,
+            timeline_view,
+        }
+    }
+}
+
+/// 从文件读取的待导入配置
+struct PendingImport {
+    file_name: String,
+    config: AppConfig,
+}
+
+/// 检测线程没有心跳的状态
+struct EngineStall {
+    since: Instant,
+    reported: bool,
+}
+
+/// “测试全部音效”的进度
+struct SoundTest {
+    /// 待试听的（名称, 音效）
+    queue: VecDeque<(&'static str, SoundSource)>,
+    total: usize,
+    /// 下一段最早开始的时刻；`None` 表示正在等待当前铃声播完
+    next_at: Option<Instant>,
+    /// 无法播放的音效及原因
+    failures: Vec<String>,
+}
+
+/// 时间轴上的一个色块：启用的开始节点与其后第一个启用的结束节点配对；
+/// 标记节点则延伸到其后的第一个启用节点
+struct TimelineBlock {
+    /// 开始节点在 `periods` 中的下标
+    index: usize,
+    start: NaiveTime,
+    end: NaiveTime,
+    name: String,
+    marker: bool,
+}
+
+fn timeline_blocks(schedule: &ScheduleProfile) -> Vec<TimelineBlock> {
+    let mut points: Vec<(usize, NaiveTime, &Period)> = schedule
+        .periods
+        .iter()
+        .enumerate()
+        .filter(|(_, period)| period.enabled)
+        .filter_map(|(index, period)| period.naive_time().map(|time| (index, time, period)))
+        .collect();
+    points.sort_by_key(|(_, time, _)| *time);
+
+    let mut blocks = Vec::new();
+    let mut open: Option<(usize, NaiveTime, &Period)> = None;
+    let mut open_marker: Option<(usize, NaiveTime, &Period)> = None;
+    for (index, time, period) in points {
+        if let Some((marker_index, start, marker)) = open_marker.take() {
+            blocks.push(TimelineBlock {
+                index: marker_index,
+                start,
+                end: time,
+                name: marker.name.clone(),
+                marker: true,
+            });
+        }
+        match period.kind {
+            PeriodKind::Start => open = Some((index, time, period)),
+            PeriodKind::Prepare => {}
+            PeriodKind::Marker => open_marker = Some((index, time, period)),
+            PeriodKind::End => {
+                if let Some((start_index, start, start_period)) = open.take() {
+                    blocks.push(TimelineBlock {
+                        index: start_index,
+                        start,
+                        end: time,
+                        name: start_period.name.clone(),
+                        marker: false,
+                    });
+                }
+            }
+        }
+    }
+    blocks
+}
+
+fn minutes_of(time: NaiveTime) -> i32 {
+    (time.num_seconds_from_midnight() / 60) as i32
+}
+
+fn minutes_of_f32(time: NaiveTime) -> f32 {
+    time.num_seconds_from_midnight() as f32 / 60.0
+}
+
 fn period_row_style(period: &Period, now: &NaiveTime) -> (Color32, Color32) {
     let is_past = period.naive_time().map(|time| time < *now).unwrap_or(false);
 
@@ -1219,7 +4900,7 @@ fn period_row_style(period: &Period, now: &NaiveTime) -> (Color32, Color32) {
 
     let is_current = period.matches_now(now);
     match period.kind {
-        PeriodKind::Start => {
+        PeriodKind::Start | PeriodKind::Prepare => {
             if is_current {
                 (
                     color_period_start_current_fill(),
@@ -1239,6 +4920,7 @@ fn period_row_style(period: &Period, now: &NaiveTime) -> (Color32, Color32) {
                 (color_period_end_fill(), color_period_end_border())
             }
         }
+        PeriodKind::Marker => (color_period_marker_fill(), color_period_marker_border()),
     }
 }
 
@@ -1276,6 +4958,37 @@ fn card_no_title<R>(ui: &mut Ui, add_contents: impl FnOnce(&mut Ui) -> R) -> R {
     inner.inner
 }
 
+/// 用 galley 测量实际渲染宽度，超出 `max_width` 时截断并追加 "…"。
+/// 中文等全角字符比拉丁字符宽，不能按字符数截断。
+fn truncate_to_width(ui: &Ui, text: &str, font_id: &egui::FontId, max_width: f32) -> String {
+    truncate_with(text, max_width, |candidate| {
+        ui.fonts(|f| f.layout_no_wrap(candidate, font_id.clone(), Color32::PLACEHOLDER))
+            .rect
+            .width()
+    })
+}
+
+/// `truncate_to_width` 的截断逻辑，宽度由 `width_of` 测量
+fn truncate_with(text: &str, max_width: f32, width_of: impl Fn(String) -> f32) -> String {
+    if width_of(text.to_string()) <= max_width {
+        return text.to_string();
+    }
+
+    // 二分查找能放下的最长前缀（含省略号）
+    let chars: Vec<char> = text.chars().collect();
+    let mut lo = 0usize;
+    let mut hi = chars.len();
+    while lo + 1 < hi {
+        let mid = (lo + hi) / 2;
+        if width_of(chars[..mid].iter().collect::<String>() + "…") <= max_width {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    chars[..lo].iter().collect::<String>() + "…"
+}
+
 /// 带宽度限制的 chip：value 超出时截断并追加 "…"，不换行
 fn summary_chip_truncated(
     ui: &mut Ui,
@@ -1301,31 +5014,8 @@ fn summary_chip_truncated(
                     .color(color_text_muted()),
             );
 
-            // 用 galley 测量文字宽度，超出则逐字符截断
-            let font_id = egui::FontId::proportional(13.0);
-            let full_text = value.to_string();
-            let galley =
-                ui.fonts(|f| f.layout_no_wrap(full_text.clone(), font_id.clone(), value_color));
-
-            let display_text = if galley.rect.width() <= inner_w {
-                full_text
-            } else {
-                // 二分或线性截断，找到最长可放入的前缀
-                let chars: Vec<char> = value.chars().collect();
-                let mut lo = 0usize;
-                let mut hi = chars.len();
-                while lo + 1 < hi {
-                    let mid = (lo + hi) / 2;
-                    let candidate: String = chars[..mid].iter().collect::<String>() + "…";
-                    let g = ui.fonts(|f| f.layout_no_wrap(candidate, font_id.clone(), value_color));
-                    if g.rect.width() <= inner_w {
-                        lo = mid;
-                    } else {
-                        hi = mid;
-                    }
-                }
-                chars[..lo].iter().collect::<String>() + "…"
-            };
+            let display_text =
+                truncate_to_width(ui, value, &egui::FontId::proportional(13.0), inner_w);
 
             ui.label(
                 RichText::new(display_text)
@@ -1337,20 +5027,68 @@ fn summary_chip_truncated(
         });
 }
 
+/// 倒计时文字：不足 1 小时显示 `MM:SS`，否则显示“1小时23分”（整小时为“2小时”）
 fn format_countdown(diff_secs: i64) -> String {
+    let diff_secs = diff_secs.max(0);
     let h = diff_secs / 3600;
     let m = (diff_secs % 3600) / 60;
     let s = diff_secs % 60;
-    format!("{:02}:{:02}:{:02}", h, m, s)
+    match (h, m) {
+        (0, _) => format!("{:02}:{:02}", m, s),
+        (_, 0) => format!("{h}小时"),
+        _ => format!("{h}小时{m}分"),
+    }
+}
+
+/// 节点列表行的尺寸，随界面密度变化
+struct RowMetrics {
+    min_height: f32,
+    margin: egui::Margin,
+    /// 相邻两行的间距
+    gap: f32,
+}
+
+impl RowMetrics {
+    fn for_density(density: Density) -> Self {
+        match density {
+            Density::Comfortable => Self {
+                min_height: 38.0,
+                margin: egui::Margin::symmetric(8, 6),
+                gap: 4.0,
+            },
+            Density::Compact => Self {
+                min_height: 28.0,
+                margin: egui::Margin::symmetric(6, 2),
+                gap: 2.0,
+            },
+        }
+    }
 }
 
-fn apply_theme(ctx: &egui::Context) {
+fn apply_theme(ctx: &egui::Context, reduce_motion: bool, density: Density) {
     let mut style = (*ctx.style()).clone();
     style.visuals = egui::Visuals::light();
-
-    style.spacing.item_spacing = egui::vec2(8.0, 8.0);
-    style.spacing.button_padding = egui::vec2(12.0, 7.0);
-    style.spacing.interact_size = egui::vec2(44.0, 30.0);
+    style.animation_time = if reduce_motion {
+        0.0
+    } else {
+        egui::Style::default().animation_time
+    };
+
+    let (item_spacing, button_padding, interact_size) = match density {
+        Density::Comfortable => (
+            egui::vec2(8.0, 8.0),
+            egui::vec2(12.0, 7.0),
+            egui::vec2(44.0, 30.0),
+        ),
+        Density::Compact => (
+            egui::vec2(6.0, 4.0),
+            egui::vec2(8.0, 3.0),
+            egui::vec2(36.0, 22.0),
+        ),
+    };
+    style.spacing.item_spacing = item_spacing;
+    style.spacing.button_padding = button_padding;
+    style.spacing.interact_size = interact_size;
 
     style.text_styles.insert(
         TextStyle::Heading,
@@ -1376,13 +5114,27 @@ fn apply_theme(ctx: &egui::Context) {
     ctx.set_style(style);
 }
 
-fn status_color(status_msg: &str) -> Color32 {
-    if status_msg.contains("失败") || status_msg.contains("错误") {
-        color_danger_text()
-    } else if status_msg.contains("暂停") {
-        color_warning_text()
-    } else {
-        color_text_muted()
+/// 固定宽度弹窗的实际宽度：界面放大后不超过窗口可用宽度
+fn fit_width(ctx: &egui::Context, width: f32) -> f32 {
+    width.min(ctx.screen_rect().width() - 24.0).max(200.0)
+}
+
+fn status_color(severity: Severity) -> Color32 {
+    match severity {
+        Severity::Info => color_text_muted(),
+        Severity::Success => color_success_text(),
+        Severity::Warning => color_warning_text(),
+        Severity::Error => color_danger_text(),
+    }
+}
+
+/// 状态栏前缀图标，颜色之外再用形状区分，色弱用户也能分辨
+fn severity_icon(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Info => "ℹ",
+        Severity::Success => "✔",
+        Severity::Warning => "⚠",
+        Severity::Error => "✖",
     }
 }
 
@@ -1434,6 +5186,14 @@ fn color_period_end_current_border() -> Color32 {
     Color32::from_rgb(205, 170, 122)
 }
 
+fn color_period_marker_fill() -> Color32 {
+    Color32::from_rgb(236, 238, 250)
+}
+
+fn color_period_marker_border() -> Color32 {
+    Color32::from_rgb(180, 186, 222)
+}
+
 fn color_period_past_fill() -> Color32 {
     Color32::from_rgb(239, 241, 239)
 }
@@ -1496,3 +5256,39 @@ fn shorten_path(path: &str, max_chars: usize) -> String {
         format!("…{}", keep.iter().collect::<String>())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 等宽近似：ASCII 宽 1，其余（中文、省略号）宽 2
+    fn cell_width(text: String) -> f32 {
+        text.chars()
+            .map(|c| if c.is_ascii() { 1.0 } else { 2.0 })
+            .sum()
+    }
+
+    #[test]
+    fn truncation_measures_cjk_as_wide() {
+        assert_eq!(truncate_with("abcdef", 6.0, cell_width), "abcdef");
+        assert_eq!(truncate_with("abcdefgh", 6.0, cell_width), "abcd…");
+        // 同样 6 个字符，中文只能放下 2 个字加省略号
+        assert_eq!(truncate_with("第一节语文课", 6.0, cell_width), "第一…");
+        assert_eq!(truncate_with("8点第一节", 6.0, cell_width), "8点…");
+        // 连省略号都放不下时只剩省略号
+        assert_eq!(truncate_with("第一节", 1.0, cell_width), "…");
+        assert_eq!(truncate_with("", 0.0, cell_width), "");
+    }
+
+    #[test]
+    fn countdown_switches_format_at_one_hour() {
+        assert_eq!(format_countdown(-5), "00:00");
+        assert_eq!(format_countdown(0), "00:00");
+        assert_eq!(format_countdown(59), "00:59");
+        assert_eq!(format_countdown(60), "01:00");
+        assert_eq!(format_countdown(3599), "59:59");
+        assert_eq!(format_countdown(3600), "1小时");
+        assert_eq!(format_countdown(3660), "1小时1分");
+        assert_eq!(format_countdown(7199), "1小时59分");
+    }
+}
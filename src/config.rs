@@ -1,15 +1,201 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
-use crate::schedule::AppConfig;
+use chrono::{Local, NaiveDateTime};
 
-/// 获取配置文件路径：~/.config/wc_notice/schedule.toml (Linux)
-/// 或 %APPDATA%\wc_notice\schedule.toml (Windows)
+use crate::schedule::{AppConfig, UiState};
+
+/// 指定配置文件完整路径的环境变量
+const CONFIG_ENV: &str = "WC_NOTICE_CONFIG";
+/// 便携模式开关：设为 1 时配置文件放在程序所在目录
+const PORTABLE_ENV: &str = "WC_NOTICE_PORTABLE";
+
+/// 命令行 `--config` 或运行时“另存为”指定的配置路径，优先于其他规则（不持久化）
+static PATH_OVERRIDE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// 之后的读写改用指定路径（启动时的 `--config`，或默认目录不可写时的“另存为”）
+pub fn set_config_path(path: PathBuf) {
+    log::info!("配置路径已改为 {:?}", path);
+    *PATH_OVERRIDE.lock().unwrap() = Some(path);
+}
+
+/// 获取配置文件路径，优先级：
+/// 0. 命令行 `--config` 或运行时“另存为”指定的路径
+/// 1. `WC_NOTICE_CONFIG` 指定的完整路径
+/// 2. `WC_NOTICE_PORTABLE=1` 时为程序所在目录下的 schedule.toml
+/// 3. ~/.config/wc_notice/schedule.toml (Linux) 或 %APPDATA%\wc_notice\schedule.toml (Windows)
 pub fn config_path() -> PathBuf {
+    if let Some(path) = PATH_OVERRIDE.lock().unwrap().clone() {
+        return path;
+    }
+
+    if let Some(path) = std::env::var_os(CONFIG_ENV).filter(|path| !path.is_empty()) {
+        return PathBuf::from(path);
+    }
+
+    if std::env::var(PORTABLE_ENV).is_ok_and(|value| value.trim() == "1") {
+        match std::env::current_exe() {
+            Ok(exe) => {
+                if let Some(dir) = exe.parent() {
+                    return dir.join("schedule.toml");
+                }
+            }
+            Err(e) => log::warn!("便携模式无法获取程序路径，使用默认配置目录: {}", e),
+        }
+    }
+
     let base = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
     base.join("wc_notice").join("schedule.toml")
 }
 
+/// 允许保留的最大备份数量
+pub const MAX_BACKUP_COUNT: u8 = 20;
+
+/// 第 `index` 份备份的路径：schedule.toml.bak.N
+fn backup_path(path: &Path, index: u8) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".bak.{index}"));
+    PathBuf::from(name)
+}
+
+/// 写入前轮转备份：.bak.1 为最近一次，超出 `count` 的旧备份删除。
+/// 备份失败只记录日志，不阻止保存。
+fn rotate_backups(path: &Path, count: u8) {
+    // 调小数量后清理多出的旧备份
+    for index in count + 1..=MAX_BACKUP_COUNT {
+        let stale = backup_path(path, index);
+        if stale.exists()
+            && let Err(e) = fs::remove_file(&stale)
+        {
+            log::warn!("删除旧备份 {:?} 失败: {}", stale, e);
+        }
+    }
+
+    if count == 0 || !path.exists() {
+        return;
+    }
+
+    for index in (1..count).rev() {
+        let from = backup_path(path, index);
+        if from.exists()
+            && let Err(e) = fs::rename(&from, backup_path(path, index + 1))
+        {
+            log::warn!("备份轮转失败 {:?}: {}", from, e);
+        }
+    }
+
+    if let Err(e) = fs::copy(path, backup_path(path, 1)) {
+        log::warn!("备份配置失败: {}", e);
+    }
+}
+
+/// 保留的历史版本数量
+const MAX_SNAPSHOTS: usize = 30;
+const SNAPSHOT_FORMAT: &str = "schedule-%Y%m%d-%H%M%S";
+
+/// 一份历史版本快照
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub path: PathBuf,
+    pub taken_at: NaiveDateTime,
+    /// 快照中的时间表数量；解析失败时为 `None`
+    pub schedule_count: Option<usize>,
+}
+
+/// 历史版本目录：配置文件旁的 snapshots/
+fn snapshots_dir(path: &Path) -> PathBuf {
+    path.parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("snapshots")
+}
+
+/// 解析快照文件名（不含扩展名），返回时间戳与同一秒内的序号（无后缀为 1）
+fn parse_snapshot_stem(stem: &str) -> Option<(NaiveDateTime, u32)> {
+    if let Ok(taken_at) = NaiveDateTime::parse_from_str(stem, SNAPSHOT_FORMAT) {
+        return Some((taken_at, 1));
+    }
+    let (stem, seq) = stem.rsplit_once('-')?;
+    let seq = seq.parse().ok().filter(|seq| *seq > 1)?;
+    let taken_at = NaiveDateTime::parse_from_str(stem, SNAPSHOT_FORMAT).ok()?;
+    Some((taken_at, seq))
+}
+
+/// 按文件名时间戳列出快照，最新在前
+fn snapshot_files(dir: &Path) -> Vec<(PathBuf, NaiveDateTime)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter_map(|path| {
+            let stem = path.file_stem()?.to_str()?;
+            let (taken_at, seq) = parse_snapshot_stem(stem)?;
+            Some((path, taken_at, seq))
+        })
+        .collect();
+    files.sort_by_key(|(_, taken_at, seq)| std::cmp::Reverse((*taken_at, *seq)));
+    files
+        .into_iter()
+        .map(|(path, taken_at, _)| (path, taken_at))
+        .collect()
+}
+
+/// 写入一份带时间戳的快照，并删除超出 `MAX_SNAPSHOTS` 的旧快照。
+/// 失败只记录日志，不阻止保存。
+fn write_snapshot(path: &Path, content: &str) {
+    let dir = snapshots_dir(path);
+    if let Err(e) = fs::create_dir_all(&dir) {
+        log::warn!("创建历史版本目录失败: {}", e);
+        return;
+    }
+
+    // 同一秒内多次保存时追加序号，避免覆盖前一份快照
+    let stamp = Local::now().format(SNAPSHOT_FORMAT).to_string();
+    let file = (1..)
+        .map(|n| match n {
+            1 => dir.join(format!("{stamp}.toml")),
+            n => dir.join(format!("{stamp}-{n}.toml")),
+        })
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_default();
+    if let Err(e) = fs::write(file, content) {
+        log::warn!("写入历史版本失败: {}", e);
+        return;
+    }
+
+    for (stale, _) in snapshot_files(&dir).into_iter().skip(MAX_SNAPSHOTS) {
+        if let Err(e) = fs::remove_file(&stale) {
+            log::warn!("删除旧历史版本 {:?} 失败: {}", stale, e);
+        }
+    }
+}
+
+/// 列出当前配置文件的历史版本，最新在前
+pub fn list_snapshots() -> Vec<Snapshot> {
+    snapshot_files(&snapshots_dir(&config_path()))
+        .into_iter()
+        .map(|(path, taken_at)| {
+            let schedule_count = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| toml::from_str::<AppConfig>(&content).ok())
+                .map(|config| config.schedules.len());
+            Snapshot {
+                path,
+                taken_at,
+                schedule_count,
+            }
+        })
+        .collect()
+}
+
+/// 读取任意位置的配置文件（历史版本、导入的配置）
+pub fn read_config_file(path: &Path) -> anyhow::Result<AppConfig> {
+    let content = fs::read_to_string(path)?;
+    Ok(toml::from_str::<AppConfig>(&content)?)
+}
+
 pub fn load_config() -> AppConfig {
     let path = config_path();
 
@@ -33,14 +219,215 @@ pub fn load_config() -> AppConfig {
 }
 
 pub fn save_config(config: &AppConfig) -> anyhow::Result<()> {
+    save_config_to(&config_path(), config)
+}
+
+/// 检查配置文件能否写入，不修改配置内容：在同目录创建再删除一个临时文件，
+/// 已存在的配置文件再以追加方式打开一次（检查只读属性）
+pub fn probe_config_writable() -> anyhow::Result<PathBuf> {
     let path = config_path();
+    probe_writable(&path)?;
+    Ok(path)
+}
+
+fn probe_writable(path: &Path) -> anyhow::Result<()> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    fs::create_dir_all(dir)?;
+    let probe = dir.join(format!(".wc_notice_probe_{}", std::process::id()));
+    fs::write(&probe, "")?;
+    let _ = fs::remove_file(&probe);
+
+    if path.exists() {
+        fs::OpenOptions::new().append(true).open(path)?;
+    }
+    Ok(())
+}
 
+/// 保存到新位置，写入成功后才把之后的读写切换到该路径
+pub fn save_config_as(path: PathBuf, config: &AppConfig) -> anyhow::Result<()> {
+    save_config_to(&path, config)?;
+    set_config_path(path);
+    Ok(())
+}
+
+fn save_config_to(path: &Path, config: &AppConfig) -> anyhow::Result<()> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
     }
 
     let content = toml::to_string_pretty(config)?;
-    fs::write(&path, content)?;
+    // 内容未变化时不轮转备份、不产生新的历史版本，避免重复保存挤掉真正的旧版本；
+    // 只有界面状态（窗口开关）变化时同样视为未变化
+    let changed = fs::read_to_string(path).map_or(true, |previous| {
+        toml::from_str::<AppConfig>(&previous).map_or(true, |previous| {
+            without_ui_state(&previous) != without_ui_state(config)
+        })
+    });
+    if changed {
+        rotate_backups(path, config.backup_count.min(MAX_BACKUP_COUNT));
+    }
+    fs::write(path, &content)?;
     log::info!("配置已保存到 {:?}", path);
+    if changed {
+        write_snapshot(path, &content);
+    }
     Ok(())
 }
+
+/// 去掉界面状态后的配置内容，用于判断配置是否真正变化
+fn without_ui_state(config: &AppConfig) -> Option<String> {
+    let mut config = config.clone();
+    config.ui_state = UiState::default();
+    toml::to_string_pretty(&config).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 每个测试独立的临时目录
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("wc_notice_cfg_{}_{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn config_with(backup_count: u8, name: &str) -> AppConfig {
+        let mut config = AppConfig::default_config();
+        config.backup_count = backup_count;
+        config.schedules[0].name = name.to_string();
+        config
+    }
+
+    #[test]
+    fn identical_saves_do_not_rotate_backups() {
+        let dir = temp_dir("identical");
+        let path = dir.join("schedule.toml");
+
+        save_config_to(&path, &config_with(3, "A")).unwrap();
+        save_config_to(&path, &config_with(3, "B")).unwrap();
+        let first = fs::read_to_string(backup_path(&path, 1)).unwrap();
+        assert!(first.contains("name = \"A\""));
+
+        // 内容相同的保存不应把 A 挤到 .bak.2，也不产生新快照
+        let snapshots = snapshot_files(&snapshots_dir(&path)).len();
+        fs::remove_dir_all(snapshots_dir(&path)).unwrap();
+        save_config_to(&path, &config_with(3, "B")).unwrap();
+        save_config_to(&path, &config_with(3, "B")).unwrap();
+        assert_eq!(fs::read_to_string(backup_path(&path, 1)).unwrap(), first);
+        assert!(!backup_path(&path, 2).exists());
+        assert!(snapshots > 0);
+        assert!(snapshot_files(&snapshots_dir(&path)).is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn ui_state_changes_are_saved_without_backup_or_snapshot() {
+        let dir = temp_dir("ui_state");
+        let path = dir.join("schedule.toml");
+        save_config_to(&path, &config_with(3, "A")).unwrap();
+        fs::remove_dir_all(snapshots_dir(&path)).unwrap();
+
+        // 开关窗口只写盘保存界面状态，不轮转备份、不产生历史版本
+        let mut config = config_with(3, "A");
+        config.ui_state.show_settings_window = true;
+        save_config_to(&path, &config).unwrap();
+        let saved = read_config_file(&path).unwrap();
+        assert!(saved.ui_state.show_settings_window);
+        assert!(!backup_path(&path, 1).exists());
+        assert!(snapshot_files(&snapshots_dir(&path)).is_empty());
+
+        config.schedules[0].name = "B".to_string();
+        save_config_to(&path, &config).unwrap();
+        assert!(backup_path(&path, 1).exists());
+        assert_eq!(snapshot_files(&snapshots_dir(&path)).len(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn probe_leaves_config_and_backups_untouched() {
+        let dir = temp_dir("probe");
+        let path = dir.join("schedule.toml");
+        save_config_to(&path, &config_with(3, "A")).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        let entries = || fs::read_dir(&dir).unwrap().count();
+        let before = entries();
+
+        probe_writable(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        assert_eq!(entries(), before);
+        assert!(!backup_path(&path, 1).exists());
+
+        // 目录不可用时报告失败
+        let blocker = dir.join("not_a_dir");
+        fs::write(&blocker, "").unwrap();
+        assert!(probe_writable(&blocker.join("schedule.toml")).is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn snapshots_in_the_same_second_do_not_overwrite() {
+        let dir = temp_dir("snapshot_same_second");
+        let path = dir.join("schedule.toml");
+        for content in ["A", "B", "C"] {
+            write_snapshot(&path, content);
+        }
+
+        let files = snapshot_files(&snapshots_dir(&path));
+        let contents: Vec<String> = files
+            .iter()
+            .map(|(file, _)| fs::read_to_string(file).unwrap())
+            .collect();
+        assert_eq!(contents, ["C", "B", "A"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn failed_save_as_keeps_previous_path() {
+        let dir = temp_dir("save_as");
+        let blocker = dir.join("not_a_dir");
+        fs::write(&blocker, "").unwrap();
+
+        let before = config_path();
+        let result = save_config_as(blocker.join("schedule.toml"), &config_with(3, "A"));
+        assert!(result.is_err());
+        assert_eq!(config_path(), before);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn lowering_backup_count_prunes_older_backups() {
+        let dir = temp_dir("prune");
+        let path = dir.join("schedule.toml");
+        save_config_to(&path, &config_with(5, "0")).unwrap();
+        for version in 1..=5 {
+            save_config_to(&path, &config_with(5, &version.to_string())).unwrap();
+        }
+        assert!((1..=5).all(|index| backup_path(&path, index).exists()));
+
+        save_config_to(&path, &config_with(2, "6")).unwrap();
+        assert!(
+            fs::read_to_string(backup_path(&path, 1))
+                .unwrap()
+                .contains("name = \"5\"")
+        );
+        assert!(
+            fs::read_to_string(backup_path(&path, 2))
+                .unwrap()
+                .contains("name = \"4\"")
+        );
+        assert!((3..=5).all(|index| !backup_path(&path, index).exists()));
+
+        // 设为 0 时不再保留任何备份
+        save_config_to(&path, &config_with(0, "7")).unwrap();
+        assert!((1..=MAX_BACKUP_COUNT).all(|index| !backup_path(&path, index).exists()));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,108 +1,1680 @@
 use std::collections::HashSet;
-use std::sync::{Arc, Mutex};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex, mpsc};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use chrono::{Local, Timelike};
+use chrono::{
+    DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeDelta, Timelike,
+    Utc,
+};
 
-use crate::notifier::{play_sound_for_period, send_notification};
-use crate::schedule::AppConfig;
+use crate::feed::FeedEvent;
+use crate::notifier::{
+    detect_meeting, play_sound_for_period, send_notification, system_volume_low,
+};
+use crate::schedule::{AppConfig, Period, PeriodKind, ScheduleMode, ScheduleProfile, SoundSlots};
+
+/// 无临近节点时的最长休眠时长，保证配置变化、锁屏状态等在该时长内被感知
+const MAX_IDLE_SLEEP: Duration = Duration::from_secs(5);
+/// 引擎最多每 5 秒醒来一次，超过该时长没有心跳视为检测线程已停止
+pub const HEARTBEAT_STALE: Duration = Duration::from_secs(15);
+/// 检测循环 panic 后等待该时长再重新进入，避免持续出错时空转
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+/// 在目标秒开始后稍晚醒来，避免因调度误差落在上一秒
+const WAKE_MARGIN: Duration = Duration::from_millis(50);
+/// 系统音量检查间隔（开启“检查系统音量”时）
+const VOLUME_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// 系统音量静音或过低时上报的状态消息
+const LOW_VOLUME_WARNING: &str = "系统音量过低/静音，铃声可能听不到";
+/// 墙钟与单调时钟的偏差超过该值即视为系统时间被手动调整
+const CLOCK_JUMP_THRESHOLD: TimeDelta = TimeDelta::seconds(30);
+/// 检测到系统时间跳变时上报的状态消息
+const CLOCK_JUMP_WARNING: &str = "系统时间已变化，已重置检测状态";
+
+/// 状态消息的严重程度，决定状态栏的颜色与图标
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+/// 检测线程使用的时间源，默认 `SystemClock`；可替换为模拟时钟以驱动确定性的触发测试
+pub trait Clock: Send + Sync {
+    /// 当前本地时间
+    fn now(&self) -> DateTime<Local>;
+
+    /// 单调时钟，用于检测系统时间跳变；模拟时钟应与 `now()` 同步推进
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// 系统时钟
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+/// 防重复记录所属的“日期”：`day_start_hour` 点之前仍算作前一天
+fn school_day(now: NaiveDateTime, day_start_hour: u32) -> NaiveDate {
+    let shift = TimeDelta::hours(i64::from(day_start_hour.min(23)));
+    (now - shift).date()
+}
+
+/// 防重复触发状态。
+///
+/// - 按“日期（以 `day_start_hour` 点为界）+ 节点时间”记录当天已触发的节点：夏令时回拨使同一墙钟时刻重复出现时
+///   不会再次触发；夏令时前拨跳过的时刻不会补响。
+/// - 用 UTC 墙钟与单调时钟比对检测系统时间被手动调整（夏令时只改变时区偏移，不算跳变），
+///   检测到跳变后清空记录，之后按新的墙钟时间正常触发。
+#[derive(Debug, Default)]
+struct FiredState {
+    date: Option<NaiveDate>,
+    /// 已触发节点：(时间, 类型, 名称)，同一时刻的多个节点各自独立
+    fired: HashSet<(NaiveTime, PeriodKind, String)>,
+    last_tick: Option<(Instant, DateTime<Utc>)>,
+    /// 最近一次整点报时所在的 (日期, 小时)
+    last_chime: Option<(NaiveDate, u32)>,
+}
+
+impl FiredState {
+    /// 记录本次检测的时间点；检测到系统时间跳变时清空记录并返回跳变秒数
+    fn observe_clock(&mut self, mono: Instant, wall: DateTime<Utc>) -> Option<i64> {
+        let previous = self.last_tick.replace((mono, wall));
+        let (prev_mono, prev_wall) = previous?;
+
+        let elapsed = TimeDelta::from_std(mono.duration_since(prev_mono)).ok()?;
+        let drift = (wall - prev_wall) - elapsed;
+        if drift.abs() < CLOCK_JUMP_THRESHOLD {
+            return None;
+        }
+
+        self.date = None;
+        self.fired.clear();
+        self.last_chime = None;
+        Some(drift.num_seconds())
+    }
+
+    /// 跨日后清空前一天的记录
+    fn roll_date(&mut self, today: NaiveDate) {
+        if self.date != Some(today) {
+            self.date = Some(today);
+            self.fired.clear();
+        }
+    }
+
+    /// 标记节点已触发；当天已触发过返回 `false`
+    fn mark_fired(&mut self, time: NaiveTime, period: &Period) -> bool {
+        self.fired.insert((time, period.kind, period.name.clone()))
+    }
+
+    /// 标记该小时已报时；已报过返回 `false`
+    fn mark_chimed(&mut self, date: NaiveDate, hour: u32) -> bool {
+        self.last_chime.replace((date, hour)) != Some((date, hour))
+    }
+}
+
+/// 临时提醒到点后仍视为命中的时长，避免检测线程偶尔跳过某一秒导致漏响
+const ONESHOT_WINDOW: TimeDelta = TimeDelta::seconds(60);
+
+/// 临时提醒：不属于任何时间表，触发一次后自动移除（不持久化）
+#[derive(Debug, Clone)]
+pub struct OneShot {
+    pub id: u64,
+    /// 完整的日期与时间：跨午夜的提醒不会在当天提前命中，错过的也不会隔天同一时刻再响
+    pub at: NaiveDateTime,
+    pub name: String,
+    pub kind: PeriodKind,
+}
+
+impl OneShot {
+    /// 到点后 `ONESHOT_WINDOW` 内视为命中
+    fn is_due(&self, now: &NaiveDateTime) -> bool {
+        let late = *now - self.at;
+        late >= TimeDelta::zero() && late < ONESHOT_WINDOW
+    }
+
+    /// 已超过命中窗口（如暂停、锁屏期间到点），不会再触发
+    fn is_expired(&self, now: &NaiveDateTime) -> bool {
+        *now - self.at >= ONESHOT_WINDOW
+    }
+}
 
 /// 时间检测引擎
 pub struct Engine {
     pub config: Arc<Mutex<AppConfig>>,
     pub enabled: Arc<Mutex<bool>>,
-    /// 上次触发的分钟数（防重复触发）
-    last_triggered_minute: Arc<Mutex<Option<u32>>>,
+    /// 当天已触发节点（防重复触发）
+    fired_state: Arc<Mutex<FiredState>>,
     /// 后台线程向 UI 上报状态消息
-    status_events: Arc<Mutex<Vec<String>>>,
+    status_events: Arc<Mutex<Vec<(Severity, String)>>>,
+    /// 已上报过的音效警告（每种只上报一次）
+    warned_once: Arc<Mutex<HashSet<String>>>,
+    /// 计时器模式下已响过的节点（时间表 id, 节点），由界面取走并从时间表移除
+    fired_timers: Arc<Mutex<Vec<(u64, Period)>>>,
+    /// 当前时间表中当前所处节点（时间表 id, 下标），每次醒来更新
+    current_period: Arc<Mutex<Option<(u64, usize)>>>,
+    /// 有提醒触发、等待界面显示视觉提醒
+    visual_alert_pending: Arc<Mutex<bool>>,
+    /// 待触发的临时提醒
+    oneshots: Arc<Mutex<Vec<OneShot>>>,
+    next_oneshot_id: Mutex<u64>,
+    /// 检测线程最近一次醒来的时间（自检用）
+    heartbeat: Arc<Mutex<Instant>>,
+    /// 触发事件推送（开启 `event_feed` 时由 `set_event_feed` 设置）
+    event_feed: Arc<Mutex<Option<mpsc::Sender<FeedEvent>>>>,
+    /// 唤醒检测线程（配置/状态变化后立即重新计算休眠时长）
+    wake: Arc<(Mutex<bool>, Condvar)>,
+    clock: Arc<dyn Clock>,
 }
 
 impl Engine {
     pub fn new(config: AppConfig) -> Self {
+        Self::new_with_clock(config, Arc::new(SystemClock))
+    }
+
+    /// 使用指定时间源创建引擎
+    pub fn new_with_clock(config: AppConfig, clock: Arc<dyn Clock>) -> Self {
+        let enabled = config.enabled;
         Self {
             config: Arc::new(Mutex::new(config)),
-            enabled: Arc::new(Mutex::new(true)),
-            last_triggered_minute: Arc::new(Mutex::new(None)),
+            enabled: Arc::new(Mutex::new(enabled)),
+            fired_state: Arc::new(Mutex::new(FiredState::default())),
             status_events: Arc::new(Mutex::new(Vec::new())),
+            warned_once: Arc::new(Mutex::new(HashSet::new())),
+            fired_timers: Arc::new(Mutex::new(Vec::new())),
+            current_period: Arc::new(Mutex::new(None)),
+            visual_alert_pending: Arc::new(Mutex::new(false)),
+            oneshots: Arc::new(Mutex::new(Vec::new())),
+            next_oneshot_id: Mutex::new(1),
+            heartbeat: Arc::new(Mutex::new(clock.instant())),
+            event_feed: Arc::new(Mutex::new(None)),
+            wake: Arc::new((Mutex::new(false), Condvar::new())),
+            clock,
         }
     }
 
-    /// 启动后台检测线程，每秒检查一次系统时间
+    /// 启动后台检测线程。
+    ///
+    /// 线程按“距下一个待触发节点的时长”休眠（最长 `MAX_IDLE_SLEEP`），
+    /// 而不是每秒轮询；配置或状态变化时通过 `wake` 提前唤醒。
+    ///
+    /// 检测、响铃和通知都在该线程内完成，不依赖界面的 `update()`：
+    /// 窗口最小化、隐藏到托盘甚至界面卡住时仍会按时响铃。界面只通过
+    /// `update_config` / `take_status_events` 等与引擎交换数据。
+    ///
+    /// 循环内的 panic 不会让提醒就此停止：记录日志并上报后清除各共享锁的中毒标记，
+    /// 稍后重新进入循环。防重复记录保存在共享状态中，重启后不会重复响铃。
     pub fn start(&self) {
-        let config = Arc::clone(&self.config);
-        let enabled = Arc::clone(&self.enabled);
-        let last_triggered = Arc::clone(&self.last_triggered_minute);
-        let status_events = Arc::clone(&self.status_events);
+        let mut detector = self.detector();
+        let clock = Arc::clone(&self.clock);
+        let wake = Arc::clone(&self.wake);
+        let heartbeat = Arc::clone(&self.heartbeat);
+        let status_sink = Arc::clone(&self.status_events);
 
         thread::spawn(move || {
-            let mut warned_once: HashSet<String> = HashSet::new();
-            log::info!("时间检测引擎已启动");
-
-            loop {
-                thread::sleep(Duration::from_secs(1));
-
-                if !*enabled.lock().unwrap() {
-                    continue;
+            supervise(&status_sink, |restarted| {
+                if restarted {
+                    detector.clear_poison();
+                    wake.0.clear_poison();
+                    heartbeat.clear_poison();
+                    thread::sleep(RESTART_DELAY);
                 }
+                log::info!("时间检测引擎已启动");
 
-                let now = Local::now().naive_local().time();
-                let current_minute = now.hour() * 60 + now.minute();
+                loop {
+                    let delay = detector.next_wake_delay(&clock.now());
+                    {
+                        let (woken, cvar) = &*wake;
+                        let guard = woken.lock().unwrap();
+                        let (mut guard, _) = cvar
+                            .wait_timeout_while(guard, delay, |woken| !*woken)
+                            .unwrap();
+                        *guard = false;
+                    }
+                    *heartbeat.lock().unwrap() = clock.instant();
 
-                {
-                    let last = last_triggered.lock().unwrap();
-                    if *last == Some(current_minute) {
-                        continue;
+                    if let Some((triggers, settings)) = detector.tick(clock.now(), clock.instant())
+                    {
+                        dispatch(
+                            triggers,
+                            &settings,
+                            &detector.warned_once,
+                            &detector.status_events,
+                        );
                     }
                 }
+            });
+        });
+    }
 
-                let triggered = {
-                    let cfg = config.lock().unwrap();
-                    cfg.active_schedule().and_then(|schedule| {
-                        schedule
-                            .periods
-                            .iter()
-                            .find(|period| period.matches_now(&now))
-                            .cloned()
-                            .map(|period| (period, schedule.sound.clone()))
-                    })
-                };
-
-                if let Some((period, sound_slots)) = triggered {
-                    log::info!("命中节点: {} - {}", period.name, period.kind.label());
-
-                    if let Some(warning) = play_sound_for_period(period.kind, &sound_slots) {
-                        if warned_once.insert(warning.clone()) {
-                            status_events.lock().unwrap().push(warning);
-                        }
-                    }
+    /// 检测线程使用的状态：共享数据的句柄，启动宽限期从此刻开始计时
+    fn detector(&self) -> Detector {
+        Detector {
+            config: Arc::clone(&self.config),
+            enabled: Arc::clone(&self.enabled),
+            fired_state: Arc::clone(&self.fired_state),
+            status_events: Arc::clone(&self.status_events),
+            warned_once: Arc::clone(&self.warned_once),
+            fired_timers: Arc::clone(&self.fired_timers),
+            visual_alert_pending: Arc::clone(&self.visual_alert_pending),
+            current_period: Arc::clone(&self.current_period),
+            oneshots: Arc::clone(&self.oneshots),
+            event_feed: Arc::clone(&self.event_feed),
+            started: self.clock.instant(),
+            auto_paused: false,
+            last_volume_check: None,
+            volume_low: false,
+            last_offset: None,
+        }
+    }
 
-                    send_notification(&format!("🔔 {}", period.kind.label()), &period.name);
+    /// 模拟一天（试运行）：从 `day_start_hour` 点起按 `step` 推进 24 小时（越过午夜后接着算），
+    /// 用与检测线程相同的匹配规则（容差取一个步长，同一节点只触发一次）收集会触发的节点，
+    /// 不响铃也不休眠。返回 (触发时刻, 节点、音效与触发后状态的描述)，按模拟的先后排列
+    pub fn simulate_day(
+        schedule: &ScheduleProfile,
+        step: Duration,
+        day_start_hour: u32,
+    ) -> Vec<(NaiveTime, String)> {
+        let step = TimeDelta::from_std(step)
+            .unwrap_or(TimeDelta::seconds(1))
+            .max(TimeDelta::seconds(1));
+        let tolerance = step - TimeDelta::seconds(1);
+        let start = NaiveTime::from_hms_opt(day_start_hour.min(23), 0, 0).unwrap_or(NaiveTime::MIN);
+        let one_second = TimeDelta::seconds(1);
+        // 23:59:59 与一天的最后一秒都要检查一次，步长越过它们时不漏掉之前的节点
+        let last = TimeDelta::days(1) - one_second;
+        let before_midnight = TimeDelta::days(1) - (start - NaiveTime::MIN) - one_second;
 
-                    let mut last = last_triggered.lock().unwrap();
-                    *last = Some(current_minute);
+        let mut state = FiredState::default();
+        let mut hits = Vec::new();
+        let mut elapsed = TimeDelta::zero();
+        loop {
+            let (now, _) = start.overflowing_add_signed(elapsed);
+            for period in &schedule.periods {
+                if !period.kind.fires() || !period.matches_within(&now, tolerance) {
+                    continue;
+                }
+                if let Some(time) = period.naive_time()
+                    && state.mark_fired(time, period)
+                {
+                    let sound = if period.silent {
+                        "静音（仅通知）".to_string()
+                    } else {
+                        schedule.sound_for(period).for_kind(period.kind).describe()
+                    };
+                    hits.push((
+                        now,
+                        format!(
+                            "{} - {}，音效: {}，之后状态: {}",
+                            period.name,
+                            period.kind.label(),
+                            sound,
+                            schedule.current_status(&now, "待机")
+                        ),
+                    ));
                 }
             }
-        });
+            if elapsed == last {
+                break;
+            }
+            // 补查边界后仍按原来的步长网格推进
+            let step_secs = step.num_seconds();
+            let mut next = TimeDelta::seconds((elapsed.num_seconds() / step_secs + 1) * step_secs);
+            for boundary in [before_midnight, last] {
+                if elapsed < boundary && next > boundary {
+                    next = boundary;
+                }
+            }
+            elapsed = next;
+        }
+        hits
+    }
+
+    /// 立即按节点触发一次音效和通知（用于试响），不影响检测线程的防重复记录
+    pub fn fire_period(&self, period: &Period, slots: &SoundSlots) {
+        let settings = TriggerSettings::from_config(&self.config.lock().unwrap());
+        let trigger = Trigger {
+            source: "手动触发节点",
+            schedule_id: None,
+            schedule_name: None,
+            time: period.naive_time().unwrap_or_default(),
+            kind: period.kind,
+            name: period.name.clone(),
+            slots: slots.clone(),
+            note: period.note.clone(),
+            silent: period.silent,
+            notify: true,
+        };
+        if settings.visual_alert {
+            *self.visual_alert_pending.lock().unwrap() = true;
+        }
+        fire(&trigger, &settings, &self.warned_once, &self.status_events);
+    }
+
+    /// 设置触发事件推送的发送端（检测线程命中节点时发送，手动试响不推送）
+    pub fn set_event_feed(&self, feed: mpsc::Sender<FeedEvent>) {
+        *self.event_feed.lock().unwrap() = Some(feed);
     }
 
     pub fn update_config(&self, new_config: AppConfig) {
-        let mut cfg = self.config.lock().unwrap();
-        *cfg = new_config;
+        {
+            let mut cfg = self.config.lock().unwrap();
+            *cfg = new_config;
+            cfg.resolve_solar_times(&self.clock.now());
+        }
+        self.wake();
     }
 
     pub fn toggle_enabled(&self) -> bool {
-        let mut enabled = self.enabled.lock().unwrap();
-        *enabled = !*enabled;
-        *enabled
+        let enabled = {
+            let mut enabled = self.enabled.lock().unwrap();
+            *enabled = !*enabled;
+            *enabled
+        };
+        self.wake();
+        enabled
+    }
+
+    /// 按配置设置暂停状态（导入、恢复历史版本等整体替换配置时）
+    pub fn set_enabled(&self, enabled: bool) {
+        *self.enabled.lock().unwrap() = enabled;
+        self.wake();
+    }
+
+    fn wake(&self) {
+        let (woken, cvar) = &*self.wake;
+        *woken.lock().unwrap() = true;
+        cvar.notify_one();
     }
 
     pub fn is_enabled(&self) -> bool {
         *self.enabled.lock().unwrap()
     }
 
-    pub fn take_status_events(&self) -> Vec<String> {
+    /// 当前时间表中当前所处节点（时间表 id, 下标），变化时界面滚动并高亮该行
+    pub fn current_period(&self) -> Option<(u64, usize)> {
+        *self.current_period.lock().unwrap()
+    }
+
+    /// 检测线程是否已超过 `HEARTBEAT_STALE` 没有醒来
+    pub fn is_stalled(&self) -> bool {
+        self.heartbeat_age() > HEARTBEAT_STALE
+    }
+
+    /// 距检测线程最近一次醒来的时长
+    pub fn heartbeat_age(&self) -> Duration {
+        self.clock
+            .instant()
+            .duration_since(*self.heartbeat.lock().unwrap())
+    }
+
+    pub fn take_status_events(&self) -> Vec<(Severity, String)> {
         let mut events = self.status_events.lock().unwrap();
         std::mem::take(&mut *events)
     }
+
+    /// 自上次调用以来是否有提醒需要显示视觉提醒
+    pub fn take_visual_alert(&self) -> bool {
+        std::mem::take(&mut *self.visual_alert_pending.lock().unwrap())
+    }
+
+    pub fn take_fired_timers(&self) -> Vec<(u64, Period)> {
+        let mut fired = self.fired_timers.lock().unwrap();
+        std::mem::take(&mut *fired)
+    }
+
+    /// 添加一个临时提醒，返回其 id（用于取消）
+    pub fn add_oneshot(&self, at: NaiveDateTime, name: String, kind: PeriodKind) -> u64 {
+        let id = {
+            let mut next_id = self.next_oneshot_id.lock().unwrap();
+            let id = *next_id;
+            *next_id += 1;
+            id
+        };
+        let at = at.with_nanosecond(0).unwrap_or(at);
+        log::info!("已添加临时提醒: {} @ {}", name, at.format("%m-%d %H:%M:%S"));
+        self.oneshots
+            .lock()
+            .unwrap()
+            .push(OneShot { id, at, name, kind });
+        self.wake();
+        id
+    }
+
+    pub fn cancel_oneshot(&self, id: u64) -> bool {
+        let mut pending = self.oneshots.lock().unwrap();
+        let before = pending.len();
+        pending.retain(|oneshot| oneshot.id != id);
+        pending.len() != before
+    }
+
+    /// 待触发的临时提醒（按时间排序）
+    pub fn pending_oneshots(&self) -> Vec<OneShot> {
+        let mut pending = self.oneshots.lock().unwrap().clone();
+        pending.sort_by_key(|oneshot| oneshot.at);
+        pending
+    }
+}
+
+/// 检测线程的状态：与 `Engine` 共享的数据，以及跨轮次保留的局部状态。
+/// 检测循环 panic 重启后沿用同一份状态（启动宽限期不重新计时）
+struct Detector {
+    config: Arc<Mutex<AppConfig>>,
+    enabled: Arc<Mutex<bool>>,
+    fired_state: Arc<Mutex<FiredState>>,
+    status_events: Arc<Mutex<Vec<(Severity, String)>>>,
+    warned_once: Arc<Mutex<HashSet<String>>>,
+    fired_timers: Arc<Mutex<Vec<(u64, Period)>>>,
+    visual_alert_pending: Arc<Mutex<bool>>,
+    current_period: Arc<Mutex<Option<(u64, usize)>>>,
+    oneshots: Arc<Mutex<Vec<OneShot>>>,
+    event_feed: Arc<Mutex<Option<mpsc::Sender<FeedEvent>>>>,
+    started: Instant,
+    /// 锁屏自动暂停与手动暂停相互独立：解锁后自然回到用户原先的状态
+    auto_paused: bool,
+    last_volume_check: Option<Instant>,
+    volume_low: bool,
+    last_offset: Option<FixedOffset>,
+}
+
+impl Detector {
+    fn next_wake_delay(&self, now: &DateTime<Local>) -> Duration {
+        let cfg = self.config.lock().unwrap();
+        let pending = self.oneshots.lock().unwrap();
+        next_wake_delay(&cfg, &pending, now)
+    }
+
+    /// 按 `now_local`（单调时钟读数为 `mono`）检测一轮：更新状态、收集命中的提醒并推送事件，
+    /// 返回需要分发的提醒与触发设置；暂停（手动或锁屏）时返回 `None`。不响铃，便于用模拟时钟测试
+    fn tick(
+        &mut self,
+        now_local: DateTime<Local>,
+        mono: Instant,
+    ) -> Option<(Vec<Trigger>, TriggerSettings)> {
+        if let Some(jump) = self
+            .fired_state
+            .lock()
+            .unwrap()
+            .observe_clock(mono, now_local.with_timezone(&Utc))
+        {
+            log::warn!("检测到系统时间跳变 {} 秒，已重置触发记录", jump);
+            self.status_events
+                .lock()
+                .unwrap()
+                .push((Severity::Warning, CLOCK_JUMP_WARNING.to_string()));
+        }
+
+        // 时区或夏令时切换：墙钟整体平移，已触发记录按本地日期与时刻保留，
+        // 回拨时不会重响、前拨跳过的时刻不会补响，这里只提示一次
+        let offset = now_local.offset().fix();
+        if let Some(previous) = self.last_offset.replace(offset)
+            && previous != offset
+        {
+            let msg = format!("系统时区已变化（UTC{previous} → UTC{offset}），已按新时间继续检测");
+            log::warn!("{}", msg);
+            self.status_events
+                .lock()
+                .unwrap()
+                .push((Severity::Warning, msg));
+        }
+
+        // 音量状态变为过低时提示一次，恢复后再次过低会重新提示
+        let check_volume = self.config.lock().unwrap().check_system_volume;
+        if check_volume
+            && self
+                .last_volume_check
+                .is_none_or(|checked| mono.duration_since(checked) >= VOLUME_CHECK_INTERVAL)
+        {
+            self.last_volume_check = Some(mono);
+            if let Some(low) = system_volume_low() {
+                if low && !self.volume_low {
+                    log::warn!("{}", LOW_VOLUME_WARNING);
+                    self.status_events
+                        .lock()
+                        .unwrap()
+                        .push((Severity::Warning, LOW_VOLUME_WARNING.to_string()));
+                }
+                self.volume_low = low;
+            }
+        }
+
+        // 暂停时也更新，供界面高亮当前节点
+        *self.current_period.lock().unwrap() = self
+            .config
+            .lock()
+            .unwrap()
+            .active_schedule()
+            .and_then(|schedule| {
+                let now = schedule.zone_now(&now_local).time();
+                Some((schedule.id, schedule.current_period_index(&now)?))
+            });
+
+        // 暂停、锁屏期间错过的临时提醒直接丢弃，恢复后不会补响
+        self.oneshots.lock().unwrap().retain(|oneshot| {
+            let expired = oneshot.is_expired(&now_local.naive_local());
+            if expired {
+                log::info!(
+                    "临时提醒已错过，不再触发: {} @ {}",
+                    oneshot.name,
+                    oneshot.at.format("%m-%d %H:%M:%S")
+                );
+            }
+            !expired
+        });
+
+        // 全局暂停是总开关，优先于时间表的单独暂停（见 `effective_schedule`）
+        if !*self.enabled.lock().unwrap() {
+            return None;
+        }
+
+        let pause_when_locked = self.config.lock().unwrap().pause_when_locked;
+        let locked = pause_when_locked && session_locked();
+        if locked != self.auto_paused {
+            self.auto_paused = locked;
+            let msg = if locked {
+                "已锁屏，提醒自动暂停"
+            } else {
+                "已解锁，提醒自动恢复"
+            };
+            log::info!("{}", msg);
+            self.status_events
+                .lock()
+                .unwrap()
+                .push((Severity::Info, msg.to_string()));
+        }
+        if self.auto_paused {
+            return None;
+        }
+
+        let now = now_local.naive_local();
+
+        // 临时提醒不参与防重，命中后直接从队列移除
+        let due: Vec<OneShot> = {
+            let mut pending = self.oneshots.lock().unwrap();
+            let (due, rest) = std::mem::take(&mut *pending)
+                .into_iter()
+                .partition(|oneshot| oneshot.is_due(&now));
+            *pending = rest;
+            due
+        };
+
+        let (mut triggers, chime, timer_fired, settings) = {
+            let mut cfg = self.config.lock().unwrap();
+            // 相对日出/日落的节点按当天日期换算（跨日后才会变化）
+            cfg.resolve_solar_times(&now_local);
+            let mut state = self.fired_state.lock().unwrap();
+            // 时间表设置了时区时，节点匹配与跨日都按该时区的墙钟计算
+            let schedule = cfg.effective_schedule();
+            let schedule_now = schedule.map_or(now_local.naive_local(), |s| s.zone_now(&now_local));
+            state.roll_date(school_day(schedule_now, cfg.day_start_hour));
+            if let Some(error) = schedule.and_then(|s| s.timezone_error())
+                && self.warned_once.lock().unwrap().insert(error.clone())
+            {
+                log::warn!("{}", error);
+                self.status_events
+                    .lock()
+                    .unwrap()
+                    .push((Severity::Warning, error));
+            }
+
+            let oneshot_slots = cfg
+                .active_schedule()
+                .map(|schedule| schedule.sound.clone())
+                .unwrap_or_default();
+            let mut triggers: Vec<Trigger> = due
+                .into_iter()
+                .map(|oneshot| Trigger {
+                    source: "临时提醒命中",
+                    schedule_id: None,
+                    schedule_name: None,
+                    time: oneshot.at.time(),
+                    kind: oneshot.kind,
+                    name: oneshot.name,
+                    slots: oneshot_slots.clone(),
+                    note: None,
+                    silent: false,
+                    notify: true,
+                })
+                .collect();
+
+            let tolerance = TimeDelta::seconds(i64::from(cfg.trigger_tolerance_secs));
+            let mut timer_fired = Vec::new();
+            if let Some(schedule) = schedule {
+                collect_schedule_triggers(
+                    schedule,
+                    schedule_now.time(),
+                    tolerance,
+                    &mut state,
+                    &mut triggers,
+                    &mut timer_fired,
+                );
+            }
+            let chime = hourly_chime(&cfg, &mut state, &now_local, schedule_now.time(), tolerance);
+            (
+                triggers,
+                chime,
+                timer_fired,
+                TriggerSettings::from_config(&cfg),
+            )
+        };
+
+        // 计时器节点只响一次：引擎先从自己的配置中移除（不依赖界面是否在运行），
+        // 再交给界面同步移除并保存（宽限期内跳过的也一并移除）
+        if !timer_fired.is_empty() {
+            let mut cfg = self.config.lock().unwrap();
+            for (schedule_id, period) in &timer_fired {
+                cfg.remove_period(*schedule_id, period);
+            }
+        }
+        self.fired_timers.lock().unwrap().extend(timer_fired);
+
+        // 开机自启时避免刚启动就响铃；跳过的节点已记为触发，宽限期后不会补响
+        let in_grace = mono.duration_since(self.started) < settings.startup_grace;
+        if in_grace {
+            triggers.retain(|trigger| {
+                let skip = trigger.schedule_id.is_some();
+                if skip {
+                    log::info!("启动宽限期内，跳过节点 {}", trigger.name);
+                }
+                !skip
+            });
+        }
+
+        if let Some(feed) = self.event_feed.lock().unwrap().as_ref() {
+            for trigger in &triggers {
+                let _ = feed.send(trigger.feed_event(&now_local));
+            }
+        }
+        // 整点报时只响铃：不推送事件、不发通知
+        triggers.extend(chime.filter(|_| !in_grace));
+        if settings.visual_alert && !triggers.is_empty() {
+            *self.visual_alert_pending.lock().unwrap() = true;
+        }
+        Some((triggers, settings))
+    }
+
+    /// 检测循环 panic 后清除各共享锁的中毒标记
+    fn clear_poison(&self) {
+        self.config.clear_poison();
+        self.enabled.clear_poison();
+        self.fired_state.clear_poison();
+        self.status_events.clear_poison();
+        self.warned_once.clear_poison();
+        self.fired_timers.clear_poison();
+        self.visual_alert_pending.clear_poison();
+        self.current_period.clear_poison();
+        self.oneshots.clear_poison();
+        self.event_feed.clear_poison();
+    }
+}
+
+/// 反复运行检测循环 `body`：panic 时记录日志、上报错误，并以 `restarted = true` 重新进入；
+/// `body` 正常返回时结束
+fn supervise(status_events: &Mutex<Vec<(Severity, String)>>, mut body: impl FnMut(bool)) {
+    let mut restarted = false;
+    while let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| body(restarted))) {
+        let reason = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "未知错误".to_string());
+        log::error!("检测线程出错（{}），即将重新启动", reason);
+        status_events.clear_poison();
+        status_events
+            .lock()
+            .unwrap()
+            .push((Severity::Error, "检测线程出错，已自动重新启动".to_string()));
+        restarted = true;
+    }
+}
+
+/// 计算检测线程下一次醒来前的休眠时长：
+/// 恰好在下一个启用节点/临时提醒所在秒开始后 `WAKE_MARGIN` 醒来，最长 `MAX_IDLE_SLEEP`。
+fn next_wake_delay(cfg: &AppConfig, oneshots: &[OneShot], now: &DateTime<Local>) -> Duration {
+    let local = now.naive_local().time();
+    // 节点时间按时间表时区计算，换算成距本地当前时刻的间隔后再与其他时间比较
+    let schedule = cfg.effective_schedule();
+    let zone_shift = schedule.map_or(TimeDelta::zero(), |schedule| {
+        schedule.zone_now(now).time() - local
+    });
+    let period_times = schedule
+        .into_iter()
+        .flat_map(|schedule| schedule.periods.iter())
+        .filter(|period| period.enabled)
+        .filter_map(|period| period.naive_time())
+        .map(|time| time - zone_shift);
+    // 临时提醒带日期，直接按完整时刻计算间隔
+    let oneshot_delays = oneshots
+        .iter()
+        .filter(|oneshot| oneshot.at > now.naive_local())
+        .filter_map(|oneshot| (oneshot.at - now.naive_local()).to_std().ok());
+    // 下一个整点（跨午夜时由 MAX_IDLE_SLEEP 兜底）
+    let chime_time = cfg
+        .hourly_chime
+        .as_ref()
+        .and_then(|_| NaiveTime::from_hms_opt(local.hour() + 1, 0, 0));
+
+    period_times
+        .chain(chime_time)
+        .filter(|time| *time > local)
+        .filter_map(|time| (time - local).to_std().ok())
+        .chain(oneshot_delays)
+        .min()
+        .map(|until| until + WAKE_MARGIN)
+        .unwrap_or(MAX_IDLE_SLEEP)
+        .min(MAX_IDLE_SLEEP)
+}
+
+/// 开启整点报时且刚过整点（容差内）时返回报时的触发项，每小时只返回一次；
+/// 该整点恰有启用节点时以节点为准，不再报时，避免连响两次。
+/// 整点按本地时间；`schedule_now` 为时间表时区下的同一时刻，用于比对节点
+fn hourly_chime(
+    cfg: &AppConfig,
+    state: &mut FiredState,
+    now: &DateTime<Local>,
+    schedule_now: NaiveTime,
+    tolerance: TimeDelta,
+) -> Option<Trigger> {
+    let sound = cfg.hourly_chime.as_ref()?;
+    let time = now.time().with_nanosecond(0)?;
+    let hour = NaiveTime::from_hms_opt(time.hour(), 0, 0)?;
+    if time - hour > tolerance || !state.mark_chimed(now.date_naive(), time.hour()) {
+        return None;
+    }
+
+    let schedule_hour = schedule_now.with_nanosecond(0)? - (time - hour);
+    let has_period = cfg.effective_schedule().is_some_and(|schedule| {
+        schedule.periods.iter().any(|period| {
+            period.enabled && period.kind.fires() && period.naive_time() == Some(schedule_hour)
+        })
+    });
+    if has_period {
+        log::info!("整点 {} 有节点，跳过整点报时", hour.format("%H:%M"));
+        return None;
+    }
+
+    Some(Trigger {
+        source: "整点报时",
+        schedule_id: None,
+        schedule_name: None,
+        time: hour,
+        kind: PeriodKind::Start,
+        name: format!("{} 整点", hour.format("%H:%M")),
+        slots: SoundSlots {
+            start: sound.clone(),
+            ..SoundSlots::default()
+        },
+        note: None,
+        silent: false,
+        notify: false,
+    })
+}
+
+/// 当前会话是否处于锁屏状态。
+///
+/// 锁屏时输入桌面切换到 Winlogon 安全桌面，普通进程无法打开/切换到它。
+#[cfg(target_os = "windows")]
+fn session_locked() -> bool {
+    use windows_sys::Win32::System::StationsAndDesktops::{
+        CloseDesktop, DESKTOP_SWITCHDESKTOP, OpenInputDesktop, SwitchDesktop,
+    };
+
+    unsafe {
+        let desktop = OpenInputDesktop(0, 0, DESKTOP_SWITCHDESKTOP);
+        if desktop.is_null() {
+            return true;
+        }
+        let switched = SwitchDesktop(desktop) != 0;
+        CloseDesktop(desktop);
+        !switched
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn session_locked() -> bool {
+    false
+}
+
+/// 触发时需要的全局设置（从配置中拷贝，避免持锁播放）
+#[derive(Clone)]
+struct TriggerSettings {
+    suppress_during_calls: bool,
+    meeting_processes: Vec<String>,
+    sound_cooldown: Duration,
+    duck_others: bool,
+    startup_grace: Duration,
+    visual_alert: bool,
+    /// 仅视觉提醒：不播放铃声
+    silent: bool,
+}
+
+impl TriggerSettings {
+    fn from_config(cfg: &AppConfig) -> Self {
+        Self {
+            suppress_during_calls: cfg.suppress_during_calls,
+            meeting_processes: cfg.meeting_processes.clone(),
+            sound_cooldown: Duration::from_millis(cfg.sound_cooldown_ms),
+            duck_others: cfg.duck_others,
+            startup_grace: Duration::from_secs(u64::from(cfg.startup_grace_secs)),
+            visual_alert: cfg.visual_alert.enabled,
+            silent: cfg.visual_alert.enabled && cfg.visual_alert.silent,
+        }
+    }
+}
+
+/// 同一轮检测中相继触发的铃声至少按该间隔排队，保证排入同一路播放
+const COINCIDENT_WINDOW: Duration = Duration::from_secs(1);
+
+/// 一轮检测中命中的一个提醒（时间表节点或临时提醒）
+struct Trigger {
+    /// 日志中的触发来源
+    source: &'static str,
+    /// 所属时间表；临时提醒为 `None`
+    schedule_id: Option<u64>,
+    schedule_name: Option<String>,
+    time: NaiveTime,
+    kind: PeriodKind,
+    name: String,
+    slots: SoundSlots,
+    /// 节点备注，显示在通知正文中
+    note: Option<String>,
+    /// 节点设为静音：只发通知，不响铃
+    silent: bool,
+    /// 是否发送桌面通知（整点报时只响铃）
+    notify: bool,
+}
+
+impl Trigger {
+    fn feed_event(&self, fired_at: &DateTime<Local>) -> FeedEvent {
+        FeedEvent {
+            event: "fired",
+            source: if self.schedule_id.is_some() {
+                "period"
+            } else {
+                "oneshot"
+            },
+            schedule_id: self.schedule_id,
+            schedule: self.schedule_name.clone(),
+            name: self.name.clone(),
+            kind: self.kind,
+            time: self.time.format("%H:%M:%S").to_string(),
+            fired_at: fired_at.to_rfc3339(),
+            note: self.note.clone(),
+        }
+    }
+}
+
+/// 收集时间表中在 `now` 命中（`tolerance` 内）且当天未触发过的节点，并记为已触发；
+/// 计时器模式下命中的节点同时记入 `timer_fired`
+fn collect_schedule_triggers(
+    schedule: &ScheduleProfile,
+    now: NaiveTime,
+    tolerance: TimeDelta,
+    state: &mut FiredState,
+    triggers: &mut Vec<Trigger>,
+    timer_fired: &mut Vec<(u64, Period)>,
+) {
+    for period in &schedule.periods {
+        if !period.kind.fires() || !period.matches_within(&now, tolerance) {
+            continue;
+        }
+        if let Some(time) = period.naive_time()
+            && state.mark_fired(time, period)
+        {
+            if schedule.mode == ScheduleMode::Timer {
+                timer_fired.push((schedule.id, period.clone()));
+            }
+            triggers.push(Trigger {
+                source: "命中节点",
+                schedule_id: Some(schedule.id),
+                schedule_name: Some(schedule.name.clone()),
+                time,
+                kind: period.kind,
+                name: period.name.clone(),
+                slots: schedule.sound_for(period),
+                note: period.note.clone(),
+                silent: period.silent,
+                notify: true,
+            });
+        }
+    }
+}
+
+/// 统一分发一轮检测中命中的提醒：按 (时间表 id, 时间) 排序后依次触发，
+/// 第一个之后的铃声排入同一路播放（相同音效合并），不会同时打开多路输出流
+fn dispatch(
+    triggers: Vec<Trigger>,
+    settings: &TriggerSettings,
+    warned_once: &Mutex<HashSet<String>>,
+    status_events: &Mutex<Vec<(Severity, String)>>,
+) {
+    dispatch_with(triggers, settings, |trigger, settings| {
+        fire(trigger, settings, warned_once, status_events)
+    });
+}
+
+/// `dispatch` 的排序与排队规则，实际触发交给 `fire`（测试中替换为记录调用）
+fn dispatch_with(
+    mut triggers: Vec<Trigger>,
+    settings: &TriggerSettings,
+    mut fire: impl FnMut(&Trigger, &TriggerSettings),
+) {
+    triggers.sort_by_key(|trigger| (trigger.schedule_id, trigger.time));
+
+    let coincident = TriggerSettings {
+        sound_cooldown: settings.sound_cooldown.max(COINCIDENT_WINDOW),
+        ..settings.clone()
+    };
+    for (index, trigger) in triggers.iter().enumerate() {
+        fire(trigger, if index == 0 { settings } else { &coincident });
+    }
+}
+
+/// 播放音效并发送通知，日志记录触发来源与实际音效；本地音效回退的警告每种只上报一次
+fn fire(
+    trigger: &Trigger,
+    settings: &TriggerSettings,
+    warned_once: &Mutex<HashSet<String>>,
+    status_events: &Mutex<Vec<(Severity, String)>>,
+) {
+    let Trigger {
+        source,
+        kind,
+        name,
+        slots,
+        note,
+        silent,
+        ..
+    } = trigger;
+    let meeting = (settings.suppress_during_calls && !settings.silent && !silent)
+        .then(|| detect_meeting(&settings.meeting_processes))
+        .flatten();
+    if *silent {
+        log::info!(
+            "{}: {} - {}，节点已设为静音，仅发送通知",
+            source,
+            name,
+            kind.label()
+        );
+    } else if settings.silent {
+        log::info!(
+            "{}: {} - {}，仅视觉提醒，跳过铃声",
+            source,
+            name,
+            kind.label()
+        );
+    } else if let Some(process) = meeting {
+        log::info!(
+            "{}: {} - {}，检测到会议进行中（{}），跳过铃声",
+            source,
+            name,
+            kind.label(),
+            process
+        );
+    } else {
+        let outcome =
+            play_sound_for_period(*kind, slots, settings.sound_cooldown, settings.duck_others);
+        log::info!(
+            "{}: {} - {}，音效: {}",
+            source,
+            name,
+            kind.label(),
+            outcome.describe()
+        );
+        if let Some(warning) = outcome.warning
+            && warned_once.lock().unwrap().insert(warning.clone())
+        {
+            status_events
+                .lock()
+                .unwrap()
+                .push((Severity::Warning, warning));
+        }
+    }
+
+    if !trigger.notify {
+        return;
+    }
+    // 节点备注附在通知正文的第二行
+    let body = match note {
+        Some(note) => format!("{name}\n{note}"),
+        None => name.clone(),
+    };
+    send_notification(&format!("🔔 {}", kind.label()), &body);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedule::SoundSource;
+
+    fn at(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("{date} {time}"), "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    /// 模拟时钟：墙钟与单调时钟同步推进，也可单独调整墙钟模拟手动改时间
+    struct FakeClock {
+        now: Mutex<DateTime<Local>>,
+        mono: Mutex<Instant>,
+    }
+
+    impl FakeClock {
+        fn new(date: &str, time: &str) -> Arc<Self> {
+            Arc::new(Self {
+                now: Mutex::new(local(date, time)),
+                mono: Mutex::new(Instant::now()),
+            })
+        }
+
+        fn advance(&self, secs: u64) {
+            *self.now.lock().unwrap() += TimeDelta::seconds(secs as i64);
+            *self.mono.lock().unwrap() += Duration::from_secs(secs);
+        }
+
+        /// 只改墙钟（单调时钟前进 1 秒），模拟系统时间被手动调整
+        fn set_wall(&self, date: &str, time: &str) {
+            *self.now.lock().unwrap() = local(date, time);
+            *self.mono.lock().unwrap() += Duration::from_secs(1);
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> DateTime<Local> {
+            *self.now.lock().unwrap()
+        }
+
+        fn instant(&self) -> Instant {
+            *self.mono.lock().unwrap()
+        }
+    }
+
+    fn local(date: &str, time: &str) -> DateTime<Local> {
+        at(date, time).and_local_timezone(Local).single().unwrap()
+    }
+
+    /// 只含给定节点的配置；关闭启动宽限期，避免测试中的节点被跳过
+    fn config_with(periods: &[(&str, PeriodKind, &str)]) -> AppConfig {
+        let mut config = AppConfig::default_config();
+        let schedule = &mut config.schedules[0];
+        schedule.periods = periods
+            .iter()
+            .map(|(time, kind, name)| Period::new(time, *kind, name))
+            .collect();
+        config.startup_grace_secs = 0;
+        config
+    }
+
+    /// 用模拟时钟检测一轮，返回命中的提醒名称
+    fn tick(detector: &mut Detector, clock: &FakeClock) -> Vec<String> {
+        detector
+            .tick(clock.now(), clock.instant())
+            .map(|(triggers, _)| triggers.into_iter().map(|trigger| trigger.name).collect())
+            .unwrap_or_default()
+    }
+
+    fn oneshot(at: NaiveDateTime) -> OneShot {
+        OneShot {
+            id: 1,
+            at,
+            name: "喝水".to_string(),
+            kind: PeriodKind::Start,
+        }
+    }
+
+    #[test]
+    fn oneshot_due_only_on_its_own_date() {
+        let reminder = oneshot(at("2026-03-02", "00:05:00"));
+        // 前一天同一时刻不命中
+        assert!(!reminder.is_due(&at("2026-03-01", "00:05:00")));
+        assert!(reminder.is_due(&at("2026-03-02", "00:05:00")));
+        assert!(reminder.is_due(&at("2026-03-02", "00:05:59")));
+        assert!(!reminder.is_due(&at("2026-03-02", "00:06:00")));
+    }
+
+    #[test]
+    fn school_day_rolls_at_day_start_hour() {
+        let day = |date: &str| NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+        // 默认 0 点：午夜即换日
+        assert_eq!(
+            school_day(at("2026-01-12", "23:59:59"), 0),
+            day("2026-01-12")
+        );
+        assert_eq!(
+            school_day(at("2026-01-13", "00:00:00"), 0),
+            day("2026-01-13")
+        );
+        // 4 点换日：凌晨仍属于前一天，跨月也一样
+        assert_eq!(
+            school_day(at("2026-01-13", "00:30:00"), 4),
+            day("2026-01-12")
+        );
+        assert_eq!(
+            school_day(at("2026-01-13", "03:59:59"), 4),
+            day("2026-01-12")
+        );
+        assert_eq!(
+            school_day(at("2026-01-13", "04:00:00"), 4),
+            day("2026-01-13")
+        );
+        assert_eq!(
+            school_day(at("2026-03-01", "01:00:00"), 4),
+            day("2026-02-28")
+        );
+        // 越界的小时按 23 处理
+        assert_eq!(
+            school_day(at("2026-01-13", "22:59:59"), 30),
+            day("2026-01-12")
+        );
+        assert_eq!(
+            school_day(at("2026-01-13", "23:00:00"), 30),
+            day("2026-01-13")
+        );
+    }
+
+    #[test]
+    fn same_period_fires_once_within_tolerance() {
+        let clock = FakeClock::new("2026-01-12", "07:59:59");
+        let engine = Engine::new_with_clock(
+            config_with(&[("08:00:00", PeriodKind::Start, "第1节开始")]),
+            clock.clone(),
+        );
+        let mut detector = engine.detector();
+
+        assert!(tick(&mut detector, &clock).is_empty());
+        clock.advance(1);
+        assert_eq!(tick(&mut detector, &clock), ["第1节开始"]);
+        // 容差（默认 5 秒）内反复醒来也不再触发
+        for _ in 0..5 {
+            clock.advance(1);
+            assert!(tick(&mut detector, &clock).is_empty());
+        }
+    }
+
+    #[test]
+    fn late_wake_within_tolerance_still_fires() {
+        let clock = FakeClock::new("2026-01-12", "08:00:03");
+        let engine = Engine::new_with_clock(
+            config_with(&[("08:00:00", PeriodKind::Start, "第1节开始")]),
+            clock.clone(),
+        );
+        let mut detector = engine.detector();
+        assert_eq!(tick(&mut detector, &clock), ["第1节开始"]);
+    }
+
+    #[test]
+    fn fired_record_resets_at_day_start_hour_not_midnight() {
+        let clock = FakeClock::new("2026-01-12", "23:00:00");
+        let mut config = config_with(&[("23:00:00", PeriodKind::Start, "晚自习开始")]);
+        config.day_start_hour = 4;
+        let engine = Engine::new_with_clock(config, clock.clone());
+        let mut detector = engine.detector();
+
+        assert_eq!(tick(&mut detector, &clock), ["晚自习开始"]);
+        assert_eq!(engine.fired_state.lock().unwrap().fired.len(), 1);
+
+        // 过了午夜仍属于同一天，记录保留
+        clock.advance(3600 + 5);
+        tick(&mut detector, &clock);
+        assert_eq!(engine.fired_state.lock().unwrap().fired.len(), 1);
+
+        // 到 04:00 才开始新的一天
+        clock.advance(4 * 3600 - 5);
+        tick(&mut detector, &clock);
+        assert!(engine.fired_state.lock().unwrap().fired.is_empty());
+
+        clock.advance(19 * 3600);
+        assert_eq!(tick(&mut detector, &clock), ["晚自习开始"]);
+    }
+
+    #[test]
+    fn manual_clock_change_reports_status_and_refires() {
+        let clock = FakeClock::new("2026-01-12", "08:00:00");
+        let engine = Engine::new_with_clock(
+            config_with(&[("08:00:00", PeriodKind::Start, "第1节开始")]),
+            clock.clone(),
+        );
+        let mut detector = engine.detector();
+        assert_eq!(tick(&mut detector, &clock), ["第1节开始"]);
+        clock.advance(1);
+        tick(&mut detector, &clock);
+        assert!(engine.take_status_events().is_empty());
+
+        // 手动把时间调回 07:50：界面收到提示，再次到点时重新响铃
+        clock.set_wall("2026-01-12", "07:50:00");
+        assert!(tick(&mut detector, &clock).is_empty());
+        let events = engine.take_status_events();
+        assert_eq!(
+            events,
+            [(Severity::Warning, CLOCK_JUMP_WARNING.to_string())]
+        );
+        assert!(engine.fired_state.lock().unwrap().fired.is_empty());
+
+        clock.advance(600);
+        assert_eq!(tick(&mut detector, &clock), ["第1节开始"]);
+        assert!(engine.take_status_events().is_empty());
+    }
+
+    #[test]
+    fn supervise_restarts_after_panic_until_body_returns() {
+        let status_events = Mutex::new(Vec::new());
+        let mut runs = Vec::new();
+        supervise(&status_events, |restarted| {
+            runs.push(restarted);
+            if runs.len() < 3 {
+                panic!("模拟第 {} 次出错", runs.len());
+            }
+        });
+        assert_eq!(runs, [false, true, true]);
+        let events = status_events.into_inner().unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(
+            events
+                .iter()
+                .all(|(severity, _)| *severity == Severity::Error)
+        );
+    }
+
+    /// 第一次读取墙钟时 panic，之后返回真实时间
+    struct PanicOnceClock {
+        panicked: Mutex<bool>,
+    }
+
+    impl Clock for PanicOnceClock {
+        fn now(&self) -> DateTime<Local> {
+            if !std::mem::replace(&mut *self.panicked.lock().unwrap(), true) {
+                panic!("模拟检测循环出错");
+            }
+            Local::now()
+        }
+    }
+
+    /// 等待 `done` 成立，最多 `timeout`
+    fn wait_until(timeout: Duration, mut done: impl FnMut() -> bool) -> bool {
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            if done() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        done()
+    }
+
+    #[test]
+    fn engine_thread_recovers_from_panic() {
+        // 没有节点，不会响铃或发通知
+        let clock = Arc::new(PanicOnceClock {
+            panicked: Mutex::new(false),
+        });
+        let engine = Engine::new_with_clock(config_with(&[]), clock);
+        let started = *engine.heartbeat.lock().unwrap();
+        engine.start();
+
+        let mut events = Vec::new();
+        assert!(wait_until(RESTART_DELAY * 5, || {
+            events.extend(engine.take_status_events());
+            !events.is_empty()
+        }));
+        assert_eq!(
+            events,
+            [(Severity::Error, "检测线程出错，已自动重新启动".to_string())]
+        );
+
+        // 重启后的循环照常醒来并更新心跳
+        engine.wake();
+        assert!(wait_until(RESTART_DELAY * 5, || {
+            *engine.heartbeat.lock().unwrap() > started
+        }));
+        assert!(!engine.is_stalled());
+    }
+
+    fn schedule_with(periods: &[(&str, PeriodKind, &str)]) -> ScheduleProfile {
+        let mut schedule = ScheduleProfile::empty(1, "测试");
+        schedule.periods = periods
+            .iter()
+            .map(|(time, kind, name)| Period::new(time, *kind, name))
+            .collect();
+        schedule
+    }
+
+    fn hit_times(hits: &[(NaiveTime, String)]) -> Vec<String> {
+        hits.iter()
+            .map(|(time, _)| time.format("%H:%M:%S").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn simulation_hits_each_ringing_period_once() {
+        let mut schedule = schedule_with(&[
+            ("07:55:00", PeriodKind::Prepare, "预备"),
+            ("08:00:00", PeriodKind::Start, "第1节开始"),
+            ("08:45:00", PeriodKind::End, "第1节结束"),
+            ("12:00:00", PeriodKind::Marker, "午休"),
+            ("13:30:00", PeriodKind::Start, "第2节开始"),
+            ("14:15:00", PeriodKind::End, "第2节结束"),
+        ]);
+        let hits = Engine::simulate_day(&schedule, Duration::from_secs(1), 0);
+        // 标记节点不响铃
+        assert_eq!(
+            hit_times(&hits),
+            ["07:55:00", "08:00:00", "08:45:00", "13:30:00", "14:15:00"]
+        );
+
+        // 步长较大时最多晚一个步长，但不会漏掉或重复
+        let hits = Engine::simulate_day(&schedule, Duration::from_secs(60 * 7), 0);
+        assert_eq!(hits.len(), 5);
+
+        schedule.periods[1].enabled = false;
+        let hits = Engine::simulate_day(&schedule, Duration::from_secs(1), 0);
+        assert_eq!(
+            hit_times(&hits),
+            ["07:55:00", "08:45:00", "13:30:00", "14:15:00"]
+        );
+    }
+
+    #[test]
+    fn simulation_uses_day_end_sound_for_last_end() {
+        let mut schedule = schedule_with(&[
+            ("08:00:00", PeriodKind::Start, "第1节开始"),
+            ("08:45:00", PeriodKind::End, "第1节结束"),
+            ("16:00:00", PeriodKind::Start, "第2节开始"),
+            ("16:45:00", PeriodKind::End, "放学"),
+        ]);
+        schedule.sound.day_end = Some(SoundSource::Local {
+            path: "/sounds/放学铃.mp3".to_string(),
+            gain_db: 0.0,
+        });
+        let hits = Engine::simulate_day(&schedule, Duration::from_secs(1), 0);
+        let day_end: Vec<(NaiveTime, String)> = hits
+            .into_iter()
+            .filter(|(_, description)| description.contains("放学铃.mp3"))
+            .collect();
+        assert_eq!(hit_times(&day_end), ["16:45:00"]);
+    }
+
+    #[test]
+    fn simulation_starts_at_day_start_hour_and_wraps() {
+        let schedule = schedule_with(&[
+            ("01:00:00", PeriodKind::End, "晚自习结束"),
+            ("08:00:00", PeriodKind::Start, "第1节开始"),
+            ("23:59:58", PeriodKind::Start, "晚自习开始"),
+        ]);
+        let hits = Engine::simulate_day(&schedule, Duration::from_secs(1), 0);
+        assert_eq!(hit_times(&hits), ["01:00:00", "08:00:00", "23:59:58"]);
+
+        // 4 点为一天的开始：午夜后的节点排在最后
+        let hits = Engine::simulate_day(&schedule, Duration::from_secs(1), 4);
+        assert_eq!(hit_times(&hits), ["08:00:00", "23:59:58", "01:00:00"]);
+
+        // 步长越过午夜时在 23:59:59 补查一次，不漏掉午夜前的节点
+        let hits = Engine::simulate_day(&schedule, Duration::from_secs(60), 4);
+        assert_eq!(hit_times(&hits), ["08:00:00", "23:59:59", "01:00:00"]);
+    }
+
+    #[test]
+    fn paused_engine_does_not_fire_or_mark() {
+        let clock = FakeClock::new("2026-01-12", "08:00:00");
+        let engine = Engine::new_with_clock(
+            config_with(&[("08:00:00", PeriodKind::Start, "第1节开始")]),
+            clock.clone(),
+        );
+        let mut detector = engine.detector();
+        engine.toggle_enabled();
+        assert!(detector.tick(clock.now(), clock.instant()).is_none());
+        engine.toggle_enabled();
+        clock.advance(1);
+        assert_eq!(tick(&mut detector, &clock), ["第1节开始"]);
+    }
+
+    #[test]
+    fn timer_periods_fire_and_are_removed_on_the_engine_thread() {
+        let clock = FakeClock::new("2026-01-12", "08:00:00");
+        let mut config = config_with(&[
+            ("08:00:00", PeriodKind::Start, "泡面"),
+            ("08:05:00", PeriodKind::End, "午休结束"),
+        ]);
+        config.schedules[0].mode = ScheduleMode::Timer;
+        let engine = Engine::new_with_clock(config, clock.clone());
+        let mut detector = engine.detector();
+
+        // 只驱动检测循环，不经过界面：命中即响，并从引擎自己的配置中移除
+        assert_eq!(tick(&mut detector, &clock), ["泡面"]);
+        let remaining: Vec<String> = engine.config.lock().unwrap().schedules[0]
+            .periods
+            .iter()
+            .map(|period| period.name.clone())
+            .collect();
+        assert_eq!(remaining, ["午休结束"]);
+
+        // 次日触发记录清空后也不会再响
+        clock.advance(24 * 3600);
+        assert!(tick(&mut detector, &clock).is_empty());
+    }
+
+    #[test]
+    fn set_enabled_follows_replaced_config() {
+        let clock = FakeClock::new("2026-01-12", "08:00:00");
+        let engine = Engine::new_with_clock(
+            config_with(&[("08:00:00", PeriodKind::Start, "第1节开始")]),
+            clock.clone(),
+        );
+        let mut detector = engine.detector();
+        engine.set_enabled(false);
+        assert!(!engine.is_enabled());
+        assert!(detector.tick(clock.now(), clock.instant()).is_none());
+        // 重复设置不会像切换那样翻转状态
+        engine.set_enabled(true);
+        engine.set_enabled(true);
+        assert!(engine.is_enabled());
+        clock.advance(1);
+        assert_eq!(tick(&mut detector, &clock), ["第1节开始"]);
+    }
+
+    fn trigger(schedule_id: Option<u64>, time: &str, name: &str) -> Trigger {
+        Trigger {
+            source: "测试",
+            schedule_id,
+            schedule_name: None,
+            time: NaiveTime::parse_from_str(time, "%H:%M:%S").unwrap(),
+            kind: PeriodKind::Start,
+            name: name.to_string(),
+            slots: SoundSlots::default(),
+            note: None,
+            silent: false,
+            notify: true,
+        }
+    }
+
+    #[test]
+    fn coincident_triggers_play_in_one_ordered_queue() {
+        let settings = TriggerSettings::from_config(&AppConfig::default_config());
+        let triggers = || {
+            vec![
+                trigger(Some(2), "08:00:00", "B 表"),
+                trigger(Some(1), "08:00:03", "A 表晚"),
+                trigger(None, "08:00:05", "临时提醒"),
+                trigger(Some(1), "08:00:00", "A 表早"),
+            ]
+        };
+
+        let mut fired = Vec::new();
+        dispatch_with(triggers(), &settings, |trigger, settings| {
+            fired.push((trigger.name.clone(), settings.sound_cooldown));
+        });
+        let names: Vec<&str> = fired.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["临时提醒", "A 表早", "A 表晚", "B 表"]);
+
+        // 第一个沿用原设置，其余至少间隔 `COINCIDENT_WINDOW` 排入同一路播放
+        assert_eq!(fired[0].1, settings.sound_cooldown);
+        assert!(
+            fired[1..]
+                .iter()
+                .all(|(_, cooldown)| *cooldown >= COINCIDENT_WINDOW)
+        );
+
+        // 输入顺序不影响结果
+        let mut again = Vec::new();
+        dispatch_with(
+            triggers().into_iter().rev().collect(),
+            &settings,
+            |trigger, _| {
+                again.push(trigger.name.clone());
+            },
+        );
+        assert_eq!(again, names);
+    }
+
+    #[test]
+    fn two_schedules_sharing_a_second_dispatch_in_order() {
+        let schedule = |id: u64, periods: &[(&str, &str)]| {
+            let mut schedule = ScheduleProfile::empty(id, &format!("表 {id}"));
+            schedule.periods = periods
+                .iter()
+                .map(|(time, name)| Period::new(time, PeriodKind::Start, name))
+                .collect();
+            schedule
+        };
+        let first = schedule(1, &[("08:00:00", "A 表"), ("07:59:58", "A 表早")]);
+        let second = schedule(2, &[("08:00:00", "B 表")]);
+
+        // 与检测循环相同的收集路径，收集顺序与时间表顺序相反
+        let now = NaiveTime::from_hms_opt(8, 0, 0).unwrap();
+        let tolerance = TimeDelta::seconds(5);
+        let mut state = FiredState::default();
+        let (mut triggers, mut timer_fired) = (Vec::new(), Vec::new());
+        for schedule in [&second, &first] {
+            collect_schedule_triggers(
+                schedule,
+                now,
+                tolerance,
+                &mut state,
+                &mut triggers,
+                &mut timer_fired,
+            );
+        }
+        assert_eq!(triggers.len(), 3);
+
+        let settings = TriggerSettings::from_config(&AppConfig::default_config());
+        let mut fired = Vec::new();
+        dispatch_with(triggers, &settings, |trigger, settings| {
+            fired.push((
+                trigger.schedule_id,
+                trigger.name.clone(),
+                settings.sound_cooldown,
+            ));
+        });
+        let order: Vec<(Option<u64>, &str)> = fired
+            .iter()
+            .map(|(id, name, _)| (*id, name.as_str()))
+            .collect();
+        assert_eq!(
+            order,
+            [(Some(1), "A 表早"), (Some(1), "A 表"), (Some(2), "B 表")]
+        );
+        // 同一秒的两张表排入同一路播放，而不是各开一路
+        assert!(
+            fired[1..]
+                .iter()
+                .all(|(_, _, cooldown)| *cooldown >= COINCIDENT_WINDOW)
+        );
+    }
+
+    /// 与检测循环相同的顺序：先比对时钟，再按本地日期滚动记录，最后标记节点
+    fn observe(
+        state: &mut FiredState,
+        mono: Instant,
+        utc: &str,
+        offset_hours: i64,
+    ) -> (Option<i64>, NaiveTime) {
+        let wall = at(&utc[..10], &utc[11..]).and_utc();
+        let jump = state.observe_clock(mono, wall);
+        let local = wall.naive_utc() + TimeDelta::hours(offset_hours);
+        state.roll_date(school_day(local, 0));
+        (jump, local.time())
+    }
+
+    #[test]
+    fn backward_clock_jump_resets_fired_state() {
+        let period = Period::new("08:00:00", PeriodKind::Start, "第1节开始");
+        let time = period.naive_time().unwrap();
+        let mut state = FiredState::default();
+        let base = Instant::now();
+
+        let (jump, _) = observe(&mut state, base, "2026-03-01 08:00:00", 0);
+        assert_eq!(jump, None);
+        assert!(state.mark_fired(time, &period));
+        let (jump, _) = observe(
+            &mut state,
+            base + Duration::from_secs(1),
+            "2026-03-01 08:00:01",
+            0,
+        );
+        assert_eq!(jump, None);
+        assert!(!state.mark_fired(time, &period), "同一节点不应重复触发");
+
+        // 系统时间被手动往回调了 10 分钟：记录清空，之后按新时间正常触发
+        let (jump, _) = observe(
+            &mut state,
+            base + Duration::from_secs(2),
+            "2026-03-01 07:50:02",
+            0,
+        );
+        assert_eq!(jump, Some(-600));
+        assert!(state.mark_fired(time, &period));
+    }
+
+    #[test]
+    fn small_scheduling_delay_is_not_a_clock_jump() {
+        let mut state = FiredState::default();
+        let base = Instant::now();
+        observe(&mut state, base, "2026-03-01 08:00:00", 0);
+        // 线程晚醒 5 秒，墙钟与单调时钟一致前进
+        let (jump, _) = observe(
+            &mut state,
+            base + Duration::from_secs(5),
+            "2026-03-01 08:00:05",
+            0,
+        );
+        assert_eq!(jump, None);
+    }
+
+    #[test]
+    fn dst_fall_back_does_not_refire_repeated_wall_time() {
+        let period = Period::new("01:30:00", PeriodKind::Start, "早读");
+        let mut state = FiredState::default();
+        let base = Instant::now();
+
+        // 夏令时（UTC+2）01:30 触发
+        let (_, local) = observe(&mut state, base, "2026-10-24 23:30:00", 2);
+        assert_eq!(local, period.naive_time().unwrap());
+        assert!(state.mark_fired(local, &period));
+
+        // 一小时后回拨到 UTC+1，墙钟再次出现 01:30：不是跳变，也不重响
+        let (jump, local) = observe(
+            &mut state,
+            base + Duration::from_secs(3600),
+            "2026-10-25 00:30:00",
+            1,
+        );
+        assert_eq!(jump, None);
+        assert_eq!(local, period.naive_time().unwrap());
+        assert!(!state.mark_fired(local, &period));
+    }
+
+    #[test]
+    fn missed_oneshot_expires_instead_of_firing_next_day() {
+        let reminder = oneshot(at("2026-03-01", "10:00:00"));
+        assert!(!reminder.is_expired(&at("2026-03-01", "10:00:30")));
+        assert!(reminder.is_expired(&at("2026-03-01", "10:01:00")));
+        assert!(!reminder.is_due(&at("2026-03-02", "10:00:00")));
+        assert!(reminder.is_expired(&at("2026-03-02", "10:00:00")));
+    }
 }
@@ -0,0 +1,262 @@
+use std::io::Write;
+use std::sync::{Arc, Mutex, mpsc};
+
+use serde::Serialize;
+
+use crate::schedule::PeriodKind;
+
+/// 触发事件：每行一个 JSON 对象推送给已连接的客户端（格式见 README）
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedEvent {
+    /// 固定为 `"fired"`，为以后扩展其他事件预留
+    pub event: &'static str,
+    /// `"period"`（时间表节点）或 `"oneshot"`（临时提醒）
+    pub source: &'static str,
+    pub schedule_id: Option<u64>,
+    pub schedule: Option<String>,
+    pub name: String,
+    pub kind: PeriodKind,
+    /// 节点时间 `HH:MM:SS`
+    pub time: String,
+    /// 实际触发时刻（RFC 3339，本地时区）
+    pub fired_at: String,
+    pub note: Option<String>,
+}
+
+type Clients = Arc<Mutex<Vec<Box<dyn Write + Send>>>>;
+
+/// 客户端连接地址：Unix 为 socket 文件路径，Windows 为命名管道名
+pub fn feed_address() -> String {
+    #[cfg(target_os = "windows")]
+    {
+        r"\\.\pipe\wc_notice".to_string()
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        dirs::runtime_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("wc_notice.sock")
+            .display()
+            .to_string()
+    }
+}
+
+/// 启动监听线程与广播线程，返回供引擎发送事件的 channel；监听失败返回 `None`。
+///
+/// 推送是只读的：不读取客户端发来的任何数据。写入失败（客户端断开，或长时间不读取
+/// 导致写入超时/缓冲区写满）时只移除该客户端，不影响其他客户端和后续事件。
+pub fn start() -> Option<mpsc::Sender<FeedEvent>> {
+    let clients: Clients = Arc::new(Mutex::new(Vec::new()));
+    if let Err(e) = spawn_listener(Arc::clone(&clients)) {
+        log::warn!("事件推送监听失败（{}）: {e}", feed_address());
+        return None;
+    }
+
+    let (tx, rx) = mpsc::channel::<FeedEvent>();
+    let spawned = std::thread::Builder::new()
+        .name("event-feed".to_string())
+        .spawn(move || {
+            for event in rx {
+                let line = match serde_json::to_string(&event) {
+                    Ok(json) => json + "\n",
+                    Err(e) => {
+                        log::warn!("事件序列化失败: {e}");
+                        continue;
+                    }
+                };
+                clients.lock().unwrap().retain_mut(|client| {
+                    let sent = client
+                        .write_all(line.as_bytes())
+                        .and_then(|()| client.flush());
+                    if let Err(e) = &sent {
+                        log::info!("事件推送客户端已断开或未读取，已移除: {e}");
+                    }
+                    sent.is_ok()
+                });
+            }
+        });
+    if let Err(e) = spawned {
+        log::warn!("无法创建事件推送线程: {e}");
+        return None;
+    }
+
+    log::info!("事件推送已启用: {}", feed_address());
+    Some(tx)
+}
+
+#[cfg(unix)]
+fn spawn_listener(clients: Clients) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::UnixListener;
+    use std::time::Duration;
+
+    let path = feed_address();
+    // 上次异常退出可能留下 socket 文件，不删除会导致 bind 失败
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+
+    std::thread::Builder::new()
+        .name("event-feed-listener".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        // 客户端不读取时避免阻塞广播线程
+                        let _ = stream.set_write_timeout(Some(Duration::from_secs(1)));
+                        log::info!("事件推送客户端已连接");
+                        clients.lock().unwrap().push(Box::new(stream));
+                    }
+                    Err(e) => log::warn!("事件推送接受连接失败: {e}"),
+                }
+            }
+        })?;
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn spawn_listener(clients: Clients) -> std::io::Result<()> {
+    let name: Vec<u16> = feed_address()
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    // 先创建第一个管道实例，名称被占用等错误能在启动时报告
+    let mut pending = pipe::create(&name)?;
+
+    std::thread::Builder::new()
+        .name("event-feed-listener".to_string())
+        .spawn(move || {
+            loop {
+                match pending.connect().and_then(|()| pending.set_nonblocking()) {
+                    Ok(()) => {
+                        log::info!("事件推送客户端已连接");
+                        clients.lock().unwrap().push(Box::new(pending));
+                    }
+                    Err(e) => log::warn!("事件推送接受连接失败: {e}"),
+                }
+                pending = match pipe::create(&name) {
+                    Ok(next) => next,
+                    Err(e) => {
+                        log::warn!("事件推送无法创建管道实例，停止监听: {e}");
+                        return;
+                    }
+                };
+            }
+        })?;
+    Ok(())
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn spawn_listener(_clients: Clients) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "当前平台不支持",
+    ))
+}
+
+#[cfg(target_os = "windows")]
+mod pipe {
+    use std::io;
+
+    use windows_sys::Win32::Foundation::{
+        CloseHandle, ERROR_PIPE_CONNECTED, GetLastError, HANDLE, INVALID_HANDLE_VALUE,
+    };
+    use windows_sys::Win32::Storage::FileSystem::{PIPE_ACCESS_OUTBOUND, WriteFile};
+    use windows_sys::Win32::System::Pipes::{
+        ConnectNamedPipe, CreateNamedPipeW, DisconnectNamedPipe, PIPE_NOWAIT, PIPE_READMODE_BYTE,
+        PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+        SetNamedPipeHandleState,
+    };
+
+    /// 一个命名管道实例（只写），释放时断开并关闭句柄
+    pub struct PipeClient(HANDLE);
+
+    // 句柄只在某一个线程（监听线程，连接后移交广播线程）中使用
+    unsafe impl Send for PipeClient {}
+
+    pub fn create(name: &[u16]) -> io::Result<PipeClient> {
+        let handle = unsafe {
+            CreateNamedPipeW(
+                name.as_ptr(),
+                PIPE_ACCESS_OUTBOUND,
+                PIPE_TYPE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+                PIPE_UNLIMITED_INSTANCES,
+                4096,
+                0,
+                0,
+                std::ptr::null(),
+            )
+        };
+        if handle == INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(PipeClient(handle))
+    }
+
+    impl PipeClient {
+        /// 阻塞等待客户端连接
+        pub fn connect(&self) -> io::Result<()> {
+            let ok = unsafe { ConnectNamedPipe(self.0, std::ptr::null_mut()) } != 0;
+            // 客户端在 ConnectNamedPipe 之前已连上时返回 ERROR_PIPE_CONNECTED，同样视为成功
+            if ok || unsafe { GetLastError() } == ERROR_PIPE_CONNECTED {
+                Ok(())
+            } else {
+                Err(io::Error::last_os_error())
+            }
+        }
+
+        /// 连接后切换为非阻塞写入：客户端不读取、缓冲区写满时写入立即失败，
+        /// 该客户端随即被移除，不会阻塞广播线程（对应 Unix 的写超时）
+        pub fn set_nonblocking(&self) -> io::Result<()> {
+            let mode = PIPE_READMODE_BYTE | PIPE_NOWAIT;
+            let ok = unsafe {
+                SetNamedPipeHandleState(self.0, &mode, std::ptr::null(), std::ptr::null())
+            };
+            if ok == 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl io::Write for PipeClient {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let mut written = 0u32;
+            let ok = unsafe {
+                WriteFile(
+                    self.0,
+                    buf.as_ptr(),
+                    buf.len() as u32,
+                    &mut written,
+                    std::ptr::null_mut(),
+                )
+            };
+            if ok == 0 {
+                // 客户端已断开时为 ERROR_NO_DATA
+                Err(io::Error::last_os_error())
+            } else if written == 0 && !buf.is_empty() {
+                // 非阻塞模式下缓冲区已满：客户端没有在读取
+                Err(io::Error::new(
+                    io::ErrorKind::WouldBlock,
+                    "客户端未读取，管道缓冲区已满",
+                ))
+            } else {
+                Ok(written as usize)
+            }
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Drop for PipeClient {
+        fn drop(&mut self) {
+            unsafe {
+                DisconnectNamedPipe(self.0);
+                CloseHandle(self.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! WC Notice 的作息核心，可在其他工具中复用。
+//!
+//! 只包含时间表、节点与配置的数据结构和纯计算逻辑（`schedule` 模块）以及日出日落计算（`solar` 模块），
+//! 不依赖界面、音频、托盘或后台检测引擎；这些留在可执行程序中。
+//!
+//! ```
+//! use chrono::NaiveTime;
+//! use wc_notice::schedule::{ScheduleProfile, normalize_time_str};
+//!
+//! let schedule = ScheduleProfile::default_preset(1);
+//! let now = NaiveTime::from_hms_opt(8, 30, 0).unwrap();
+//! assert_eq!(schedule.current_status(&now, "未开始"), "第1节开始");
+//! assert_eq!(schedule.next_period(&now).unwrap().name, "第1节结束");
+//! assert_eq!(normalize_time_str("8:05").as_deref(), Some("08:05:00"));
+//! ```
+
+pub mod schedule;
+pub mod solar;
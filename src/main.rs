@@ -3,12 +3,17 @@
 mod app;
 mod config;
 mod engine;
+mod feed;
+mod monitor;
 mod notifier;
-mod schedule;
+mod selftest;
 mod tray;
 
 use std::sync::Arc;
 
+// 作息核心在库中（src/lib.rs），以 `crate::schedule` 供各模块使用
+use wc_notice::schedule;
+
 use app::WcNoticeApp;
 use engine::Engine;
 
@@ -17,54 +22,30 @@ fn main() -> eframe::Result {
 
     log::info!("WC Notice 启动中...");
 
+    // 命令行 --config <path> 优先于环境变量与默认目录
+    if let Some(path) = config_path_from_args(std::env::args().skip(1)) {
+        config::set_config_path(path);
+    }
+
     // 加载应用配置
+    log::info!("配置文件路径: {:?}", config::config_path());
     let config = config::load_config();
     log::info!("已加载配置，时间表数量: {}", config.schedules.len());
+    if !config.enabled {
+        log::info!("提醒处于暂停状态（沿用上次退出时的设置）");
+    }
 
     // 创建引擎并启动后台检测线程
     let engine = Arc::new(Engine::new(config.clone()));
+    if config.event_feed
+        && let Some(feed) = feed::start()
+    {
+        engine.set_event_feed(feed);
+    }
     engine.start();
 
-    // 在专用线程中创建托盘图标并运行 Win32 消息泵。
-    // tray-icon 要求：托盘图标必须在与 Win32 消息泵相同的线程上创建。
-    // eframe/winit 只泵送自己管理的窗口消息，不会泵送 tray-icon 隐藏 HWND 的消息，
-    // 因此必须在独立线程中运行 GetMessage/DispatchMessage 循环。
-    //
-    // 方案：new_split() 返回 (TrayHandle, TrayThreadState)：
-    //   - TrayHandle 只含 Arc 字段（Send），传回主线程使用
-    //   - TrayThreadState 移入专用线程，完成托盘初始化并运行消息泵
-    let mut tray = {
-        // 使用 SyncSender（容量=1），托盘线程在初始化完成后立即发送结果，
-        // 然后继续运行消息泵。主线程收到信号后即可继续启动 eframe，不再阻塞。
-        let (init_tx, init_rx) = std::sync::mpsc::sync_channel::<bool>(1);
-
-        let (handle, thread_state) =
-            tray::TrayHandle::new_split(include_bytes!("../assets/icon.ico"), init_tx);
-
-        std::thread::Builder::new()
-            .name("tray-msg-pump".to_string())
-            .spawn(move || {
-                // run() 内部：初始化托盘 → 立即通过 init_tx 通知主线程 → 运行消息泵
-                thread_state.run();
-            })
-            .expect("无法创建托盘消息泵线程");
-
-        // 等待托盘线程完成初始化（init_tx 在初始化后立即发送，不等消息泵退出）
-        match init_rx.recv() {
-            Ok(true) => {
-                log::info!("托盘功能已启用");
-                Some(handle)
-            }
-            Ok(false) => {
-                log::warn!("托盘初始化失败，将不启用托盘功能");
-                None
-            }
-            Err(_) => {
-                log::warn!("托盘线程异常退出，将不启用托盘功能");
-                None
-            }
-        }
-    };
+    // 在专用线程中创建托盘图标并运行 Win32 消息泵（详见 tray::spawn）
+    let mut tray = tray::spawn(config.tray_icon_path.clone());
 
     // 启动 egui GUI
     let native_options = eframe::NativeOptions {
@@ -91,12 +72,40 @@ fn main() -> eframe::Result {
     )
 }
 
+/// 解析 `--config <path>` / `--config=<path>`，相对路径按程序所在目录解析
+fn config_path_from_args(args: impl Iterator<Item = String>) -> Option<std::path::PathBuf> {
+    let mut args = args;
+    let raw = loop {
+        let arg = args.next()?;
+        if arg == "--config" {
+            break args.next()?;
+        }
+        if let Some(value) = arg.strip_prefix("--config=") {
+            break value.to_string();
+        }
+    };
+
+    let path = std::path::PathBuf::from(raw);
+    if path.is_absolute() {
+        return Some(path);
+    }
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.to_path_buf()));
+    Some(match exe_dir {
+        Some(dir) => dir.join(path),
+        None => path,
+    })
+}
+
 /// 从系统字体路径加载中文字体并注册到 egui
 ///
 /// 优先级：
 ///   Windows  → 微软雅黑 (msyh.ttc)
 ///   macOS    → 苹方 (PingFang.ttc) → 华文黑体 (STHeiti Medium.ttc)
 ///   Linux    → Noto Sans CJK SC → WenQuanYi Micro Hei
+///
+/// 都找不到时使用 `bundled-cjk-font` 特性内嵌的字体子集作为最后手段。
 fn setup_chinese_font(ctx: &egui::Context) {
     #[cfg(target_os = "windows")]
     let candidates: &[&str] = &[
@@ -131,7 +140,7 @@ fn setup_chinese_font(ctx: &egui::Context) {
             Err(_) => None,
         });
 
-    let Some(font_data) = font_data else {
+    let Some(font_data) = font_data.or_else(bundled_cjk_font) else {
         log::warn!("未找到系统中文字体，界面中文可能显示为方块");
         return;
     };
@@ -157,6 +166,18 @@ fn setup_chinese_font(ctx: &egui::Context) {
     log::info!("中文字体注册完成");
 }
 
+/// 内嵌的中文字体子集（常用汉字），仅在系统字体全部缺失时使用
+#[cfg(feature = "bundled-cjk-font")]
+fn bundled_cjk_font() -> Option<Vec<u8>> {
+    log::info!("未找到系统中文字体，使用内嵌字体");
+    Some(include_bytes!("../assets/fonts/cjk-subset.otf").to_vec())
+}
+
+#[cfg(not(feature = "bundled-cjk-font"))]
+fn bundled_cjk_font() -> Option<Vec<u8>> {
+    None
+}
+
 /// 加载应用图标（内嵌 ICO）
 fn load_app_icon() -> egui::IconData {
     let icon_bytes = include_bytes!("../assets/icon.ico");
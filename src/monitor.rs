@@ -0,0 +1,125 @@
+/// 显示器在虚拟桌面中的位置与大小（物理像素）
+#[derive(Debug, Clone, Copy)]
+pub struct MonitorRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    /// 工作区（去掉任务栏等停靠区域）的左上右下边界
+    pub work_left: i32,
+    pub work_top: i32,
+    pub work_right: i32,
+    pub work_bottom: i32,
+    pub primary: bool,
+}
+
+impl MonitorRect {
+    pub fn label(&self, index: usize) -> String {
+        let primary = if self.primary { "（主）" } else { "" };
+        format!(
+            "显示器 {}{primary} {}×{}",
+            index + 1,
+            self.width,
+            self.height
+        )
+    }
+
+    /// 把大小为 `width`×`height` 的窗口左上角 `(x, y)` 收进工作区；窗口比工作区大时靠左上对齐
+    pub fn clamp_into_work_area(&self, x: i32, y: i32, width: i32, height: i32) -> (i32, i32) {
+        let x = x.clamp(
+            self.work_left,
+            (self.work_right - width).max(self.work_left),
+        );
+        let y = y.clamp(
+            self.work_top,
+            (self.work_bottom - height).max(self.work_top),
+        );
+        (x, y)
+    }
+
+    /// 窗口在工作区居中时的左上角
+    pub fn centered(&self, width: i32, height: i32) -> (i32, i32) {
+        let x = self.work_left + (self.work_right - self.work_left - width) / 2;
+        let y = self.work_top + (self.work_bottom - self.work_top - height) / 2;
+        self.clamp_into_work_area(x, y, width, height)
+    }
+}
+
+/// 离点 `(x, y)` 最近的显示器（点在显示器内时距离为 0）
+pub fn nearest(monitors: &[MonitorRect], x: i32, y: i32) -> Option<&MonitorRect> {
+    monitors.iter().min_by_key(|monitor| {
+        let dx = (monitor.x - x).max(x - (monitor.x + monitor.width)).max(0);
+        let dy = (monitor.y - y).max(y - (monitor.y + monitor.height)).max(0);
+        i64::from(dx).pow(2) + i64::from(dy).pow(2)
+    })
+}
+
+/// 窗口左上角（物理像素）附近的标题栏是否落在某个显示器内；
+/// 不在任何显示器内时用户无法把窗口拖回来
+pub fn is_reachable(monitors: &[MonitorRect], x: i32, y: i32) -> bool {
+    // 取标题栏上略靠内的一点，避免窗口边框阴影造成误判
+    let (px, py) = (x + 48, y + 16);
+    monitors.iter().any(|monitor| {
+        px >= monitor.x
+            && px < monitor.x + monitor.width
+            && py >= monitor.y
+            && py < monitor.y + monitor.height
+    })
+}
+
+/// 枚举已连接的显示器，主显示器排在最前，其余按从左到右。
+///
+/// 目前仅 Windows 实现，其他平台返回空列表（界面上不提供选择）。
+#[cfg(target_os = "windows")]
+pub fn monitors() -> Vec<MonitorRect> {
+    use windows_sys::Win32::Foundation::{BOOL, LPARAM, RECT};
+    use windows_sys::Win32::Graphics::Gdi::{
+        EnumDisplayMonitors, GetMonitorInfoW, HDC, HMONITOR, MONITORINFO, MONITORINFOF_PRIMARY,
+    };
+
+    unsafe extern "system" fn collect(
+        monitor: HMONITOR,
+        _hdc: HDC,
+        _rect: *mut RECT,
+        data: LPARAM,
+    ) -> BOOL {
+        unsafe {
+            let monitors = &mut *(data as *mut Vec<MonitorRect>);
+            let mut info: MONITORINFO = std::mem::zeroed();
+            info.cbSize = std::mem::size_of::<MONITORINFO>() as u32;
+            if GetMonitorInfoW(monitor, &mut info) != 0 {
+                let rect = info.rcMonitor;
+                let work = info.rcWork;
+                monitors.push(MonitorRect {
+                    x: rect.left,
+                    y: rect.top,
+                    width: rect.right - rect.left,
+                    height: rect.bottom - rect.top,
+                    work_left: work.left,
+                    work_top: work.top,
+                    work_right: work.right,
+                    work_bottom: work.bottom,
+                    primary: info.dwFlags & MONITORINFOF_PRIMARY != 0,
+                });
+            }
+        }
+        1
+    }
+
+    let mut monitors: Vec<MonitorRect> = Vec::new();
+    unsafe {
+        EnumDisplayMonitors(
+            std::ptr::null_mut(),
+            std::ptr::null(),
+            Some(collect),
+            &mut monitors as *mut Vec<MonitorRect> as LPARAM,
+        );
+    }
+    monitors.sort_by_key(|monitor| (!monitor.primary, monitor.x, monitor.y));
+    monitors
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn monitors() -> Vec<MonitorRect> {
+    Vec::new()
+}
@@ -1,16 +1,79 @@
 use crate::schedule::{BuiltinSound, PeriodKind, SoundSlots, SoundSource};
-use rodio::{Decoder, OutputStream, Sink};
+use rodio::{Decoder, OutputStream, Sink, Source};
 use std::fs;
 use std::io::Cursor;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 static BELL_START: &[u8] = include_bytes!("../assets/bell_start.mp3");
 static BELL_END: &[u8] = include_bytes!("../assets/bell_end.mp3");
 static BELL_FUN: &[u8] = include_bytes!("../assets/bell_other.mp3");
+static CHIME: &[u8] = include_bytes!("../assets/chime.wav");
+static GONG: &[u8] = include_bytes!("../assets/gong.wav");
+static SOFT: &[u8] = include_bytes!("../assets/soft.wav");
 
 #[derive(Debug)]
 enum PreparedSound {
     Builtin(BuiltinSound),
-    Local(Vec<u8>),
+    Local { bytes: Vec<u8>, gain_db: f32 },
+}
+
+/// 排入播放线程的一段铃声
+struct QueuedSound {
+    sound: PreparedSound,
+    /// 本地音效在播放线程解码失败时改播的内置音效
+    fallback_on_decode: Option<BuiltinSound>,
+}
+
+/// 正在进行的播放。冷却期内的新铃声排入同一个 Sink，而不是再开一路输出流。
+struct ActivePlayback {
+    id: u64,
+    last_play: Instant,
+    /// 最近一次排入的音效标识（内置文件名或本地路径）
+    last_key: String,
+    queue: mpsc::Sender<QueuedSound>,
+    /// 置位后播放线程立即停止并丢弃排队的铃声
+    stop: Arc<AtomicBool>,
+}
+
+static ACTIVE_PLAYBACK: Mutex<Option<ActivePlayback>> = Mutex::new(None);
+static NEXT_PLAYBACK_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 最近一次发现无可用音频设备的时间；`None` 表示设备正常
+static AUDIO_MISSING_SINCE: Mutex<Option<Instant>> = Mutex::new(None);
+/// 无音频设备时重新检测的间隔，期间的触发直接跳过音频
+const AUDIO_RECHECK_INTERVAL: Duration = Duration::from_secs(60);
+const NO_AUDIO_DEVICE_WARNING: &str = "无音频设备，已静音";
+
+/// 检查是否有默认音频输出设备（远程桌面、无声卡环境下可能没有）。
+///
+/// 发现缺失后在 `AUDIO_RECHECK_INTERVAL` 内直接返回 `false`，不再反复探测和刷日志。
+fn audio_device_available() -> bool {
+    use rodio::cpal::traits::HostTrait;
+
+    let mut missing_since = AUDIO_MISSING_SINCE.lock().unwrap();
+    if let Some(checked) = *missing_since
+        && checked.elapsed() < AUDIO_RECHECK_INTERVAL
+    {
+        return false;
+    }
+
+    let available = rodio::cpal::default_host()
+        .default_output_device()
+        .is_some();
+    match (available, missing_since.is_some()) {
+        (false, false) => log::warn!("未检测到音频输出设备，铃声将静音直到设备恢复"),
+        (true, true) => log::info!("音频输出设备已恢复"),
+        _ => {}
+    }
+    *missing_since = (!available).then(Instant::now);
+    available
+}
+
+fn mark_audio_device_missing() {
+    *AUDIO_MISSING_SINCE.lock().unwrap() = Some(Instant::now());
 }
 
 fn builtin_sound_bytes(sound: BuiltinSound) -> &'static [u8] {
@@ -18,107 +81,748 @@ fn builtin_sound_bytes(sound: BuiltinSound) -> &'static [u8] {
         BuiltinSound::BellStart => BELL_START,
         BuiltinSound::BellEnd => BELL_END,
         BuiltinSound::Fun => BELL_FUN,
+        BuiltinSound::Chime => CHIME,
+        BuiltinSound::Gong => GONG,
+        BuiltinSound::Soft => SOFT,
     }
 }
 
 fn append_sound(sink: &Sink, sound: PreparedSound) -> Result<(), String> {
-    let bytes = match sound {
-        PreparedSound::Builtin(builtin) => builtin_sound_bytes(builtin).to_vec(),
-        PreparedSound::Local(bytes) => bytes,
+    let (bytes, gain_db) = match sound {
+        PreparedSound::Builtin(builtin) => (builtin_sound_bytes(builtin).to_vec(), 0.0),
+        PreparedSound::Local { bytes, gain_db } => (bytes, gain_db),
     };
 
     let cursor = Cursor::new(bytes);
     let source = Decoder::new(cursor).map_err(|e| e.to_string())?;
-    sink.append(source);
+    sink.append(source.amplify(db_to_factor(gain_db)));
     Ok(())
 }
 
+/// 增益调节范围（±dB）
+pub const MAX_GAIN_DB: f32 = 20.0;
+/// 响度分析只取开头若干秒，避免长音频分析过久
+const ANALYZE_SECS: usize = 10;
+
+fn db_to_factor(gain_db: f32) -> f32 {
+    10f32.powf(gain_db.clamp(-MAX_GAIN_DB, MAX_GAIN_DB) / 20.0)
+}
+
+/// 解码音频开头一段，计算 RMS 响度（0.0 ~ 1.0）
+fn rms_of(bytes: Vec<u8>) -> Result<f32, String> {
+    let decoder = Decoder::new(Cursor::new(bytes)).map_err(|e| format!("解码失败: {e}"))?;
+    let samples_per_sec = decoder.sample_rate() as usize * usize::from(decoder.channels());
+    let (sum, count) = decoder
+        .convert_samples::<f32>()
+        .take(samples_per_sec * ANALYZE_SECS)
+        .fold((0f64, 0usize), |(sum, count), sample| {
+            (sum + f64::from(sample * sample), count + 1)
+        });
+    if count == 0 {
+        return Err("音频为空".to_string());
+    }
+    Ok((sum / count as f64).sqrt() as f32)
+}
+
+/// 比较本地音效与参考内置铃声的 RMS 响度，返回让两者接近的建议增益（dB）
+pub fn suggest_gain_db(path: &str, reference: BuiltinSound) -> Result<f32, String> {
+    let local = rms_of(read_local_sound(path)?)?;
+    if local <= f32::EPSILON {
+        return Err("音频几乎无声，无法分析".to_string());
+    }
+    let builtin = rms_of(builtin_sound_bytes(reference).to_vec())?;
+    let gain_db = 20.0 * (builtin / local).log10();
+    Ok(gain_db.clamp(-MAX_GAIN_DB, MAX_GAIN_DB))
+}
+
+/// 读取本地音效并做一次解码检查，失败时返回具体原因（含 rodio 的解码错误）
+fn read_local_sound(path: &str) -> Result<Vec<u8>, String> {
+    if path.trim().is_empty() {
+        return Err("未选择文件".to_string());
+    }
+    let bytes = fs::read(path).map_err(|e| format!("读取失败: {e}"))?;
+    Decoder::new(Cursor::new(bytes.clone())).map_err(|e| format!("解码失败: {e}"))?;
+    Ok(bytes)
+}
+
+/// 检查音效是否可用；内置音效恒可用，本地音效返回读取/解码的具体错误。
+/// 可用时附带时长：内置音效、以及无法从文件头得知时长的格式为 `None`
+pub fn validate_sound(source: &SoundSource) -> Result<Option<Duration>, String> {
+    match source {
+        SoundSource::Builtin(_) => Ok(None),
+        SoundSource::Local { path, .. } => {
+            let bytes = read_local_sound(path)?;
+            Ok(Decoder::new(Cursor::new(bytes))
+                .ok()
+                .and_then(|decoder| decoder.total_duration()))
+        }
+    }
+}
+
+fn append_queued(sink: &Sink, queued: QueuedSound) {
+    if let Err(e) = append_sound(sink, queued.sound) {
+        log::warn!("铃声解码失败: {}", e);
+        if let Some(fallback) = queued.fallback_on_decode
+            && append_sound(sink, PreparedSound::Builtin(fallback)).is_err()
+        {
+            log::warn!("回退默认音效也失败");
+        }
+    }
+}
+
+/// 一次触发实际使用的音效（用于日志排查“为什么响的不是这个铃”）
+#[derive(Debug, Clone)]
+pub struct PlayOutcome {
+    /// 实际播放的音效：内置文件名或本地路径；无音频设备时为 `None`
+    pub source: Option<String>,
+    /// 所选本地音效不可用，已回退默认内置音效
+    pub fallback: bool,
+    /// 需要上报给界面的警告
+    pub warning: Option<String>,
+}
+
+impl PlayOutcome {
+    /// 日志中的音效描述
+    pub fn describe(&self) -> String {
+        match (&self.source, self.fallback) {
+            (Some(source), false) => source.clone(),
+            (Some(source), true) => format!("{source}（本地音效失效回退）"),
+            (None, _) => "无（无音频设备）".to_string(),
+        }
+    }
+}
+
 /// 播放节点对应音效（在独立线程中播放，不阻塞主线程）。
 ///
-/// 返回值：
+/// 距上次播放不足 `cooldown` 时：相同音效直接跳过，不同音效排在当前铃声之后播放，
+/// 避免两路输出流同时发声。
+///
+/// 返回实际使用的音效，其中 `warning`：
 /// - Some("本地音效失效，已回退默认")：本次本地音效无效并已自动回退
+/// - Some("无音频设备，已静音")：没有可用的音频输出设备，本次不播放
 /// - None：正常使用所选音效
-pub fn play_sound_for_period(kind: PeriodKind, slots: &SoundSlots) -> Option<String> {
-    let (selected, default_builtin) = match kind {
-        PeriodKind::Start => (&slots.start, BuiltinSound::BellStart),
-        PeriodKind::End => (&slots.end, BuiltinSound::BellEnd),
-    };
+///
+/// `duck_others` 为 `true` 时播放期间压低其他程序的音量（仅 Windows）。
+pub fn play_sound_for_period(
+    kind: PeriodKind,
+    slots: &SoundSlots,
+    cooldown: Duration,
+    duck_others: bool,
+) -> PlayOutcome {
+    if !audio_device_available() {
+        return PlayOutcome {
+            source: None,
+            fallback: false,
+            warning: Some(NO_AUDIO_DEVICE_WARNING.to_string()),
+        };
+    }
+
+    let selected = slots.for_kind(kind);
+    let default_builtin = kind.default_builtin_sound();
 
     let mut warning: Option<String> = None;
     let mut fallback_on_decode: Option<BuiltinSound> = None;
 
-    let prepared = match selected {
-        SoundSource::Builtin(sound) => PreparedSound::Builtin(*sound),
-        SoundSource::Local { path } => match fs::read(path) {
+    let (prepared, key) = match selected {
+        SoundSource::Builtin(sound) => (PreparedSound::Builtin(*sound), sound.label().to_string()),
+        // 在主线程提前做一次解码可用性检查，避免在播放线程才发现本地文件损坏。
+        SoundSource::Local { path, gain_db } => match read_local_sound(path) {
             Ok(bytes) => {
-                // 在主线程提前做一次解码可用性检查，避免在播放线程才发现本地文件损坏。
-                if Decoder::new(Cursor::new(bytes.clone())).is_ok() {
-                    fallback_on_decode = Some(default_builtin);
-                    PreparedSound::Local(bytes)
-                } else {
-                    warning = Some("本地音效失效，已回退默认".to_string());
-                    PreparedSound::Builtin(default_builtin)
-                }
+                fallback_on_decode = Some(default_builtin);
+                (
+                    PreparedSound::Local {
+                        bytes,
+                        gain_db: *gain_db,
+                    },
+                    path.clone(),
+                )
             }
             Err(e) => {
-                log::warn!("读取本地音效失败（{}）: {}", path, e);
+                log::warn!("本地音效不可用（{}）: {}", path, e);
                 warning = Some("本地音效失效，已回退默认".to_string());
-                PreparedSound::Builtin(default_builtin)
+                (
+                    PreparedSound::Builtin(default_builtin),
+                    default_builtin.label().to_string(),
+                )
             }
         },
     };
 
-    std::thread::spawn(move || match OutputStream::try_default() {
-        Ok((_stream, handle)) => match Sink::try_new(&handle) {
-            Ok(sink) => match append_sound(&sink, prepared) {
-                Ok(_) => sink.sleep_until_end(),
-                Err(e) => {
-                    log::warn!("铃声解码失败: {}", e);
-                    if let Some(fallback) = fallback_on_decode {
-                        if append_sound(&sink, PreparedSound::Builtin(fallback)).is_ok() {
-                            sink.sleep_until_end();
-                        } else {
-                            log::warn!("回退默认音效也失败");
-                        }
+    let outcome = PlayOutcome {
+        source: Some(key.clone()),
+        fallback: warning.is_some(),
+        warning,
+    };
+
+    start_or_queue(
+        key,
+        QueuedSound {
+            sound: prepared,
+            fallback_on_decode,
+        },
+        cooldown,
+        duck_others,
+    );
+
+    outcome
+}
+
+/// 冷却期内排入当前播放线程（相同音效跳过），否则新开播放线程
+fn start_or_queue(key: String, mut queued: QueuedSound, cooldown: Duration, duck_others: bool) {
+    let mut active = ACTIVE_PLAYBACK.lock().unwrap();
+
+    if let Some(playback) = active.as_mut()
+        && playback.last_play.elapsed() < cooldown
+    {
+        if playback.last_key == key {
+            log::info!("冷却期内重复的铃声已跳过: {}", key);
+            return;
+        }
+        match playback.queue.send(queued) {
+            Ok(()) => {
+                log::info!("冷却期内的铃声已排队: {}", key);
+                playback.last_play = Instant::now();
+                playback.last_key = key;
+                return;
+            }
+            // 播放线程已退出，重新开一路
+            Err(mpsc::SendError(returned)) => queued = returned,
+        }
+    }
+
+    let id = NEXT_PLAYBACK_ID.fetch_add(1, Ordering::Relaxed);
+    let (tx, rx) = mpsc::channel();
+    let stop = Arc::new(AtomicBool::new(false));
+    *active = Some(ActivePlayback {
+        id,
+        last_play: Instant::now(),
+        last_key: key,
+        queue: tx,
+        stop: Arc::clone(&stop),
+    });
+    drop(active);
+
+    std::thread::spawn(move || {
+        run_playback(queued, rx, id, &stop, duck_others);
+        clear_active_playback(id);
+    });
+}
+
+/// 是否有铃声正在播放（含排队中的）
+pub fn is_playing() -> bool {
+    ACTIVE_PLAYBACK.lock().unwrap().is_some()
+}
+
+/// 立即停止正在播放的铃声并丢弃排队的铃声
+pub fn stop_playback() {
+    if let Some(playback) = ACTIVE_PLAYBACK.lock().unwrap().take() {
+        playback.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn clear_active_playback(id: u64) {
+    let mut active = ACTIVE_PLAYBACK.lock().unwrap();
+    if active.as_ref().is_some_and(|playback| playback.id == id) {
+        *active = None;
+    }
+}
+
+fn run_playback(
+    first: QueuedSound,
+    queue: mpsc::Receiver<QueuedSound>,
+    id: u64,
+    stop: &AtomicBool,
+    duck_others: bool,
+) {
+    let (_stream, handle) = match OutputStream::try_default() {
+        Ok(output) => output,
+        Err(e) => {
+            log::warn!("音频输出设备初始化失败: {}", e);
+            mark_audio_device_missing();
+            return;
+        }
+    };
+    let sink = match Sink::try_new(&handle) {
+        Ok(sink) => sink,
+        Err(e) => {
+            log::warn!("音频 Sink 初始化失败: {}", e);
+            return;
+        }
+    };
+
+    // 守卫在本函数返回时（包括解码失败、播放结束）恢复其他程序的音量
+    let _duck = duck_others.then(ducking::duck_other_sessions);
+
+    append_queued(&sink, first);
+    loop {
+        if stop.load(Ordering::Relaxed) {
+            sink.stop();
+            log::info!("铃声播放已停止");
+            return;
+        }
+        while let Ok(next) = queue.try_recv() {
+            append_queued(&sink, next);
+        }
+
+        if sink.empty() {
+            // 持锁再确认一次队列，避免与 start_or_queue 的发送产生竞争
+            let mut active = ACTIVE_PLAYBACK.lock().unwrap();
+            match queue.try_recv() {
+                Ok(next) => {
+                    drop(active);
+                    append_queued(&sink, next);
+                }
+                Err(_) => {
+                    if active.as_ref().is_some_and(|playback| playback.id == id) {
+                        *active = None;
                     }
+                    return;
                 }
-            },
-            Err(e) => log::warn!("音频 Sink 初始化失败: {}", e),
-        },
-        Err(e) => log::warn!("音频输出设备初始化失败: {}", e),
+            }
+        }
+
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// 铃声播放期间压低其他程序音量（仅 Windows，其他平台为空操作）。
+///
+/// 多路播放线程重叠时（冷却期后的新铃声、铃声中试听）共用一次压低：第一个守卫压低，
+/// 最后一个守卫释放时恢复，避免后来者把已压低的音量记作原音量
+#[cfg(target_os = "windows")]
+mod ducking {
+    use std::sync::Mutex;
+
+    use windows::Win32::Media::Audio::{
+        IAudioSessionControl, IAudioSessionControl2, IAudioSessionManager2, IMMDeviceEnumerator,
+        ISimpleAudioVolume, MMDeviceEnumerator, eMultimedia, eRender,
+    };
+    use windows::Win32::System::Com::{
+        CLSCTX_ALL, COINIT_MULTITHREADED, CoCreateInstance, CoInitializeEx, CoTaskMemFree,
+    };
+    use windows::core::Interface;
+
+    /// 压低后的音量比例
+    const DUCK_RATIO: f32 = 0.3;
+
+    /// 持有守卫的播放线程数，以及被压低的会话（会话实例 ID）与其原音量。
+    /// COM 接口不能跨线程保存，恢复时按 ID 重新查找会话
+    struct DuckState {
+        holders: usize,
+        originals: Vec<(String, f32)>,
+    }
+
+    static STATE: Mutex<DuckState> = Mutex::new(DuckState {
+        holders: 0,
+        originals: Vec::new(),
+    });
+
+    /// 播放线程持有期间保持压低，最后一个释放时恢复
+    pub struct DuckGuard(());
+
+    impl Drop for DuckGuard {
+        fn drop(&mut self) {
+            let mut state = STATE.lock().unwrap();
+            state.holders -= 1;
+            if state.holders == 0 {
+                restore_sessions(&std::mem::take(&mut state.originals));
+            }
+        }
+    }
+
+    pub fn duck_other_sessions() -> DuckGuard {
+        let mut state = STATE.lock().unwrap();
+        state.holders += 1;
+        if state.holders == 1 {
+            state.originals = duck_sessions();
+        }
+        DuckGuard(())
+    }
+
+    /// 压低除本进程外的所有会话，返回成功压低的会话及原音量；单个会话失败时跳过
+    fn duck_sessions() -> Vec<(String, f32)> {
+        let mut originals = Vec::new();
+        for (id, volume) in other_sessions() {
+            let ducked = unsafe {
+                volume.GetMasterVolume().and_then(|original| {
+                    volume
+                        .SetMasterVolume(original * DUCK_RATIO, std::ptr::null())
+                        .map(|()| original)
+                })
+            };
+            match ducked {
+                Ok(original) => originals.push((id, original)),
+                Err(e) => log::warn!("压低音频会话失败，已跳过: {}", e),
+            }
+        }
+        log::info!("铃声播放期间已压低 {} 个音频会话", originals.len());
+        originals
+    }
+
+    fn restore_sessions(originals: &[(String, f32)]) {
+        if originals.is_empty() {
+            return;
+        }
+        for (id, volume) in other_sessions() {
+            let Some((_, original)) = originals.iter().find(|(ducked, _)| *ducked == id) else {
+                continue;
+            };
+            if let Err(e) = unsafe { volume.SetMasterVolume(*original, std::ptr::null()) } {
+                log::warn!("恢复其他程序音量失败: {}", e);
+            }
+        }
+    }
+
+    /// 默认输出设备上除本进程外的音频会话（会话实例 ID，音量接口）；
+    /// 枚举失败时返回空，单个会话查询失败时跳过
+    fn other_sessions() -> Vec<(String, ISimpleAudioVolume)> {
+        let sessions = unsafe {
+            // 本线程可能已初始化过 COM，忽略重复初始化的返回值
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+
+            CoCreateInstance::<_, IMMDeviceEnumerator>(&MMDeviceEnumerator, None, CLSCTX_ALL)
+                .and_then(|enumerator| enumerator.GetDefaultAudioEndpoint(eRender, eMultimedia))
+                .and_then(|device| device.Activate::<IAudioSessionManager2>(CLSCTX_ALL, None))
+                .and_then(|manager| manager.GetSessionEnumerator())
+        };
+        let sessions = match sessions {
+            Ok(sessions) => sessions,
+            Err(e) => {
+                log::warn!("枚举音频会话失败: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let own_pid = std::process::id();
+        let count = unsafe { sessions.GetCount() }.unwrap_or(0);
+        (0..count)
+            .filter_map(|index| {
+                unsafe { sessions.GetSession(index) }
+                    .and_then(|control| other_session(&control, own_pid))
+                    .inspect_err(|e| log::warn!("查询音频会话失败，已跳过: {}", e))
+                    .ok()
+                    .flatten()
+            })
+            .collect()
+    }
+
+    /// 会话的实例 ID 与音量接口；属于本进程的会话返回 `None`
+    fn other_session(
+        control: &IAudioSessionControl,
+        own_pid: u32,
+    ) -> windows::core::Result<Option<(String, ISimpleAudioVolume)>> {
+        unsafe {
+            let control2: IAudioSessionControl2 = control.cast()?;
+            if control2.GetProcessId().is_ok_and(|pid| pid == own_pid) {
+                return Ok(None);
+            }
+            let raw_id = control2.GetSessionInstanceIdentifier()?;
+            let id = raw_id.to_string();
+            CoTaskMemFree(Some(raw_id.0 as *const _));
+            let volume: ISimpleAudioVolume = control.cast()?;
+            Ok(id.ok().map(|id| (id, volume)))
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod ducking {
+    pub struct DuckGuard;
+
+    pub fn duck_other_sessions() -> DuckGuard {
+        DuckGuard
+    }
+}
+
+/// 尽力检测是否有会议/通话正在进行，返回命中的进程名。
+///
+/// 依次检查：是否运行着 `processes` 中的进程（不区分大小写），以及默认通信录音设备上
+/// 是否有其他进程的活动音频会话（即正占用通话麦克风）。仅 Windows，其他平台恒返回 `None`。
+#[cfg(target_os = "windows")]
+pub fn detect_meeting(processes: &[String]) -> Option<String> {
+    let running = running_processes();
+    let listed = running.iter().find(|(_, exe)| {
+        processes
+            .iter()
+            .any(|process| process.trim().eq_ignore_ascii_case(exe))
     });
+    if let Some((_, exe)) = listed {
+        return Some(exe.clone());
+    }
 
-    warning
+    let pid = active_communication_session()
+        .inspect_err(|e| log::debug!("检查通话音频会话失败: {}", e))
+        .ok()
+        .flatten()?;
+    let exe = running
+        .into_iter()
+        .find(|(running_pid, _)| *running_pid == pid)
+        .map_or_else(|| format!("进程 {pid}"), |(_, exe)| exe);
+    Some(format!("{exe} 正在使用通话麦克风"))
 }
 
-/// 发送系统桌面通知
+/// 当前运行的进程（PID，小写的可执行文件名）
+#[cfg(target_os = "windows")]
+fn running_processes() -> Vec<(u32, String)> {
+    use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, PROCESSENTRY32W, Process32FirstW, Process32NextW,
+        TH32CS_SNAPPROCESS,
+    };
+
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+        if snapshot == INVALID_HANDLE_VALUE {
+            return Vec::new();
+        }
+
+        let mut entry: PROCESSENTRY32W = std::mem::zeroed();
+        entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+
+        let mut running = Vec::new();
+        if Process32FirstW(snapshot, &mut entry) != 0 {
+            loop {
+                let len = entry
+                    .szExeFile
+                    .iter()
+                    .position(|&c| c == 0)
+                    .unwrap_or(entry.szExeFile.len());
+                let exe = String::from_utf16_lossy(&entry.szExeFile[..len]).to_lowercase();
+                running.push((entry.th32ProcessID, exe));
+                if Process32NextW(snapshot, &mut entry) == 0 {
+                    break;
+                }
+            }
+        }
+
+        CloseHandle(snapshot);
+        running
+    }
+}
+
+/// 默认通信录音设备上处于活动状态的其他进程的音频会话，返回其 PID
+#[cfg(target_os = "windows")]
+fn active_communication_session() -> windows::core::Result<Option<u32>> {
+    use windows::Win32::Media::Audio::{
+        AudioSessionStateActive, IAudioSessionControl2, IAudioSessionManager2, IMMDeviceEnumerator,
+        MMDeviceEnumerator, eCapture, eCommunications,
+    };
+    use windows::Win32::System::Com::{
+        CLSCTX_ALL, COINIT_MULTITHREADED, CoCreateInstance, CoInitializeEx,
+    };
+    use windows::core::Interface;
+
+    unsafe {
+        // 本线程可能已初始化过 COM，忽略重复初始化的返回值
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+
+        let enumerator: IMMDeviceEnumerator =
+            CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+        let device = enumerator.GetDefaultAudioEndpoint(eCapture, eCommunications)?;
+        let manager: IAudioSessionManager2 = device.Activate(CLSCTX_ALL, None)?;
+        let sessions = manager.GetSessionEnumerator()?;
+
+        let own_pid = std::process::id();
+        for index in 0..sessions.GetCount()? {
+            let control = sessions.GetSession(index)?;
+            if control.GetState()? != AudioSessionStateActive {
+                continue;
+            }
+            let control2: IAudioSessionControl2 = control.cast()?;
+            let pid = control2.GetProcessId()?;
+            if pid != own_pid && pid != 0 {
+                return Ok(Some(pid));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn detect_meeting(_processes: &[String]) -> Option<String> {
+    None
+}
+
+/// 系统主音量低于该比例视为“过低”
+#[cfg(target_os = "windows")]
+const LOW_VOLUME_THRESHOLD: f32 = 0.05;
+
+/// 检查系统主音量是否静音或过低（仅 Windows）。
+///
+/// 无法检测时（其他平台、查询失败）返回 `None`。
+#[cfg(target_os = "windows")]
+pub fn system_volume_low() -> Option<bool> {
+    use windows::Win32::Media::Audio::Endpoints::IAudioEndpointVolume;
+    use windows::Win32::Media::Audio::{
+        IMMDeviceEnumerator, MMDeviceEnumerator, eMultimedia, eRender,
+    };
+    use windows::Win32::System::Com::{
+        CLSCTX_ALL, COINIT_MULTITHREADED, CoCreateInstance, CoInitializeEx,
+    };
+
+    fn query() -> windows::core::Result<bool> {
+        unsafe {
+            // 本线程可能已初始化过 COM，忽略重复初始化的返回值
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+
+            let enumerator: IMMDeviceEnumerator =
+                CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+            let device = enumerator.GetDefaultAudioEndpoint(eRender, eMultimedia)?;
+            let volume: IAudioEndpointVolume = device.Activate(CLSCTX_ALL, None)?;
+            let muted = volume.GetMute()?.as_bool();
+            let level = volume.GetMasterVolumeLevelScalar()?;
+            Ok(muted || level < LOW_VOLUME_THRESHOLD)
+        }
+    }
+
+    query()
+        .inspect_err(|e| log::warn!("查询系统音量失败: {}", e))
+        .ok()
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn system_volume_low() -> Option<bool> {
+    None
+}
+
+/// 点击通知时调用（恢复主窗口），由界面在托盘就绪后设置
+static CLICK_HANDLER: Mutex<Option<Box<dyn Fn() + Send>>> = Mutex::new(None);
+
+/// 设置点击通知的回调。目前只有 Linux（XDG 通知服务）能收到点击事件；
+/// Windows 与 macOS 上 notify-rust 不提供点击回调，设置后不会被调用
+pub fn set_click_handler(handler: impl Fn() + Send + 'static) {
+    *CLICK_HANDLER.lock().unwrap() = Some(Box::new(handler));
+}
+
+/// 发送系统桌面通知（后台线程，不阻塞调用方）
 pub fn send_notification(title: &str, body: &str) {
     let title = title.to_string();
     let body = body.to_string();
 
     std::thread::spawn(move || {
-        #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+        #[cfg(target_os = "linux")]
         {
-            use notify_rust::Notification;
-
-            #[cfg(target_os = "macos")]
-            let result = Notification::new()
-                .summary(&title)
-                .body(&body)
-                .timeout(notify_rust::Timeout::Milliseconds(5000))
-                .show();
-
-            #[cfg(not(target_os = "macos"))]
-            let result = Notification::new()
-                .summary(&title)
-                .body(&body)
-                .icon("dialog-information")
-                .timeout(notify_rust::Timeout::Milliseconds(5000))
-                .show();
-
-            if let Err(e) = result {
-                log::warn!("系统通知发送失败: {}", e);
+            // 通知关闭（超时或点击）前一直等待，本线程专用于这条通知
+            let shown = notification(&title, &body)
+                .action("default", "打开")
+                .show()
+                .map_err(|e| e.to_string());
+            match shown {
+                Ok(handle) => handle.wait_for_action(|action| {
+                    if action == "default"
+                        && let Some(handler) = CLICK_HANDLER.lock().unwrap().as_ref()
+                    {
+                        handler();
+                    }
+                }),
+                Err(e) => log::warn!("系统通知发送失败: {}", e),
             }
         }
+
+        #[cfg(not(target_os = "linux"))]
+        if let Err(e) = show_notification(&title, &body) {
+            log::warn!("系统通知发送失败: {}", e);
+        }
     });
 }
+
+#[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+fn notification(title: &str, body: &str) -> notify_rust::Notification {
+    let mut notification = notify_rust::Notification::new();
+    notification
+        .summary(title)
+        .body(body)
+        .timeout(notify_rust::Timeout::Milliseconds(5000));
+    #[cfg(not(target_os = "macos"))]
+    notification.icon("dialog-information");
+    notification
+}
+
+/// 同步发送系统桌面通知并返回结果（自检用）
+pub fn show_notification(title: &str, body: &str) -> Result<(), String> {
+    #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+    {
+        notification(title, body)
+            .show()
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+    {
+        let _ = (title, body);
+        Err("当前平台不支持系统通知".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 在临时目录写入测试音频，返回路径
+    fn temp_sound(name: &str, bytes: &[u8]) -> String {
+        let path = std::env::temp_dir().join(format!("wc_notice_{}_{name}", std::process::id()));
+        fs::write(&path, bytes).unwrap();
+        path.display().to_string()
+    }
+
+    fn local(path: String) -> SoundSource {
+        SoundSource::Local { path, gain_db: 0.0 }
+    }
+
+    #[test]
+    fn valid_wav_reports_duration() {
+        let path = temp_sound("valid.wav", CHIME);
+        let result = validate_sound(&local(path.clone()));
+        let _ = fs::remove_file(&path);
+        assert!(matches!(result, Ok(Some(duration)) if duration > Duration::ZERO));
+    }
+
+    #[test]
+    fn garbage_bytes_are_rejected() {
+        // 固定种子的伪随机字节，保证可复现
+        let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+        let garbage: Vec<u8> = (0..4096)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                seed as u8
+            })
+            .collect();
+        let path = temp_sound("garbage.mp3", &garbage);
+        let result = validate_sound(&local(path.clone()));
+        let _ = fs::remove_file(&path);
+        let error = result.unwrap_err();
+        assert!(error.starts_with("解码失败"), "{error}");
+    }
+
+    #[test]
+    fn truncated_wav_is_rejected() {
+        // 截断在 WAV 文件头中间
+        let path = temp_sound("truncated.wav", &CHIME[..24]);
+        let result = validate_sound(&local(path.clone()));
+        let _ = fs::remove_file(&path);
+        let error = result.unwrap_err();
+        assert!(error.starts_with("解码失败"), "{error}");
+    }
+
+    #[test]
+    fn missing_or_empty_path_is_rejected() {
+        assert_eq!(
+            validate_sound(&local("  ".to_string())).unwrap_err(),
+            "未选择文件"
+        );
+        let missing = std::env::temp_dir().join("wc_notice_missing_sound.wav");
+        let error = validate_sound(&local(missing.display().to_string())).unwrap_err();
+        assert!(error.starts_with("读取失败"), "{error}");
+    }
+
+    #[test]
+    fn builtin_sounds_are_always_valid() {
+        assert_eq!(
+            validate_sound(&SoundSource::Builtin(BuiltinSound::Chime)),
+            Ok(None)
+        );
+    }
+}
@@ -1,24 +1,51 @@
-use chrono::{NaiveTime, Timelike};
+//! 时间表、节点与应用配置的数据结构，以及不依赖界面和引擎的纯计算：
+//! 时间解析（`normalize_time_str` 等）、当前状态与下一节点（`ScheduleProfile::current_status`
+//! / `ScheduleProfile::next_period`）、节点文本导入导出等。配置的读写见可执行程序的 `config` 模块。
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum PeriodKind {
     Start,
     End,
+    /// 预备铃
+    Prepare,
+    /// 仅用于在列表与时间轴上标注（如午休），从不响铃
+    Marker,
 }
 
 impl PeriodKind {
+    pub const ALL: [PeriodKind; 4] = [
+        PeriodKind::Start,
+        PeriodKind::End,
+        PeriodKind::Prepare,
+        PeriodKind::Marker,
+    ];
+
     pub fn label(&self) -> &str {
         match self {
             PeriodKind::Start => "开始",
             PeriodKind::End => "结束",
+            PeriodKind::Prepare => "预备",
+            PeriodKind::Marker => "标记",
         }
     }
 
+    /// 是否会被引擎触发（标记节点只显示不响铃）
+    pub fn fires(&self) -> bool {
+        *self != PeriodKind::Marker
+    }
+
+    pub fn from_label(label: &str) -> Option<PeriodKind> {
+        Self::ALL.into_iter().find(|kind| kind.label() == label)
+    }
+
     pub fn default_builtin_sound(&self) -> BuiltinSound {
         match self {
             PeriodKind::Start => BuiltinSound::BellStart,
             PeriodKind::End => BuiltinSound::BellEnd,
+            PeriodKind::Prepare | PeriodKind::Marker => BuiltinSound::Fun,
         }
     }
 }
@@ -28,40 +55,145 @@ pub enum BuiltinSound {
     BellStart,
     BellEnd,
     Fun,
+    Chime,
+    Gong,
+    Soft,
 }
 
 impl BuiltinSound {
-    pub const ALL: [BuiltinSound; 3] = [
+    /// 界面下拉框按此顺序列出；新增内置音效时加在这里，
+    /// `label` / `display_name` / `notifier::builtin_sound_bytes` 的穷举 match 会提示补全其余位置
+    pub const ALL: [BuiltinSound; 6] = [
         BuiltinSound::BellStart,
         BuiltinSound::BellEnd,
         BuiltinSound::Fun,
+        BuiltinSound::Chime,
+        BuiltinSound::Gong,
+        BuiltinSound::Soft,
     ];
 
+    /// 内置音效文件名，用于日志与音效去重；配置按枚举名序列化，与此无关
     pub fn label(&self) -> &str {
         match self {
             BuiltinSound::BellStart => "bell_start.mp3",
             BuiltinSound::BellEnd => "bell_end.mp3",
             BuiltinSound::Fun => "bell_other.mp3",
+            BuiltinSound::Chime => "chime.wav",
+            BuiltinSound::Gong => "gong.wav",
+            BuiltinSound::Soft => "soft.wav",
+        }
+    }
+
+    /// 界面上显示的名称
+    pub fn display_name(&self) -> &str {
+        match self {
+            BuiltinSound::BellStart => "上课铃",
+            BuiltinSound::BellEnd => "下课铃",
+            BuiltinSound::Fun => "趣味铃声",
+            BuiltinSound::Chime => "叮咚",
+            BuiltinSound::Gong => "锣声",
+            BuiltinSound::Soft => "柔和提示音",
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// 内置音效组合：一次为开始/结束/预备三个位置选好搭配的内置音效。
+/// 只是选择时的快捷方式，配置中仍按位置分别保存，不记录组合本身
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundPack {
+    /// 各类节点的默认内置音效
+    Classic,
+    Chime,
+    Gong,
+    Soft,
+}
+
+impl SoundPack {
+    pub const ALL: [SoundPack; 4] = [
+        SoundPack::Classic,
+        SoundPack::Chime,
+        SoundPack::Gong,
+        SoundPack::Soft,
+    ];
+
+    pub fn label(&self) -> &str {
+        match self {
+            SoundPack::Classic => "经典铃声",
+            SoundPack::Chime => "电子叮咚",
+            SoundPack::Gong => "锣声",
+            SoundPack::Soft => "柔和",
+        }
+    }
+
+    /// (开始, 结束, 预备) 三个位置的音效
+    pub fn sounds(&self) -> [BuiltinSound; 3] {
+        match self {
+            SoundPack::Classic => [
+                PeriodKind::Start.default_builtin_sound(),
+                PeriodKind::End.default_builtin_sound(),
+                PeriodKind::Prepare.default_builtin_sound(),
+            ],
+            SoundPack::Chime => [BuiltinSound::Chime, BuiltinSound::Chime, BuiltinSound::Soft],
+            SoundPack::Gong => [BuiltinSound::Gong, BuiltinSound::Gong, BuiltinSound::Chime],
+            SoundPack::Soft => [BuiltinSound::Soft, BuiltinSound::Soft, BuiltinSound::Chime],
+        }
+    }
+
+    /// 三个位置恰好是某个组合时返回该组合，否则为自定义（`None`）
+    pub fn detect(slots: &SoundSlots) -> Option<SoundPack> {
+        let current = [&slots.start, &slots.end, &slots.prepare];
+        Self::ALL.into_iter().find(|pack| {
+            pack.sounds()
+                .iter()
+                .zip(current)
+                .all(|(builtin, source)| *source == SoundSource::Builtin(*builtin))
+        })
+    }
+
+    pub fn apply(&self, slots: &mut SoundSlots) {
+        let [start, end, prepare] = self.sounds();
+        slots.start = SoundSource::Builtin(start);
+        slots.end = SoundSource::Builtin(end);
+        slots.prepare = SoundSource::Builtin(prepare);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SoundSource {
     Builtin(BuiltinSound),
-    Local { path: String },
+    Local {
+        path: String,
+        /// 播放增益（dB），用于让本地音效与内置铃声音量接近
+        #[serde(default)]
+        gain_db: f32,
+    },
 }
 
 impl SoundSource {
     pub fn default_for_kind(kind: PeriodKind) -> Self {
         SoundSource::Builtin(kind.default_builtin_sound())
     }
+
+    /// 内置铃声名，或本地音效的文件名
+    pub fn describe(&self) -> String {
+        match self {
+            SoundSource::Builtin(builtin) => builtin.display_name().to_string(),
+            SoundSource::Local { path, .. } => std::path::Path::new(path)
+                .file_name()
+                .map_or_else(|| path.clone(), |name| name.to_string_lossy().into_owned()),
+        }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SoundSlots {
     pub start: SoundSource,
     pub end: SoundSource,
+    #[serde(default = "default_prepare_sound")]
+    pub prepare: SoundSource,
+    /// 今日最后一个结束节点使用的音效（放学铃），未设置时沿用 `end`
+    #[serde(default)]
+    pub day_end: Option<SoundSource>,
 }
 
 impl Default for SoundSlots {
@@ -69,16 +201,134 @@ impl Default for SoundSlots {
         Self {
             start: SoundSource::default_for_kind(PeriodKind::Start),
             end: SoundSource::default_for_kind(PeriodKind::End),
+            prepare: default_prepare_sound(),
+            day_end: None,
         }
     }
 }
 
+impl SoundSlots {
+    pub fn for_kind(&self, kind: PeriodKind) -> &SoundSource {
+        match kind {
+            PeriodKind::Start => &self.start,
+            PeriodKind::End => &self.end,
+            PeriodKind::Prepare | PeriodKind::Marker => &self.prepare,
+        }
+    }
+}
+
+fn default_prepare_sound() -> SoundSource {
+    SoundSource::default_for_kind(PeriodKind::Prepare)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Period {
     pub time: String,
     pub kind: PeriodKind,
     pub name: String,
     pub enabled: bool,
+    /// 备注（如“记得收作业”），显示在通知正文中
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// 相对日出/日落定义的时间；设置时 `time` 为当天换算结果，由 `resolve_solar_times` 每天更新
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solar: Option<SolarTime>,
+    /// 静音节点：照常发送通知，但不播放铃声
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub silent: bool,
+}
+
+/// 相对日出/日落的节点当天无法换算（未设置位置、极昼/极夜）时的时间占位，不会触发
+pub const SOLAR_UNRESOLVED: &str = "--:--";
+
+/// 相对日出/日落的节点所参照的事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SolarEvent {
+    Sunrise,
+    Sunset,
+}
+
+impl SolarEvent {
+    pub const ALL: [SolarEvent; 2] = [SolarEvent::Sunrise, SolarEvent::Sunset];
+
+    pub fn label(&self) -> &str {
+        match self {
+            SolarEvent::Sunrise => "日出",
+            SolarEvent::Sunset => "日落",
+        }
+    }
+}
+
+/// 节点时间定义为日出/日落前后若干分钟（正数为之后）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SolarTime {
+    pub event: SolarEvent,
+    pub offset_mins: i64,
+}
+
+impl SolarTime {
+    /// 如“日落前 30 分钟”
+    pub fn describe(&self) -> String {
+        let event = self.event.label();
+        match self.offset_mins {
+            0 => event.to_string(),
+            mins if mins > 0 => format!("{event}后 {mins} 分钟"),
+            mins => format!("{event}前 {} 分钟", -mins),
+        }
+    }
+
+    /// 批量编辑中的写法，如“日落-30”“日出+15”“日出”
+    pub fn to_token(&self) -> String {
+        let event = self.event.label();
+        match self.offset_mins {
+            0 => event.to_string(),
+            mins => format!("{event}{mins:+}"),
+        }
+    }
+
+    /// 解析 `to_token` 的写法，偏移量为分钟
+    pub fn parse_token(input: &str) -> Option<Self> {
+        let input = input.trim();
+        SolarEvent::ALL.into_iter().find_map(|event| {
+            let rest = input.strip_prefix(event.label())?.trim();
+            let offset_mins = if rest.is_empty() {
+                0
+            } else if rest.starts_with(['+', '-']) {
+                rest.parse().ok()?
+            } else {
+                return None;
+            };
+            Some(Self { event, offset_mins })
+        })
+    }
+
+    /// 换算为 `date` 当天的墙钟时间（按分钟取整），`to_zone` 把 UTC 时刻换算到时间表的墙钟。
+    /// 当天无日出/日落，或偏移后落到其他日期时返回 `None`
+    pub fn resolve(
+        &self,
+        date: NaiveDate,
+        location: &GeoLocation,
+        to_zone: impl Fn(DateTime<Utc>) -> NaiveDateTime,
+    ) -> Option<NaiveTime> {
+        let (sunrise, sunset) =
+            crate::solar::sunrise_sunset(date, location.latitude, location.longitude)?;
+        let event = match self.event {
+            SolarEvent::Sunrise => sunrise,
+            SolarEvent::Sunset => sunset,
+        };
+        let at = to_zone(event + TimeDelta::minutes(self.offset_mins));
+        let at = at.with_second(0)?.with_nanosecond(0)?;
+        (at.date() == date).then(|| at.time())
+    }
+}
+
+/// 日出日落计算使用的地理位置
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GeoLocation {
+    /// 纬度，北纬为正
+    pub latitude: f64,
+    /// 经度，东经为正
+    pub longitude: f64,
 }
 
 impl Period {
@@ -88,9 +338,18 @@ impl Period {
             kind,
             name: name.to_string(),
             enabled: true,
+            note: None,
+            solar: None,
+            silent: false,
         }
     }
 
+    /// 设置备注，空白备注视为无备注
+    pub fn set_note(&mut self, note: &str) {
+        let note = note.trim();
+        self.note = (!note.is_empty()).then(|| note.to_string());
+    }
+
     pub fn naive_time(&self) -> Option<NaiveTime> {
         NaiveTime::parse_from_str(&self.time, "%H:%M:%S")
             .or_else(|_| NaiveTime::parse_from_str(&self.time, "%H:%M"))
@@ -110,6 +369,43 @@ impl Period {
             })
             .unwrap_or(false)
     }
+
+    /// 节点时间已到且迟到不超过 `tolerance`（0 时等同于 `matches_now`）。
+    /// 用于时钟被校正跳过节点时仍能补响；是否已响过由调用方防重。
+    pub fn matches_within(&self, now: &NaiveTime, tolerance: TimeDelta) -> bool {
+        if !self.enabled {
+            return false;
+        }
+
+        self.naive_time()
+            .map(|time| {
+                let late = now.with_nanosecond(0).unwrap_or(*now) - time;
+                late >= TimeDelta::zero() && late <= tolerance
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// 当前所处的时段：上课（开始节点到与之配对的结束节点）或课间（结束节点到下一个开始节点）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    /// 上课时为开始节点名称，课间时为下一个开始节点名称
+    pub name: String,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub in_class: bool,
+}
+
+impl Session {
+    /// 已过去的比例 0.0~1.0
+    pub fn progress(&self, now: &NaiveTime) -> f32 {
+        let total = (self.end - self.start).num_seconds();
+        if total <= 0 {
+            return 1.0;
+        }
+        let elapsed = (*now - self.start).num_seconds();
+        (elapsed as f32 / total as f32).clamp(0.0, 1.0)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -118,6 +414,22 @@ pub struct ScheduleProfile {
     pub name: String,
     pub periods: Vec<Period>,
     pub sound: SoundSlots,
+    /// 最近一次被设为当前时间表的时间（用于“最近使用”排序）
+    #[serde(default)]
+    pub last_used: Option<DateTime<Local>>,
+    /// 已归档：保留但不参与响铃，也不会被自动选为当前时间表
+    #[serde(default)]
+    pub archived: bool,
+    /// 单独暂停该时间表（仍可被选为当前时间表）。全局暂停优先：
+    /// 全局暂停时任何时间表都不响；全局启用时仅未暂停的当前时间表响铃
+    #[serde(default)]
+    pub muted: bool,
+    #[serde(default)]
+    pub mode: ScheduleMode,
+    /// 按该时区（IANA 名称，如 `Asia/Shanghai`）的时间匹配节点，`None` 为本地时间。
+    /// 需要启用 `schedule-timezone` 特性，否则始终按本地时间
+    #[serde(default)]
+    pub timezone: Option<String>,
 }
 
 impl ScheduleProfile {
@@ -146,6 +458,11 @@ impl ScheduleProfile {
             name: "默认时间表".to_string(),
             periods,
             sound: SoundSlots::default(),
+            last_used: None,
+            archived: false,
+            muted: false,
+            mode: ScheduleMode::Daily,
+            timezone: None,
         }
     }
 
@@ -155,47 +472,679 @@ impl ScheduleProfile {
             name: name.to_string(),
             periods: Vec::new(),
             sound: SoundSlots::default(),
+            last_used: None,
+            archived: false,
+            muted: false,
+            mode: ScheduleMode::Daily,
+            timezone: None,
         }
     }
 
+    /// 该时间表时区下的当前墙钟时刻；未设置或无法识别时区时为本地时间
+    pub fn zone_now(&self, now: &DateTime<Local>) -> NaiveDateTime {
+        match self.timezone.as_deref().map(parse_timezone) {
+            Some(Ok(tz)) => now.with_timezone(&tz).naive_local(),
+            _ => now.naive_local(),
+        }
+    }
+
+    /// 时区设置无效（名称无法识别或未启用 `schedule-timezone` 特性）时返回原因
+    pub fn timezone_error(&self) -> Option<String> {
+        parse_timezone(self.timezone.as_deref()?).err()
+    }
+
+    /// 把相对日出/日落的节点换算为 `now` 当天（按该时间表时区）的具体时间并重新排序；
+    /// 未设置位置或当天无法换算时写入 `SOLAR_UNRESOLVED`，该节点当天不触发。
+    /// 返回是否有节点时间发生变化
+    pub fn resolve_solar_times(
+        &mut self,
+        now: &DateTime<Local>,
+        location: Option<&GeoLocation>,
+    ) -> bool {
+        let date = self.zone_now(now).date();
+        let resolved: Vec<Option<String>> = self
+            .periods
+            .iter()
+            .map(|period| {
+                let solar = period.solar?;
+                let time = location.and_then(|location| {
+                    solar.resolve(date, location, |at| {
+                        self.zone_now(&at.with_timezone(&Local))
+                    })
+                });
+                Some(time.map_or_else(
+                    || SOLAR_UNRESOLVED.to_string(),
+                    |time| time.format("%H:%M:%S").to_string(),
+                ))
+            })
+            .collect();
+
+        let mut changed = false;
+        for (period, time) in self.periods.iter_mut().zip(resolved) {
+            if let Some(time) = time
+                && period.time != time
+            {
+                period.time = time;
+                changed = true;
+            }
+        }
+        if changed {
+            self.sort_periods();
+        }
+        changed
+    }
+
     pub fn sort_periods(&mut self) {
         self.periods.sort_by(|a, b| a.time.cmp(&b.time));
     }
 
+    /// 把下标为 `indices` 的节点整体推后 `minutes` 分钟（负数为提前），之后按时间重新排序。
+    /// 相对日出/日落的节点时间每天换算，不参与平移；任一节点会跨过午夜时返回 `None` 且不修改。
+    /// 成功时返回被平移的节点排序后的新下标
+    pub fn shift_indices(&mut self, indices: &[usize], minutes: i64) -> Option<Vec<usize>> {
+        let offset = TimeDelta::minutes(minutes);
+        let mut shifted = Vec::new();
+        for &index in indices {
+            let period = self.periods.get(index)?;
+            if period.solar.is_some() {
+                continue;
+            }
+            let Some(time) = period.naive_time() else {
+                continue;
+            };
+            let (moved, wrapped) = time.overflowing_add_signed(offset);
+            if wrapped != 0 {
+                return None;
+            }
+            shifted.push((index, moved.format("%H:%M:%S").to_string()));
+        }
+
+        let mut tagged: Vec<(Period, bool)> = std::mem::take(&mut self.periods)
+            .into_iter()
+            .map(|period| (period, false))
+            .collect();
+        for (index, time) in shifted {
+            tagged[index].0.time = time;
+            tagged[index].1 = true;
+        }
+        // 与 `sort_periods` 相同的稳定排序，同时记录平移过的节点排到了哪里
+        tagged.sort_by(|a, b| a.0.time.cmp(&b.0.time));
+        let new_indices = tagged
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, moved))| *moved)
+            .map(|(index, _)| index)
+            .collect();
+        self.periods = tagged.into_iter().map(|(period, _)| period).collect();
+        Some(new_indices)
+    }
+
+    /// 追加一节课：`start` 开始、`class_mins` 分钟后结束，并在 `break_mins` 分钟课间后
+    /// 追加下一节的开始节点，节点名为“{label_prefix}{lesson}节开始/结束”。
+    /// 已存在相同时间与类型的节点时不重复添加（便于连续追加）；跨过午夜时返回 `None` 且不修改。
+    /// 成功时返回下一节的开始时间。
+    pub fn append_class_block(
+        &mut self,
+        start: NaiveTime,
+        class_mins: u32,
+        break_mins: u32,
+        label_prefix: &str,
+        lesson: u32,
+    ) -> Option<NaiveTime> {
+        let end = add_minutes_same_day(start, class_mins)?;
+        let next_start = add_minutes_same_day(end, break_mins)?;
+
+        for (time, kind, name) in [
+            (
+                start,
+                PeriodKind::Start,
+                format!("{label_prefix}{lesson}节开始"),
+            ),
+            (
+                end,
+                PeriodKind::End,
+                format!("{label_prefix}{lesson}节结束"),
+            ),
+            (
+                next_start,
+                PeriodKind::Start,
+                format!("{label_prefix}{}节开始", lesson + 1),
+            ),
+        ] {
+            let exists = self
+                .periods
+                .iter()
+                .any(|period| period.kind == kind && period.naive_time() == Some(time));
+            if !exists {
+                let time = time.format("%H:%M:%S").to_string();
+                self.periods.push(Period::new(&time, kind, &name));
+            }
+        }
+
+        self.sort_periods();
+        Some(next_start)
+    }
+
+    /// “添加节点”对话框的预填值：按最后一个节点推算下一个节点。
+    /// 最后是开始节点时建议 `class_mins` 后的“第N节结束”，否则建议 `break_mins` 后的下一节开始；
+    /// 时间表为空时建议 08:00 的第1节开始，推算跨过午夜时沿用最后节点的时间。
+    pub fn suggest_next_period(
+        &self,
+        class_mins: u32,
+        break_mins: u32,
+    ) -> (NaiveTime, PeriodKind, String) {
+        let Some((last_time, last_kind)) = self
+            .periods
+            .iter()
+            .filter(|period| period.kind.fires())
+            .filter_map(|period| Some((period.naive_time()?, period.kind)))
+            .max_by_key(|(time, _)| *time)
+        else {
+            return (
+                NaiveTime::from_hms_opt(8, 0, 0).unwrap_or_default(),
+                PeriodKind::Start,
+                "第1节开始".to_string(),
+            );
+        };
+
+        let lessons = self
+            .periods
+            .iter()
+            .filter(|period| period.kind == PeriodKind::Start)
+            .count();
+        let (kind, minutes, name) = if last_kind == PeriodKind::Start {
+            (
+                PeriodKind::End,
+                class_mins,
+                format!("第{}节结束", lessons.max(1)),
+            )
+        } else {
+            (
+                PeriodKind::Start,
+                break_mins,
+                format!("第{}节开始", lessons + 1),
+            )
+        };
+        let time = add_minutes_same_day(last_time, minutes).unwrap_or(last_time);
+        (time, kind, name)
+    }
+
     pub fn next_period(&self, now: &NaiveTime) -> Option<&Period> {
         self.periods
             .iter()
-            .filter(|period| period.enabled)
+            .filter(|period| period.enabled && period.kind.fires())
             .filter_map(|period| period.naive_time().map(|time| (time, period)))
             .filter(|(time, _)| *time > *now)
             .min_by_key(|(time, _)| *time)
             .map(|(_, period)| period)
     }
 
-    pub fn current_status(&self, now: &NaiveTime) -> String {
-        let mut passed: Vec<&Period> = self
+    /// 倒计时目标：(节点名称, 距今秒数)。
+    /// 今天已无后续节点且 `rollover` 为 `true` 时（仅作息模式），返回明天最早的启用节点，
+    /// 名称前加“明日”，秒数跨过午夜计算。
+    pub fn next_countdown(&self, now: &NaiveTime, rollover: bool) -> Option<(String, i64)> {
+        if let Some(period) = self.next_period(now)
+            && let Some(time) = period.naive_time()
+        {
+            return Some((period.name.clone(), (time - *now).num_seconds()));
+        }
+        if !rollover || self.mode != ScheduleMode::Daily {
+            return None;
+        }
+
+        let (time, period) = self
+            .periods
+            .iter()
+            .filter(|period| period.enabled && period.kind.fires())
+            .filter_map(|period| period.naive_time().map(|time| (time, period)))
+            .min_by_key(|(time, _)| *time)?;
+        let until_midnight = 86_400 - i64::from(now.num_seconds_from_midnight());
+        let secs = until_midnight + i64::from(time.num_seconds_from_midnight());
+        Some((format!("明日 {}", period.name), secs))
+    }
+
+    /// 当前时段（上一个已过的响铃节点到下一个响铃节点）已过去的比例 0.0~1.0；
+    /// 尚未到第一个节点或已无后续节点时返回 `None`
+    pub fn period_progress(&self, now: &NaiveTime) -> Option<f32> {
+        let next = self.next_period(now)?.naive_time()?;
+        let previous = self
+            .periods
+            .iter()
+            .filter(|period| period.enabled && period.kind.fires())
+            .filter_map(|period| period.naive_time())
+            .filter(|time| *time <= *now)
+            .max()?;
+        let total = (next - previous).num_seconds();
+        let elapsed = (*now - previous).num_seconds();
+        Some(elapsed as f32 / total as f32)
+    }
+
+    /// 按开始节点与其后第一个结束节点配对（与时间轴相同），返回 `now` 所处的上课时段，
+    /// 或两节之间的课间；第一节之前、最后一节之后返回 `None`
+    pub fn current_session(&self, now: &NaiveTime) -> Option<Session> {
+        let mut points: Vec<(NaiveTime, &Period)> = self
             .periods
             .iter()
             .filter(|period| period.enabled)
-            .filter(|period| {
-                period
-                    .naive_time()
-                    .map(|time| time <= *now)
-                    .unwrap_or(false)
+            .filter_map(|period| Some((period.naive_time()?, period)))
+            .collect();
+        points.sort_by_key(|(time, _)| *time);
+
+        let mut sessions: Vec<(NaiveTime, NaiveTime, &str)> = Vec::new();
+        let mut open: Option<(NaiveTime, &Period)> = None;
+        for (time, period) in points {
+            match period.kind {
+                PeriodKind::Start => open = Some((time, period)),
+                PeriodKind::End => {
+                    if let Some((start, start_period)) = open.take() {
+                        sessions.push((start, time, &start_period.name));
+                    }
+                }
+                PeriodKind::Prepare | PeriodKind::Marker => {}
+            }
+        }
+
+        if let Some((start, end, name)) = sessions
+            .iter()
+            .find(|(start, end, _)| *start <= *now && *now < *end)
+        {
+            return Some(Session {
+                name: name.to_string(),
+                start: *start,
+                end: *end,
+                in_class: true,
+            });
+        }
+        sessions.windows(2).find_map(|pair| {
+            let ((_, break_start, _), (next_start, _, next_name)) = (pair[0], pair[1]);
+            (break_start <= *now && *now < next_start).then(|| Session {
+                name: next_name.to_string(),
+                start: break_start,
+                end: next_start,
+                in_class: false,
             })
+        })
+    }
+
+    /// 启用的结束节点中最晚的时间（放学铃触发点）
+    pub fn last_end_time(&self) -> Option<NaiveTime> {
+        self.periods
+            .iter()
+            .filter(|period| period.enabled && period.kind == PeriodKind::End)
+            .filter_map(|period| period.naive_time())
+            .max()
+    }
+
+    /// 某节点触发时使用的音效：最晚的结束节点（同一时刻有多个时均算）换用放学铃
+    pub fn sound_for(&self, period: &Period) -> SoundSlots {
+        let mut slots = self.sound.clone();
+        if self.mode == ScheduleMode::Daily
+            && period.kind == PeriodKind::End
+            && period.naive_time().is_some()
+            && period.naive_time() == self.last_end_time()
+            && let Some(day_end) = slots.day_end.take()
+        {
+            slots.end = day_end;
+        }
+        slots
+    }
+
+    /// 检查时间表，返回提示性的警告（不阻止保存）：
+    /// 结束节点之前没有与之配对的开始节点；
+    /// 相邻两个会响铃的启用节点间隔小于 `min_gap_minutes` 分钟（0 为不检查）
+    pub fn validate(&self, min_gap_minutes: u32) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let mut ringing: Vec<(NaiveTime, &Period)> = self
+            .periods
+            .iter()
+            .filter(|period| period.enabled && period.kind.fires())
+            .filter_map(|period| Some((period.naive_time()?, period)))
             .collect();
+        ringing.sort_by_key(|(time, _)| *time);
+        let show = |time: NaiveTime| {
+            let format = if time.second() == 0 {
+                "%H:%M"
+            } else {
+                "%H:%M:%S"
+            };
+            time.format(format).to_string()
+        };
+
+        // 与时间轴相同的配对规则：开始节点与其后第一个结束节点配对
+        let mut open = false;
+        for (time, period) in &ringing {
+            match period.kind {
+                PeriodKind::Start => open = true,
+                PeriodKind::End if !open => warnings.push(format!(
+                    "结束节点“{}” {} 之前没有对应的开始节点",
+                    period.name,
+                    show(*time)
+                )),
+                PeriodKind::End => open = false,
+                PeriodKind::Prepare | PeriodKind::Marker => {}
+            }
+        }
+
+        if min_gap_minutes == 0 {
+            return warnings;
+        }
+        let min_gap = TimeDelta::minutes(i64::from(min_gap_minutes));
+        for pair in ringing.windows(2) {
+            let ((prev_time, prev), (time, period)) = (pair[0], pair[1]);
+            let gap = time - prev_time;
+            if gap < min_gap {
+                warnings.push(format!(
+                    "“{}” {} → “{}” {} 仅间隔 {}（要求至少 {} 分钟）",
+                    prev.name,
+                    show(prev_time),
+                    period.name,
+                    show(time),
+                    format_gap(gap),
+                    min_gap_minutes
+                ));
+            }
+        }
+        warnings
+    }
+
+    /// 节点统计：(总数, 启用数, 最早~最晚时间)；没有可解析时间的节点时跨度为 `None`
+    pub fn stats(&self) -> (usize, usize, Option<(NaiveTime, NaiveTime)>) {
+        let enabled = self.periods.iter().filter(|period| period.enabled).count();
+        let span = self
+            .periods
+            .iter()
+            .filter_map(|period| period.naive_time())
+            .fold(None, |span: Option<(NaiveTime, NaiveTime)>, time| {
+                Some(match span {
+                    Some((min, max)) => (min.min(time), max.max(time)),
+                    None => (time, time),
+                })
+            });
+        (self.periods.len(), enabled, span)
+    }
+
+    /// 今日剩余（晚于 `now` 的）启用节点数
+    pub fn remaining_count(&self, now: &NaiveTime) -> usize {
+        self.periods
+            .iter()
+            .filter(|period| period.enabled && period.kind.fires())
+            .filter_map(|period| period.naive_time())
+            .filter(|time| *time > *now)
+            .count()
+    }
+
+    /// 今日节点是否已全部结束（没有启用节点的时间表不算“结束”）
+    pub fn is_day_over(&self, now: &NaiveTime) -> bool {
+        self.mode == ScheduleMode::Daily
+            && self
+                .periods
+                .iter()
+                .any(|period| period.enabled && period.kind.fires())
+            && self.remaining_count(now) == 0
+    }
+
+    /// 最近一个已过的启用节点名称；尚未到第一个节点时返回 `idle_label`
+    pub fn current_status(&self, now: &NaiveTime, idle_label: &str) -> String {
+        self.current_period_index(now)
+            .map(|index| self.periods[index].name.clone())
+            .unwrap_or_else(|| idle_label.to_string())
+    }
+
+    /// 当前所处节点（时间不晚于 `now` 的最后一个启用节点）在 `periods` 中的下标
+    pub fn current_period_index(&self, now: &NaiveTime) -> Option<usize> {
+        self.periods.iter().rposition(|period| {
+            period.enabled && period.naive_time().is_some_and(|time| time <= *now)
+        })
+    }
+}
+
+/// 时间表用途
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScheduleMode {
+    /// 每日作息：节点每天重复响铃，最后一个结束节点视为放学
+    #[default]
+    Daily,
+    /// 计时器：节点只响一次，响过后自动从时间表移除
+    Timer,
+}
+
+impl ScheduleMode {
+    pub const ALL: [ScheduleMode; 2] = [ScheduleMode::Daily, ScheduleMode::Timer];
+
+    pub fn label(&self) -> &str {
+        match self {
+            ScheduleMode::Daily => "作息",
+            ScheduleMode::Timer => "计时器",
+        }
+    }
+}
+
+/// 时间表下拉列表的展示顺序（不影响 `schedules` 的存储顺序）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScheduleSort {
+    #[default]
+    Manual,
+    Name,
+    Recent,
+}
+
+impl ScheduleSort {
+    pub const ALL: [ScheduleSort; 3] = [
+        ScheduleSort::Manual,
+        ScheduleSort::Name,
+        ScheduleSort::Recent,
+    ];
+
+    pub fn label(&self) -> &str {
+        match self {
+            ScheduleSort::Manual => "创建顺序",
+            ScheduleSort::Name => "按名称",
+            ScheduleSort::Recent => "最近使用",
+        }
+    }
+}
+
+/// 界面密度：紧凑模式缩小控件间距与节点行高，小屏幕上能显示更多行
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Density {
+    #[default]
+    Comfortable,
+    Compact,
+}
+
+impl Density {
+    pub const ALL: [Density; 2] = [Density::Comfortable, Density::Compact];
+
+    pub fn label(&self) -> &str {
+        match self {
+            Density::Comfortable => "舒适",
+            Density::Compact => "紧凑",
+        }
+    }
+}
+
+/// 点击窗口关闭按钮时的行为
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CloseAction {
+    /// 弹出确认框，由用户选择最小化或退出
+    #[default]
+    Ask,
+    Exit,
+    /// 最小化到托盘（托盘不可用时按退出处理）
+    Minimize,
+}
+
+impl CloseAction {
+    pub const ALL: [CloseAction; 3] = [CloseAction::Ask, CloseAction::Exit, CloseAction::Minimize];
+
+    pub fn label(&self) -> &str {
+        match self {
+            CloseAction::Ask => "每次询问",
+            CloseAction::Exit => "退出程序",
+            CloseAction::Minimize => "最小化到托盘",
+        }
+    }
+}
+
+/// 点击托盘图标恢复主窗口的方式（托盘菜单始终可用）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TrayActivation {
+    /// 单击或双击都恢复
+    #[default]
+    SingleClick,
+    /// 仅双击恢复，避免误触
+    DoubleClick,
+}
+
+impl TrayActivation {
+    pub const ALL: [TrayActivation; 2] = [TrayActivation::SingleClick, TrayActivation::DoubleClick];
+
+    pub fn label(&self) -> &str {
+        match self {
+            TrayActivation::SingleClick => "单击恢复",
+            TrayActivation::DoubleClick => "双击恢复",
+        }
+    }
+}
+
+/// 界面状态文字，可按学校习惯自定义（如把“待机”改为“课间休息”）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Labels {
+    /// 当天第一个节点之前的状态
+    pub idle: String,
+    /// 当天已无后续节点
+    pub no_next: String,
+    /// 没有任何时间表
+    pub no_schedule: String,
+    /// 当天节点全部结束后的提示
+    pub day_over: String,
+}
+
+impl Default for Labels {
+    fn default() -> Self {
+        Self {
+            idle: "待机".to_string(),
+            no_next: "今日无后续节点".to_string(),
+            no_schedule: "无活动时间表".to_string(),
+            day_over: "今日课程已结束".to_string(),
+        }
+    }
+}
 
-        passed
-            .pop()
-            .map(|period| period.name.clone())
-            .unwrap_or_else(|| "待机".to_string())
+/// 视觉提醒（无障碍）：触发时窗口着色闪烁并闪动任务栏按钮，可与铃声同时使用或代替铃声
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VisualAlert {
+    pub enabled: bool,
+    /// 闪烁颜色（sRGB）
+    pub color: [u8; 3],
+    /// 闪烁持续时长（毫秒）
+    pub duration_ms: u64,
+    /// 只做视觉提醒，不响铃
+    pub silent: bool,
+}
+
+impl Default for VisualAlert {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            color: [255, 196, 0],
+            duration_ms: 1500,
+            silent: false,
+        }
     }
 }
 
+/// 界面状态（重启后恢复上次打开的窗口与视图），仅尽力而为：解析失败时使用默认值
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    pub show_schedule_window: bool,
+    pub show_new_schedule_window: bool,
+    pub show_sound_window: bool,
+    pub show_settings_window: bool,
+    pub show_oneshot_window: bool,
+    pub show_class_block_window: bool,
+    pub timeline_view: bool,
+}
+
+/// 容错反序列化：字段内容损坏时回退默认值，不影响其余配置加载
+fn deserialize_lenient<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::de::DeserializeOwned + Default,
+{
+    let value = toml::Value::deserialize(deserializer)?;
+    Ok(value.try_into().unwrap_or_else(|e| {
+        log::warn!("界面状态解析失败，已忽略: {}", e);
+        T::default()
+    }))
+}
+
 fn default_autostart() -> bool {
     true
 }
 
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_confirm_on_close() -> bool {
+    true
+}
+
+fn default_restore_windows() -> bool {
+    true
+}
+
+fn default_show_day_end_banner() -> bool {
+    true
+}
+
+fn default_follow_current_period() -> bool {
+    true
+}
+
+fn default_sound_cooldown_ms() -> u64 {
+    1500
+}
+
+fn default_trigger_tolerance_secs() -> u32 {
+    5
+}
+
+fn default_startup_grace_secs() -> u32 {
+    5
+}
+
+/// 默认只收录会议期间才存在的进程；Teams、腾讯会议等常驻后台的客户端靠通话麦克风检测识别
+pub fn default_meeting_processes() -> Vec<String> {
+    [
+        "cpthost.exe", // Zoom 会议窗口
+        "atmgr.exe",   // Webex 会议管理器
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
+fn default_backup_count() -> u8 {
+    3
+}
+
+fn default_ui_scale() -> f32 {
+    1.0
+}
+
+/// 界面缩放范围
+pub const UI_SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.8..=2.0;
+
+/// 旧版本配置中没有该字段，视为已完成引导
+fn default_onboarded() -> bool {
+    true
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     pub active_schedule_id: Option<u64>,
@@ -203,6 +1152,108 @@ pub struct AppConfig {
     pub schedules: Vec<ScheduleProfile>,
     #[serde(default = "default_autostart")]
     pub autostart: bool,
+    /// 提醒是否启用（暂停状态跨重启保留）
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 时间输入使用时/分/秒数字微调，而非自由文本
+    #[serde(default)]
+    pub time_spinner: bool,
+    /// 检测到会议/通话进行中时跳过铃声（仍发送通知，仅 Windows）
+    #[serde(default)]
+    pub suppress_during_calls: bool,
+    /// 视为“正在开会”的进程名（不区分大小写）
+    #[serde(default = "default_meeting_processes")]
+    pub meeting_processes: Vec<String>,
+    /// 锁屏期间自动暂停提醒，解锁后恢复（不改变手动暂停状态，仅 Windows）
+    #[serde(default)]
+    pub pause_when_locked: bool,
+    #[serde(default)]
+    pub schedule_sort: ScheduleSort,
+    /// 两次铃声的最小间隔：间隔内相同音效跳过、不同音效排队播放
+    #[serde(default = "default_sound_cooldown_ms")]
+    pub sound_cooldown_ms: u64,
+    /// 节点时间过后多少秒内仍补响（时钟校正跳过节点时），同一节点当天只响一次
+    #[serde(default = "default_trigger_tolerance_secs")]
+    pub trigger_tolerance_secs: u32,
+    /// 启动后多少秒内不响铃（开机自启时避免刚登录就响）
+    #[serde(default = "default_startup_grace_secs")]
+    pub startup_grace_secs: u32,
+    /// “一天”从几点开始（0–23）：防重复记录在该时刻而非午夜清空，
+    /// 有跨午夜晚自习时，午夜后的节点仍算作前一天
+    #[serde(default)]
+    pub day_start_hour: u32,
+    /// 铃声播放期间压低其他程序音量（仅 Windows）
+    #[serde(default)]
+    pub duck_others: bool,
+    /// 启动时及定期检查系统音量，静音或过低时在状态栏提示（仅 Windows）
+    #[serde(default)]
+    pub check_system_volume: bool,
+    /// 任务栏按钮上显示暂停状态与当前时段进度（仅 Windows）
+    #[serde(default)]
+    pub taskbar_progress: bool,
+    /// 整点报时音效（与时间表无关，每小时整点响一次），`None` 为关闭
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hourly_chime: Option<SoundSource>,
+    /// 通过本地 socket（Unix）/ 命名管道（Windows）推送触发事件，重启后生效
+    #[serde(default)]
+    pub event_feed: bool,
+    #[serde(default)]
+    pub close_action: CloseAction,
+    #[serde(default)]
+    pub tray_activation: TrayActivation,
+    /// 自定义托盘图标图片（PNG/ICO），为空或读取失败时使用内嵌图标
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tray_icon_path: Option<String>,
+    /// 退出程序前是否二次确认（关闭按钮设为“退出程序”或从托盘菜单退出时生效）
+    #[serde(default = "default_confirm_on_close")]
+    pub confirm_on_close: bool,
+    /// 今日已无后续节点时，倒计时指向明天的第一个节点
+    #[serde(default)]
+    pub countdown_rollover: bool,
+    /// 当前节点变化时节点列表自动滚动到该行
+    #[serde(default = "default_follow_current_period")]
+    pub follow_current_period: bool,
+    /// 今日节点全部结束后在主界面显示“今日课程已结束”
+    #[serde(default = "default_show_day_end_banner")]
+    pub show_day_end_banner: bool,
+    #[serde(default)]
+    pub labels: Labels,
+    /// 输入时间时吸附到的分钟网格（0 = 关闭，5 = 吸附到 5 分钟整点）
+    #[serde(default)]
+    pub snap_minutes: u32,
+    /// 相邻响铃节点的最小间隔（分钟），间隔过小时在节点列表上方提示；0 为不检查
+    #[serde(default)]
+    pub min_gap_minutes: u32,
+    /// 保存配置时保留的 `.bak.N` 备份数量（0 = 不备份，最多 `MAX_BACKUP_COUNT` 份）
+    #[serde(default = "default_backup_count")]
+    pub backup_count: u8,
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    pub ui_state: UiState,
+    /// 重启后重新打开上次打开的窗口；关闭时窗口状态只在本次运行内保留
+    #[serde(default = "default_restore_windows")]
+    pub restore_windows: bool,
+    /// 最近选用的本地音效（最新在前，最多 `MAX_RECENT_SOUNDS` 个）
+    #[serde(default)]
+    pub recent_sounds: Vec<String>,
+    /// 界面缩放比例（`UI_SCALE_RANGE`），低视力用户可放大文字
+    #[serde(default = "default_ui_scale")]
+    pub ui_scale: f32,
+    #[serde(default)]
+    pub density: Density,
+    /// 大屏模式使用的显示器（`monitor::monitors()` 中的序号）；`None` 表示留在当前显示器
+    #[serde(default)]
+    pub overlay_monitor: Option<usize>,
+    /// 关闭界面动画
+    #[serde(default)]
+    pub reduce_motion: bool,
+    #[serde(default)]
+    pub visual_alert: VisualAlert,
+    /// 相对日出/日落的节点使用的地理位置
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<GeoLocation>,
+    /// 是否已看过首次启动引导
+    #[serde(default = "default_onboarded")]
+    pub onboarded: bool,
 }
 
 impl Default for AppConfig {
@@ -219,9 +1270,59 @@ impl AppConfig {
             next_schedule_id: id + 1,
             schedules: vec![ScheduleProfile::default_preset(id)],
             autostart: true,
+            enabled: true,
+            time_spinner: false,
+            suppress_during_calls: false,
+            meeting_processes: default_meeting_processes(),
+            pause_when_locked: false,
+            schedule_sort: ScheduleSort::Manual,
+            sound_cooldown_ms: default_sound_cooldown_ms(),
+            trigger_tolerance_secs: default_trigger_tolerance_secs(),
+            startup_grace_secs: default_startup_grace_secs(),
+            day_start_hour: 0,
+            duck_others: false,
+            check_system_volume: false,
+            taskbar_progress: false,
+            hourly_chime: None,
+            event_feed: false,
+            close_action: CloseAction::Ask,
+            tray_activation: TrayActivation::SingleClick,
+            tray_icon_path: None,
+            confirm_on_close: true,
+            countdown_rollover: false,
+            follow_current_period: true,
+            show_day_end_banner: true,
+            labels: Labels::default(),
+            snap_minutes: 0,
+            min_gap_minutes: 0,
+            backup_count: default_backup_count(),
+            ui_state: UiState::default(),
+            restore_windows: true,
+            recent_sounds: Vec::new(),
+            ui_scale: default_ui_scale(),
+            density: Density::Comfortable,
+            overlay_monitor: None,
+            reduce_motion: false,
+            visual_alert: VisualAlert::default(),
+            location: None,
+            onboarded: false,
         }
     }
 
+    /// 所有时间表中相对日出/日落的节点按当天日期重新换算，返回是否有变化
+    pub fn resolve_solar_times(&mut self, now: &DateTime<Local>) -> bool {
+        let location = self.location;
+        self.schedules.iter_mut().fold(false, |changed, schedule| {
+            schedule.resolve_solar_times(now, location.as_ref()) | changed
+        })
+    }
+
+    /// 移除文件已不存在的最近音效
+    pub fn prune_recent_sounds(&mut self) {
+        self.recent_sounds
+            .retain(|path| std::path::Path::new(path).is_file());
+    }
+
     pub fn active_schedule(&self) -> Option<&ScheduleProfile> {
         let id = self.active_schedule_id?;
         self.schedules.iter().find(|schedule| schedule.id == id)
@@ -232,32 +1333,115 @@ impl AppConfig {
         self.schedules.iter_mut().find(|schedule| schedule.id == id)
     }
 
+    /// 实际驱动响铃的时间表：当前时间表被归档或单独暂停时不响铃
+    pub fn effective_schedule(&self) -> Option<&ScheduleProfile> {
+        self.active_schedule()
+            .filter(|schedule| !schedule.archived && !schedule.muted)
+    }
+
+    /// 自动选择当前时间表时的候选：优先未归档的，全部归档时退回第一个
+    fn fallback_schedule_id(&self) -> Option<u64> {
+        self.schedules
+            .iter()
+            .find(|schedule| !schedule.archived)
+            .or_else(|| self.schedules.first())
+            .map(|schedule| schedule.id)
+    }
+
     pub fn ensure_active_schedule(&mut self) {
         if self.active_schedule_id.is_some() && self.active_schedule().is_some() {
             return;
         }
 
-        self.active_schedule_id = self.schedules.first().map(|schedule| schedule.id);
+        self.active_schedule_id = self.fallback_schedule_id();
+    }
+
+    /// 修复手工编辑可能带来的不一致：没有时间表时补一个默认时间表，
+    /// `next_schedule_id` 不小于已有 id，当前时间表指向存在的时间表
+    pub fn repair(&mut self) {
+        if self.schedules.is_empty() {
+            let id = self.next_schedule_id.max(1);
+            self.schedules.push(ScheduleProfile::default_preset(id));
+        }
+        let max_id = self
+            .schedules
+            .iter()
+            .map(|schedule| schedule.id)
+            .max()
+            .unwrap_or(0);
+        self.next_schedule_id = self.next_schedule_id.max(max_id + 1);
+        self.ensure_active_schedule();
+    }
+
+    /// 导入时的“合并”：把 `other` 的时间表追加到末尾，并按本配置的 `next_schedule_id`
+    /// 重新分配 id 以免与已有时间表冲突；与已有时间表重名的加上“（导入）”后缀。
+    /// 其余设置与当前时间表保持不变。返回追加的时间表数
+    pub fn merge_from(&mut self, other: AppConfig) -> usize {
+        self.repair();
+        let added = other.schedules.len();
+        for mut schedule in other.schedules {
+            schedule.id = self.next_schedule_id;
+            self.next_schedule_id += 1;
+            schedule.name = self.unused_schedule_name(&schedule.name);
+            self.schedules.push(schedule);
+        }
+        added
+    }
+
+    /// `name` 未被占用时原样返回，否则依次尝试“name（导入）”“name（导入 2）”……
+    fn unused_schedule_name(&self, name: &str) -> String {
+        let taken = |candidate: &str| self.schedules.iter().any(|s| s.name == candidate);
+        if !taken(name) {
+            return name.to_string();
+        }
+        (1..)
+            .map(|n| match n {
+                1 => format!("{name}（导入）"),
+                n => format!("{name}（导入 {n}）"),
+            })
+            .find(|candidate| !taken(candidate))
+            .unwrap_or_default()
     }
 
     pub fn create_empty_schedule(&mut self, name: String) -> u64 {
         let id = self.next_schedule_id;
         self.next_schedule_id += 1;
 
-        self.schedules.push(ScheduleProfile::empty(id, &name));
+        let mut schedule = ScheduleProfile::empty(id, &name);
+        schedule.last_used = Some(Local::now());
+        self.schedules.push(schedule);
         self.active_schedule_id = Some(id);
         id
     }
 
-    pub fn remove_active_schedule(&mut self) -> Option<ScheduleProfile> {
-        let active_id = self.active_schedule_id?;
+    /// 从指定时间表中移除一个与 `target` 时间、类型、名称相同的节点，返回是否移除
+    pub fn remove_period(&mut self, schedule_id: u64, target: &Period) -> bool {
+        let Some(schedule) = self
+            .schedules
+            .iter_mut()
+            .find(|schedule| schedule.id == schedule_id)
+        else {
+            return false;
+        };
+        let Some(index) = schedule.periods.iter().position(|period| {
+            period.time == target.time && period.kind == target.kind && period.name == target.name
+        }) else {
+            return false;
+        };
+        schedule.periods.remove(index);
+        true
+    }
+
+    pub fn remove_schedule(&mut self, id: u64) -> Option<ScheduleProfile> {
         let index = self
             .schedules
             .iter()
-            .position(|schedule| schedule.id == active_id)?;
+            .position(|schedule| schedule.id == id)?;
 
         let removed = self.schedules.remove(index);
-        self.active_schedule_id = self.schedules.first().map(|schedule| schedule.id);
+        if self.active_schedule_id == Some(id) {
+            self.active_schedule_id = self.fallback_schedule_id();
+        }
         Some(removed)
     }
 
@@ -269,33 +1453,631 @@ impl AppConfig {
         });
 
         self.ensure_active_schedule();
+        if let Some(schedule) = self.active_schedule_mut() {
+            schedule.last_used = Some(Local::now());
+        }
+    }
+
+    /// 按 `schedule_sort` 排序后的时间表视图，仅用于展示（已归档的始终排在最后）
+    pub fn sorted_schedules(&self) -> Vec<&ScheduleProfile> {
+        let mut sorted: Vec<&ScheduleProfile> = self.schedules.iter().collect();
+        match self.schedule_sort {
+            ScheduleSort::Manual => {}
+            ScheduleSort::Name => sorted.sort_by(|a, b| a.name.cmp(&b.name)),
+            // 从未使用过的排在最后（None < Some，逆序后落到末尾）
+            ScheduleSort::Recent => {
+                sorted.sort_by_key(|schedule| std::cmp::Reverse(schedule.last_used))
+            }
+        }
+        // 稳定排序，保持上面的相对顺序
+        sorted.sort_by_key(|schedule| schedule.archived);
+        sorted
     }
 }
 
+/// 最近音效列表的容量
+pub const MAX_RECENT_SOUNDS: usize = 8;
+
+/// 把本地音效记到最近列表最前面（去重并截断到 `MAX_RECENT_SOUNDS`）
+pub fn remember_recent_sound(recent: &mut Vec<String>, path: &str) {
+    if path.trim().is_empty() {
+        return;
+    }
+    recent.retain(|existing| existing != path);
+    recent.insert(0, path.to_string());
+    recent.truncate(MAX_RECENT_SOUNDS);
+}
+
+/// 宽松解析用户输入的时间
+/// - 支持 "HH:MM" / "HH:MM:SS"，全角冒号 "：" 与 "." 也可作分隔符
+/// - 支持纯数字："8" → 08:00，"830" / "0830" → 08:30
+/// - 超出范围（如 "24:00"、"08:60"）、负数或其他格式返回 None
+pub fn parse_flexible(input: &str) -> Option<NaiveTime> {
+    let input = input.trim().replace(['：', '.'], ":");
+    let parts: Vec<&str> = input.split(':').map(str::trim).collect();
+
+    // 只接受纯数字，拒绝 "+8"、"-1" 之类
+    let parse = |part: &str| -> Option<u32> {
+        if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        part.parse().ok()
+    };
+
+    let (h, m, s) = match parts.as_slice() {
+        [digits] => match digits.len() {
+            1 | 2 => (parse(digits)?, 0, 0),
+            3 | 4 => {
+                let value = parse(digits)?;
+                (value / 100, value % 100, 0)
+            }
+            _ => return None,
+        },
+        [h, m] => (parse(h)?, parse(m)?, 0),
+        [h, m, s] => (parse(h)?, parse(m)?, parse(s)?),
+        _ => return None,
+    };
+
+    NaiveTime::from_hms_opt(h, m, s)
+}
+
+/// 输入能否解析为合法时间（供界面实时校验）
+pub fn is_valid_time(input: &str) -> bool {
+    parse_flexible(input).is_some()
+}
+
 /// 将用户输入规范化为 HH:MM:SS 格式
 /// - 支持输入 "9:5:3" → "09:05:03"
 /// - 支持输入 "9:5" → "09:05:00"（补秒）
-/// - 如果格式无效返回 None
+/// - 其余写法见 `parse_flexible`，格式无效返回 None
 pub fn normalize_time_str(input: &str) -> Option<String> {
-    let parts: Vec<&str> = input.trim().split(':').collect();
-    match parts.len() {
-        2 => {
-            let h: u32 = parts[0].trim().parse().ok()?;
-            let m: u32 = parts[1].trim().parse().ok()?;
-            if h > 23 || m > 59 {
-                return None;
-            }
-            Some(format!("{:02}:{:02}:00", h, m))
+    parse_flexible(input).map(|time| time.format("%H:%M:%S").to_string())
+}
+
+/// 由时/分/秒合成 HH:MM:SS，允许分/秒越界进位或借位（如 秒=60 → 分+1）
+/// - 整体钳制在 00:00:00 ~ 23:59:59
+pub fn compose_time_str(hour: i64, minute: i64, second: i64) -> String {
+    let total = (hour * 3600 + minute * 60 + second).clamp(0, 86_399);
+    format!(
+        "{:02}:{:02}:{:02}",
+        total / 3600,
+        total % 3600 / 60,
+        total % 60
+    )
+}
+
+/// 批量编辑中停用节点的行前缀
+const DISABLED_LINE_PREFIX: char = '#';
+
+/// 批量编辑中静音节点的行前缀（位于停用前缀之后）
+const SILENT_LINE_PREFIX: char = '!';
+
+/// 批量编辑中名称与备注的分隔符
+const NOTE_SEPARATOR: &str = " // ";
+
+/// 节点转为批量编辑文本，每行 `HH:MM:SS 类型 名称[ // 备注]`，停用的节点以 `#` 开头，
+/// 静音的节点再以 `!` 开头（如 `# ! 08:00:00 开始 早读`）；相对日出/日落的节点时间写作 `日落-30`
+pub fn periods_to_text(periods: &[Period]) -> String {
+    periods
+        .iter()
+        .map(|period| {
+            let disabled = if period.enabled { "" } else { "# " };
+            let silent = if period.silent { "! " } else { "" };
+            let prefix = format!("{disabled}{silent}");
+            let note = period
+                .note
+                .as_ref()
+                .map(|note| format!("{NOTE_SEPARATOR}{note}"))
+                .unwrap_or_default();
+            let time = period
+                .solar
+                .map_or_else(|| period.time.clone(), |solar| solar.to_token());
+            format!(
+                "{prefix}{time} {} {}{note}",
+                period.kind.label(),
+                period.name
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 解析批量编辑文本（格式见 `periods_to_text`），空行忽略，结果按时间排序。
+/// 相对日出/日落的节点时间为 `SOLAR_UNRESOLVED`，需调用方再换算。
+/// 任一行无效时返回全部错误（“第 N 行: 原因”）。
+pub fn parse_periods_text(text: &str) -> Result<Vec<Period>, Vec<String>> {
+    let mut periods = Vec::new();
+    let mut errors = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
         }
-        3 => {
-            let h: u32 = parts[0].trim().parse().ok()?;
-            let m: u32 = parts[1].trim().parse().ok()?;
-            let s: u32 = parts[2].trim().parse().ok()?;
-            if h > 23 || m > 59 || s > 59 {
-                return None;
+        let (enabled, line) = match line.strip_prefix(DISABLED_LINE_PREFIX) {
+            Some(rest) => (false, rest.trim_start()),
+            None => (true, line),
+        };
+        let (silent, line) = match line.strip_prefix(SILENT_LINE_PREFIX) {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, line),
+        };
+
+        let (time, kind, name, note) = match table_separator(line) {
+            Some(separator) => split_table_row(line, separator),
+            None => {
+                let mut parts = line.splitn(3, char::is_whitespace);
+                let (time, kind, rest) = (
+                    parts.next().unwrap_or_default(),
+                    parts.next().unwrap_or_default(),
+                    parts.next().unwrap_or_default(),
+                );
+                let (name, note) = rest.split_once(NOTE_SEPARATOR.trim()).unwrap_or((rest, ""));
+                (time, kind, name.trim(), note)
             }
-            Some(format!("{:02}:{:02}:{:02}", h, m, s))
+        };
+        let line_no = index + 1;
+        let solar = SolarTime::parse_token(time);
+        let Some(time) = solar
+            .map(|_| SOLAR_UNRESOLVED.to_string())
+            .or_else(|| normalize_time_str(time))
+        else {
+            errors.push(format!(
+                "第 {line_no} 行: 时间格式错误（应为 HH:MM:SS，或相对日出日落如 日落-30）"
+            ));
+            continue;
+        };
+        let Some(kind) = PeriodKind::from_label(kind) else {
+            errors.push(format!("第 {line_no} 行: 类型应为 开始/结束/预备/标记"));
+            continue;
+        };
+        if name.is_empty() {
+            errors.push(format!("第 {line_no} 行: 缺少名称"));
+            continue;
+        }
+
+        let mut period = Period::new(&time, kind, name);
+        period.enabled = enabled;
+        period.silent = silent;
+        period.solar = solar;
+        period.set_note(note);
+        periods.push(period);
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    periods.sort_by(|a, b| a.time.cmp(&b.time));
+    Ok(periods)
+}
+
+/// 从 Word/Excel 复制的表格行以制表符分隔；CSV 以逗号分隔。
+/// 只有首个空白分隔的片段不是时间时才按逗号拆分，以免误拆名称或备注中的逗号
+fn table_separator(line: &str) -> Option<char> {
+    if line.contains('\t') {
+        return Some('\t');
+    }
+    let first = line.split_whitespace().next().unwrap_or_default();
+    let first_is_time =
+        normalize_time_str(first).is_some() || SolarTime::parse_token(first).is_some();
+    (line.contains(',') && !first_is_time).then_some(',')
+}
+
+/// 单元格是否明显是时间：相对日出/日落、带分隔符，或至少 3 位数字（"830"）。
+/// "1"、"12" 这类纯数字更可能是序号，只在整行找不到明显的时间时才当作整点
+fn looks_like_time(cell: &str) -> bool {
+    if SolarTime::parse_token(cell).is_some() {
+        return true;
+    }
+    let explicit = cell.contains([':', '：', '.']) || cell.chars().count() >= 3;
+    explicit && normalize_time_str(cell).is_some()
+}
+
+/// 拆分表格行为 (时间, 类型, 名称, 备注)：按内容识别时间列与类型列，列顺序不限；
+/// 其余非空列依次作为名称与备注（纯数字的序号列跳过）。识别不到的列返回空串，由调用方报告错误
+fn split_table_row(line: &str, separator: char) -> (&str, &str, &str, &str) {
+    let mut cells: Vec<&str> = line
+        .split(separator)
+        .map(|cell| cell.trim().trim_matches('"').trim())
+        .filter(|cell| !cell.is_empty())
+        .collect();
+    let mut take = |matches: &dyn Fn(&str) -> bool| {
+        cells
+            .iter()
+            .position(|cell| matches(cell))
+            .map(|index| cells.remove(index))
+            .unwrap_or_default()
+    };
+    let mut time = take(&looks_like_time);
+    if time.is_empty() {
+        time = take(&|cell| normalize_time_str(cell).is_some());
+    }
+    let kind = take(&|cell| PeriodKind::from_label(cell).is_some());
+    let is_index = |cell: &&str| cell.bytes().all(|b| b.is_ascii_digit());
+    if cells.iter().any(|cell| !is_index(cell)) {
+        cells.retain(|cell| !is_index(cell));
+    }
+    let name = cells.first().copied().unwrap_or_default();
+    let note = cells.get(1).copied().unwrap_or_default();
+    (time, kind, name, note)
+}
+
+/// 解析相对时间 "+N" / "+Nm"（N 分钟后），用于计时器模式
+pub fn parse_relative_minutes(input: &str) -> Option<u32> {
+    let rest = input.trim().strip_prefix('+')?;
+    let digits = rest.strip_suffix(['m', 'M']).unwrap_or(rest).trim();
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok().filter(|minutes| *minutes > 0)
+}
+
+/// 解析时间表的时区名（IANA 名称）
+#[cfg(feature = "schedule-timezone")]
+fn parse_timezone(name: &str) -> Result<chrono_tz::Tz, String> {
+    name.parse()
+        .map_err(|_| format!("无法识别的时区“{name}”，已按本地时间"))
+}
+
+#[cfg(not(feature = "schedule-timezone"))]
+fn parse_timezone(name: &str) -> Result<Local, String> {
+    Err(format!(
+        "未启用 schedule-timezone 特性，时区“{name}”无效，已按本地时间"
+    ))
+}
+
+/// 如“3 分钟”“2 分 30 秒”“0 秒”
+fn format_gap(gap: TimeDelta) -> String {
+    let (minutes, seconds) = (gap.num_minutes(), gap.num_seconds() % 60);
+    match (minutes, seconds) {
+        (0, seconds) => format!("{seconds} 秒"),
+        (minutes, 0) => format!("{minutes} 分钟"),
+        (minutes, seconds) => format!("{minutes} 分 {seconds} 秒"),
+    }
+}
+
+/// `time` 之后 `minutes` 分钟，跨过午夜时返回 `None`
+pub fn add_minutes_same_day(time: NaiveTime, minutes: u32) -> Option<NaiveTime> {
+    let (sum, wrapped) = time.overflowing_add_signed(TimeDelta::minutes(i64::from(minutes)));
+    (wrapped == 0).then_some(sum)
+}
+
+/// 规范化后按 `snap_minutes` 分钟网格四舍五入，秒归零
+/// - `snap_minutes` 为 0 时等同于 `normalize_time_str`
+/// - 舍入越过 24:00 时退回当天最后一个网格点
+pub fn normalize_time_snapped(input: &str, snap_minutes: u32) -> Option<String> {
+    let normalized = normalize_time_str(input)?;
+    if snap_minutes == 0 {
+        return Some(normalized);
+    }
+
+    let time = NaiveTime::parse_from_str(&normalized, "%H:%M:%S").ok()?;
+    let step = i64::from(snap_minutes) * 60;
+    let secs = i64::from(time.num_seconds_from_midnight());
+    let mut snapped = (secs + step / 2) / step * step;
+    if snapped >= 86_400 {
+        snapped -= step;
+    }
+    Some(compose_time_str(0, 0, snapped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_flexible_accepts_loose_input() {
+        let cases = [
+            ("8", "08:00:00"),
+            ("08", "08:00:00"),
+            ("830", "08:30:00"),
+            ("0830", "08:30:00"),
+            ("8.30", "08:30:00"),
+            ("8：30", "08:30:00"),
+            ("8：30：15", "08:30:15"),
+            (" 9:5 ", "09:05:00"),
+            ("9:5:3", "09:05:03"),
+            ("23:59:59", "23:59:59"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                normalize_time_str(input).as_deref(),
+                Some(expected),
+                "{input:?}"
+            );
+        }
+    }
+
+    /// 批量编辑文本能表达的字段
+    fn text_fields(
+        periods: &[Period],
+    ) -> Vec<(String, PeriodKind, String, bool, bool, Option<String>)> {
+        periods
+            .iter()
+            .map(|period| {
+                (
+                    period.time.clone(),
+                    period.kind,
+                    period.name.clone(),
+                    period.enabled,
+                    period.silent,
+                    period.note.clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn periods_text_round_trip() {
+        let mut periods = vec![
+            Period::new("07:55:00", PeriodKind::Prepare, "预备铃"),
+            Period::new("08:00:00", PeriodKind::Start, "第 1 节 语文"),
+            Period::new("08:45:00", PeriodKind::End, "第 1 节 语文"),
+            Period::new("12:00:00", PeriodKind::Marker, "午休"),
+            Period::new("13:30:00", PeriodKind::Start, "社团 活动"),
+        ];
+        periods[1].set_note("带课本");
+        periods[3].silent = true;
+        periods[4].enabled = false;
+        periods[4].silent = true;
+        periods[4].set_note("隔周");
+
+        let text = periods_to_text(&periods);
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[3].starts_with(SILENT_LINE_PREFIX));
+        assert!(lines[4].starts_with(DISABLED_LINE_PREFIX));
+        assert!(lines[4].contains(SILENT_LINE_PREFIX));
+        let parsed = parse_periods_text(&text).unwrap();
+        assert_eq!(text_fields(&parsed), text_fields(&periods));
+        assert_eq!(periods_to_text(&parsed), text);
+    }
+
+    #[test]
+    fn periods_text_keeps_solar_anchor() {
+        let mut periods = vec![
+            Period::new("08:00:00", PeriodKind::Start, "早读"),
+            Period::new(SOLAR_UNRESOLVED, PeriodKind::Marker, "关灯"),
+            Period::new("18:12:00", PeriodKind::Start, "晚自习"),
+        ];
+        periods[1].solar = Some(SolarTime {
+            event: SolarEvent::Sunrise,
+            offset_mins: 0,
+        });
+        periods[2].solar = Some(SolarTime {
+            event: SolarEvent::Sunset,
+            offset_mins: -30,
+        });
+
+        let text = periods_to_text(&periods);
+        assert!(!text.contains(SOLAR_UNRESOLVED));
+        assert!(text.contains("日出 标记 关灯"));
+        assert!(text.contains("日落-30 开始 晚自习"));
+
+        let parsed = parse_periods_text(&text).unwrap();
+        let solar: Vec<_> = parsed.iter().map(|period| period.solar).collect();
+        assert_eq!(solar.iter().filter(|solar| solar.is_some()).count(), 2);
+        assert!(solar.contains(&periods[1].solar));
+        assert!(solar.contains(&periods[2].solar));
+        assert!(
+            parsed
+                .iter()
+                .filter(|period| period.solar.is_some())
+                .all(|period| period.time == SOLAR_UNRESOLVED)
+        );
+
+        // 表格粘贴同样识别，偏移可写正数
+        let parsed = parse_periods_text("1,日出+15,标记,升旗").unwrap();
+        assert_eq!(
+            parsed[0].solar,
+            Some(SolarTime {
+                event: SolarEvent::Sunrise,
+                offset_mins: 15,
+            })
+        );
+        assert_eq!(parsed[0].name, "升旗");
+        assert!(parse_periods_text("日落30 开始 x").is_err());
+    }
+
+    #[test]
+    fn periods_text_reports_every_bad_line() {
+        let errors =
+            parse_periods_text("08:00 开始 第1节\n25:00 开始 x\n\n08:45 下课 x\n09:00 结束")
+                .unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0].starts_with("第 2 行"));
+        assert!(errors[1].starts_with("第 4 行"));
+        assert!(errors[2].starts_with("第 5 行"));
+    }
+
+    #[test]
+    fn status_and_next_period_follow_the_clock() {
+        let mut schedule = ScheduleProfile::default_preset(1);
+        let time = |value: &str| parse_flexible(value).unwrap();
+
+        assert_eq!(schedule.current_status(&time("07:00"), "未开始"), "未开始");
+        assert_eq!(
+            schedule.next_period(&time("07:00")).unwrap().name,
+            "第1节开始"
+        );
+        // 恰好到点即进入该节点
+        assert_eq!(
+            schedule.current_status(&time("08:00"), "未开始"),
+            "第1节开始"
+        );
+        assert_eq!(
+            schedule.next_period(&time("08:00")).unwrap().name,
+            "第1节结束"
+        );
+        assert_eq!(
+            schedule.current_status(&time("08:45"), "未开始"),
+            "第1节结束"
+        );
+        assert!(schedule.next_period(&time("23:59:59")).is_none());
+
+        // 停用的节点既不算当前状态，也不是下一个节点
+        schedule.periods[1].enabled = false;
+        assert_eq!(
+            schedule.current_status(&time("08:50"), "未开始"),
+            "第1节开始"
+        );
+        assert_ne!(
+            schedule.next_period(&time("08:30")).unwrap().name,
+            "第1节结束"
+        );
+    }
+
+    #[test]
+    fn table_rows_prefer_explicit_time_over_index_column() {
+        let parsed = parse_periods_text("1\t08:00\t开始\t第1节\n2\t0845\t结束\t第1节").unwrap();
+        assert_eq!(parsed[0].time, "08:00:00");
+        assert_eq!(parsed[0].kind, PeriodKind::Start);
+        assert_eq!(parsed[0].name, "第1节");
+        assert_eq!(parsed[1].time, "08:45:00");
+        assert_eq!(parsed[1].name, "第1节");
+
+        // 没有明显的时间列时，纯数字仍按整点解析
+        let parsed = parse_periods_text("8\t开始\t早读").unwrap();
+        assert_eq!(parsed[0].time, "08:00:00");
+        assert_eq!(parsed[0].name, "早读");
+
+        // CSV 同样适用，列顺序不限
+        let parsed = parse_periods_text("3,午休,标记,12:00").unwrap();
+        assert_eq!(parsed[0].time, "12:00:00");
+        assert_eq!(parsed[0].name, "午休");
+    }
+
+    #[test]
+    fn compose_time_carries_borrows_and_clamps() {
+        for ((hour, minute, second), expected) in [
+            ((8, 5, 3), "08:05:03"),
+            ((0, 0, 60), "00:01:00"),
+            ((0, 60, 0), "01:00:00"),
+            ((0, 59, 60), "01:00:00"),
+            ((1, -1, 0), "00:59:00"),
+            ((1, 0, -1), "00:59:59"),
+            ((23, 59, 60), "23:59:59"),
+            ((23, 60, 0), "23:59:59"),
+            ((0, 0, -1), "00:00:00"),
+            ((0, -1, 0), "00:00:00"),
+        ] {
+            assert_eq!(
+                compose_time_str(hour, minute, second),
+                expected,
+                "{hour}:{minute}:{second}"
+            );
+        }
+    }
+
+    #[test]
+    fn suggest_next_period_follows_the_last_period() {
+        let time = |value: &str| parse_flexible(value).unwrap();
+        let mut schedule = ScheduleProfile::empty(1, "测试");
+
+        // 空时间表：08:00 第1节开始
+        assert_eq!(
+            schedule.suggest_next_period(45, 10),
+            (time("08:00"), PeriodKind::Start, "第1节开始".to_string())
+        );
+
+        // 最后是开始节点：课长后同一节的结束
+        schedule.periods = vec![
+            Period::new("08:00:00", PeriodKind::Start, "第1节开始"),
+            Period::new("08:45:00", PeriodKind::End, "第1节结束"),
+            Period::new("08:55:00", PeriodKind::Start, "第2节开始"),
+        ];
+        assert_eq!(
+            schedule.suggest_next_period(45, 10),
+            (time("09:40"), PeriodKind::End, "第2节结束".to_string())
+        );
+
+        // 最后是结束节点：课间后的下一节开始；不响铃的标记节点不参与推算
+        schedule
+            .periods
+            .push(Period::new("09:40:00", PeriodKind::End, "第2节结束"));
+        schedule
+            .periods
+            .push(Period::new("12:00:00", PeriodKind::Marker, "午休"));
+        assert_eq!(
+            schedule.suggest_next_period(45, 10),
+            (time("09:50"), PeriodKind::Start, "第3节开始".to_string())
+        );
+
+        // 推算会跨过午夜时沿用最后节点的时间
+        schedule.periods = vec![Period::new("23:30:00", PeriodKind::Start, "晚自习")];
+        assert_eq!(
+            schedule.suggest_next_period(45, 10),
+            (time("23:30"), PeriodKind::End, "第1节结束".to_string())
+        );
+    }
+
+    fn config_with_schedules(names: &[&str]) -> AppConfig {
+        let mut config = AppConfig::default_config();
+        config.schedules = names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| ScheduleProfile::empty(index as u64 + 1, name))
+            .collect();
+        config.next_schedule_id = names.len() as u64 + 1;
+        config.active_schedule_id = Some(1);
+        config
+    }
+
+    #[test]
+    fn merge_reassigns_colliding_ids() {
+        let mut config = config_with_schedules(&["平日", "周末"]);
+        let mut other = config_with_schedules(&["考试", "假期"]);
+        // 导入文件里的 id 与现有的重复，且自身的 next_schedule_id 过小
+        other.next_schedule_id = 1;
+
+        assert_eq!(config.merge_from(other), 2);
+        let ids: Vec<u64> = config.schedules.iter().map(|s| s.id).collect();
+        assert_eq!(ids, [1, 2, 3, 4]);
+        assert_eq!(config.next_schedule_id, 5);
+        assert_eq!(config.active_schedule_id, Some(1));
+        assert_eq!(config.schedules[2].name, "考试");
+    }
+
+    #[test]
+    fn merge_renames_colliding_names() {
+        let mut config = config_with_schedules(&["平日", "平日（导入）"]);
+        let other = config_with_schedules(&["平日", "周末", "周末"]);
+
+        config.merge_from(other);
+        let names: Vec<&str> = config.schedules.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "平日",
+                "平日（导入）",
+                "平日（导入 2）",
+                "周末",
+                "周末（导入）"
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_flexible_rejects_invalid_input() {
+        let cases = [
+            "",
+            "24",
+            "24:00",
+            "08:60",
+            "08:00:60",
+            "2400",
+            "08300",
+            "+8",
+            "-1",
+            "8:",
+            ":30",
+            "8:30:00:00",
+            "8 30",
+            "八点",
+            "8.30.1.2",
+        ];
+        for input in cases {
+            assert_eq!(parse_flexible(input), None, "{input:?}");
+            assert!(!is_valid_time(input), "{input:?}");
         }
-        _ => None,
     }
 }
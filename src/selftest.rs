@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use crate::config::probe_config_writable;
+use crate::engine::{Engine, HEARTBEAT_STALE};
+use crate::notifier::{play_sound_for_period, show_notification};
+use crate::schedule::{AppConfig, PeriodKind, SoundSlots};
+
+/// 一项自检结果
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub name: &'static str,
+    /// `None` 表示当前平台不适用
+    pub passed: Option<bool>,
+    /// 结果说明；未通过时给出处理建议
+    pub detail: String,
+}
+
+impl CheckResult {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            passed: Some(true),
+            detail: detail.into(),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            passed: Some(false),
+            detail: detail.into(),
+        }
+    }
+
+    fn skipped(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            passed: None,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// 依次运行全部自检（会在配置目录写入并删除一个临时文件、试响一次铃声并发送一条测试通知），
+/// 耗时可能较长，应在后台线程调用
+pub fn run(engine: &Engine, config: &AppConfig) -> Vec<CheckResult> {
+    vec![
+        check_config_writable(),
+        check_audio(),
+        check_notification(),
+        check_engine(engine),
+        check_autostart(config),
+    ]
+}
+
+fn check_config_writable() -> CheckResult {
+    const NAME: &str = "配置可写";
+    match probe_config_writable() {
+        Ok(path) => CheckResult::pass(NAME, path.display().to_string()),
+        Err(e) => CheckResult::fail(
+            NAME,
+            format!("{e}；请检查目录权限，或在主界面使用“另存为”换个位置"),
+        ),
+    }
+}
+
+fn check_audio() -> CheckResult {
+    const NAME: &str = "音频设备与铃声";
+    let outcome = play_sound_for_period(
+        PeriodKind::Start,
+        &SoundSlots::default(),
+        Duration::ZERO,
+        false,
+    );
+    match (outcome.source, outcome.warning) {
+        (Some(source), None) => CheckResult::pass(NAME, format!("已试响 {source}，请确认能听到")),
+        (_, warning) => CheckResult::fail(
+            NAME,
+            format!(
+                "{}；请检查扬声器连接与系统默认输出设备",
+                warning.unwrap_or_default()
+            ),
+        ),
+    }
+}
+
+fn check_notification() -> CheckResult {
+    const NAME: &str = "桌面通知";
+    match show_notification("WC Notice 自检", "如果看到这条通知，说明通知正常") {
+        Ok(()) => CheckResult::pass(NAME, "已发送测试通知，请确认屏幕上出现"),
+        Err(e) => CheckResult::fail(NAME, format!("{e}；请在系统设置中允许本程序发送通知")),
+    }
+}
+
+fn check_engine(engine: &Engine) -> CheckResult {
+    const NAME: &str = "检测线程";
+    let age = engine.heartbeat_age();
+    if age <= HEARTBEAT_STALE {
+        CheckResult::pass(NAME, format!("{} 秒前仍在运行", age.as_secs()))
+    } else {
+        CheckResult::fail(NAME, format!("已 {} 秒无响应；请重启程序", age.as_secs()))
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn check_autostart(config: &AppConfig) -> CheckResult {
+    use winreg::RegKey;
+    use winreg::enums::HKEY_CURRENT_USER;
+
+    const NAME: &str = "开机自启";
+    let registered: Option<String> = RegKey::predef(HKEY_CURRENT_USER)
+        .open_subkey(r"Software\Microsoft\Windows\CurrentVersion\Run")
+        .and_then(|key| key.get_value("WcNotice"))
+        .ok();
+    let Ok(current) = std::env::current_exe() else {
+        return CheckResult::skipped(NAME, "无法获取程序路径");
+    };
+    let current = current.to_string_lossy();
+
+    match (config.autostart, registered) {
+        (true, Some(path)) if path.eq_ignore_ascii_case(&current) => {
+            CheckResult::pass(NAME, "已启用，指向当前程序")
+        }
+        (true, Some(path)) => CheckResult::fail(
+            NAME,
+            format!("注册表指向 {path}，与当前程序不一致；请在设置中关闭再打开“开机自动启动”"),
+        ),
+        (true, None) => CheckResult::fail(
+            NAME,
+            "已开启但注册表中没有启动项；请在设置中关闭再打开“开机自动启动”",
+        ),
+        (false, Some(_)) => CheckResult::fail(NAME, "已关闭但注册表中仍有启动项"),
+        (false, None) => CheckResult::pass(NAME, "未启用"),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn check_autostart(_config: &AppConfig) -> CheckResult {
+    CheckResult::skipped("开机自启", "当前平台不适用")
+}
@@ -0,0 +1,94 @@
+//! 日出日落时刻计算（日出方程，精度约 1 分钟），供“相对日出/日落”的节点每天换算为具体时间。
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// 2000-01-01 12:00 UTC 的儒略日
+const J2000: f64 = 2_451_545.0;
+/// 1970-01-01 00:00 UTC 的儒略日
+const UNIX_EPOCH_JD: f64 = 2_440_587.5;
+/// 地轴倾角（度）
+const OBLIQUITY: f64 = 23.4397;
+/// 日出日落时太阳中心的高度角：大气折射与太阳视半径（度）
+const HORIZON_ALTITUDE: f64 = -0.833;
+
+/// `date` 当天（按经度所在地的日期）日出与日落的 UTC 时刻；
+/// 极昼或极夜（太阳整天不落或不升）时返回 `None`
+pub fn sunrise_sunset(
+    date: NaiveDate,
+    latitude: f64,
+    longitude: f64,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let epoch = NaiveDate::from_ymd_opt(2000, 1, 1)?;
+    let days = (date - epoch).num_days() as f64;
+    // 平太阳正午（东经为正）
+    let mean_noon = days - longitude / 360.0;
+    let anomaly = (357.5291 + 0.985_600_28 * mean_noon).rem_euclid(360.0);
+    let m = anomaly.to_radians();
+    let center = 1.9148 * m.sin() + 0.0200 * (2.0 * m).sin() + 0.0003 * (3.0 * m).sin();
+    let ecliptic = (anomaly + center + 180.0 + 102.9372)
+        .rem_euclid(360.0)
+        .to_radians();
+    let transit = J2000 + mean_noon + 0.0053 * m.sin() - 0.0069 * (2.0 * ecliptic).sin();
+
+    let declination = (ecliptic.sin() * OBLIQUITY.to_radians().sin()).asin();
+    let phi = latitude.to_radians();
+    let cos_hour_angle = (HORIZON_ALTITUDE.to_radians().sin() - phi.sin() * declination.sin())
+        / (phi.cos() * declination.cos());
+    if !(-1.0..=1.0).contains(&cos_hour_angle) {
+        return None;
+    }
+    let half_day = cos_hour_angle.acos().to_degrees() / 360.0;
+    Some((
+        julian_to_utc(transit - half_day)?,
+        julian_to_utc(transit + half_day)?,
+    ))
+}
+
+fn julian_to_utc(julian: f64) -> Option<DateTime<Utc>> {
+    let seconds = ((julian - UNIX_EPOCH_JD) * 86_400.0).round() as i64;
+    DateTime::from_timestamp(seconds, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 与天文年历对比，允许 2 分钟误差
+    fn assert_near(actual: DateTime<Utc>, expected: &str) {
+        let expected = DateTime::parse_from_rfc3339(expected).unwrap();
+        let diff = (actual - expected.with_timezone(&Utc)).num_seconds().abs();
+        assert!(diff <= 120, "{actual} 与 {expected} 相差 {diff} 秒");
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn beijing_solstices() {
+        let (rise, set) = sunrise_sunset(date("2026-06-21"), 39.9042, 116.4074).unwrap();
+        assert_near(rise, "2026-06-21T04:46:00+08:00");
+        assert_near(set, "2026-06-21T19:46:00+08:00");
+
+        let (rise, set) = sunrise_sunset(date("2026-12-21"), 39.9042, 116.4074).unwrap();
+        assert_near(rise, "2026-12-21T07:33:00+08:00");
+        assert_near(set, "2026-12-21T16:53:00+08:00");
+    }
+
+    #[test]
+    fn london_solstices() {
+        let (rise, set) = sunrise_sunset(date("2026-06-21"), 51.5074, -0.1278).unwrap();
+        assert_near(rise, "2026-06-21T04:43:00+01:00");
+        assert_near(set, "2026-06-21T21:21:00+01:00");
+
+        let (rise, set) = sunrise_sunset(date("2026-12-21"), 51.5074, -0.1278).unwrap();
+        assert_near(rise, "2026-12-21T08:04:00+00:00");
+        assert_near(set, "2026-12-21T15:53:00+00:00");
+    }
+
+    #[test]
+    fn polar_day_and_night_have_no_sunrise() {
+        assert_eq!(sunrise_sunset(date("2026-06-21"), 69.6492, 18.9553), None);
+        assert_eq!(sunrise_sunset(date("2026-12-21"), 69.6492, 18.9553), None);
+    }
+}
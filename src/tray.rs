@@ -1,14 +1,20 @@
 use std::sync::{
     Arc, Mutex,
     atomic::{AtomicBool, Ordering},
+    mpsc,
 };
 
 use eframe::egui;
 
+use crate::schedule::TrayActivation;
+
 #[derive(Default)]
 struct TraySignals {
     show_requested: AtomicBool,
     exit_requested: AtomicBool,
+    reset_position_requested: AtomicBool,
+    /// 仅双击恢复窗口（`TrayActivation::DoubleClick`），由主线程设置、托盘线程读取
+    double_click_only: AtomicBool,
 }
 
 impl TraySignals {
@@ -20,6 +26,10 @@ impl TraySignals {
         self.exit_requested.store(true, Ordering::Release);
     }
 
+    fn request_reset_position(&self) {
+        self.reset_position_requested.store(true, Ordering::Release);
+    }
+
     fn take_show_request(&self) -> bool {
         self.show_requested.swap(false, Ordering::AcqRel)
     }
@@ -27,16 +37,128 @@ impl TraySignals {
     fn take_exit_request(&self) -> bool {
         self.exit_requested.swap(false, Ordering::AcqRel)
     }
+
+    fn take_reset_position_request(&self) -> bool {
+        self.reset_position_requested.swap(false, Ordering::AcqRel)
+    }
+
+    /// 托盘图标事件是否应恢复主窗口：双击总是恢复，单击（左键抬起）仅在允许单击时恢复
+    #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+    fn is_restore_click(&self, event: &tray_icon::TrayIconEvent) -> bool {
+        use tray_icon::{MouseButton, MouseButtonState, TrayIconEvent};
+
+        match event {
+            TrayIconEvent::DoubleClick {
+                button: MouseButton::Left,
+                ..
+            } => true,
+            TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } => !self.double_click_only.load(Ordering::Acquire),
+            _ => false,
+        }
+    }
+}
+
+/// 托盘图标（内嵌 ICO）
+const TRAY_ICON: &[u8] = include_bytes!("../assets/icon.ico");
+
+/// 自定义图片缩放到的托盘图标边长（像素，保持比例）
+#[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+const TRAY_ICON_SIZE: u32 = 32;
+
+/// 通知托盘线程重新加载图标的线程消息
+#[cfg(target_os = "windows")]
+const WM_RELOAD_ICON: u32 = windows_sys::Win32::UI::WindowsAndMessaging::WM_APP + 1;
+
+/// 读取托盘图标：优先使用 `path` 指向的图片，读取失败时回退到内嵌图标
+#[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+fn load_icon(path: Option<&str>) -> anyhow::Result<tray_icon::Icon> {
+    use anyhow::Context as _;
+
+    let custom = path.and_then(|path| match image::open(path) {
+        Ok(image) => Some(image.resize(
+            TRAY_ICON_SIZE,
+            TRAY_ICON_SIZE,
+            image::imageops::FilterType::Lanczos3,
+        )),
+        Err(e) => {
+            log::warn!("自定义托盘图标读取失败，使用默认图标: {path}: {e}");
+            None
+        }
+    });
+    let image = match custom {
+        Some(image) => image,
+        None => image::load_from_memory(TRAY_ICON).context("读取托盘图标失败")?,
+    }
+    .to_rgba8();
+    let (width, height) = image.dimensions();
+    tray_icon::Icon::from_rgba(image.into_raw(), width, height)
+        .map_err(|e| anyhow::anyhow!("托盘图标解码失败: {e}"))
+}
+
+/// 启动托盘线程并等待初始化结果，失败返回 `None`（可在运行时再次调用重试）。
+///
+/// 在专用线程中创建托盘图标并运行 Win32 消息泵。
+/// tray-icon 要求：托盘图标必须在与 Win32 消息泵相同的线程上创建。
+/// eframe/winit 只泵送自己管理的窗口消息，不会泵送 tray-icon 隐藏 HWND 的消息，
+/// 因此必须在独立线程中运行 GetMessage/DispatchMessage 循环。
+///
+/// 方案：new_split() 返回 (TrayHandle, TrayThreadState)：
+///   - TrayHandle 只含 Arc 字段（Send），传回主线程使用
+///   - TrayThreadState 移入专用线程，完成托盘初始化并运行消息泵
+pub fn spawn(icon_path: Option<String>) -> Option<TrayHandle> {
+    // 使用 SyncSender（容量=1），托盘线程在初始化完成后立即发送结果，
+    // 然后继续运行消息泵。主线程收到信号后即可继续启动 eframe，不再阻塞。
+    let (init_tx, init_rx) = std::sync::mpsc::sync_channel::<bool>(1);
+
+    let (handle, thread_state) = TrayHandle::new_split(icon_path, init_tx);
+
+    let spawned = std::thread::Builder::new()
+        .name("tray-msg-pump".to_string())
+        .spawn(move || {
+            // run() 内部：初始化托盘 → 立即通过 init_tx 通知主线程 → 运行消息泵
+            thread_state.run();
+        });
+    if let Err(e) = spawned {
+        log::warn!("无法创建托盘消息泵线程: {e}");
+        return None;
+    }
+
+    // 等待托盘线程完成初始化（init_tx 在初始化后立即发送，不等消息泵退出）
+    match init_rx.recv() {
+        Ok(true) => {
+            log::info!("托盘功能已启用");
+            Some(handle)
+        }
+        Ok(false) => {
+            log::warn!("托盘初始化失败，将不启用托盘功能");
+            None
+        }
+        Err(_) => {
+            log::warn!("托盘线程异常退出，将不启用托盘功能");
+            None
+        }
+    }
 }
 
 /// 主线程持有的托盘句柄。
 ///
-/// 只包含 `Arc` 包裹的共享状态，均实现了 `Send + Sync`，
+/// 只包含 `Arc` 包裹的共享状态、图标更换 channel 的发送端等 `Send + Sync` 字段，
 /// 可安全地从托盘线程传回主线程。
 /// 实际的 `TrayIcon`（内含 `Rc`，非 `Send`）留在托盘线程中。
 pub struct TrayHandle {
     signals: Arc<TraySignals>,
     repaint_ctx: Arc<Mutex<Option<egui::Context>>>,
+    /// 向托盘线程发送新的图标路径（`None` 为内嵌图标）
+    icon_tx: mpsc::Sender<Option<String>>,
+    /// 托盘线程当前使用的图标路径，未变化时不重复加载
+    icon_path: Mutex<Option<String>>,
+    /// 托盘线程 ID，用于唤醒阻塞在 `GetMessageW` 上的消息泵
+    #[cfg(target_os = "windows")]
+    thread_id: Arc<std::sync::atomic::AtomicU32>,
 }
 
 impl TrayHandle {
@@ -48,22 +170,32 @@ impl TrayHandle {
     /// `init_tx` 用于在托盘初始化完成后立即通知主线程（成功/失败），
     /// 通知发出后消息泵继续在托盘线程中运行，主线程不再阻塞。
     pub fn new_split(
-        icon_bytes: &'static [u8],
+        icon_path: Option<String>,
         init_tx: std::sync::mpsc::SyncSender<bool>,
     ) -> (TrayHandle, TrayThreadState) {
         let signals = Arc::new(TraySignals::default());
         let repaint_ctx = Arc::new(Mutex::new(None::<egui::Context>));
+        let (icon_tx, icon_rx) = mpsc::channel();
+        #[cfg(target_os = "windows")]
+        let thread_id = Arc::new(std::sync::atomic::AtomicU32::new(0));
 
         let handle = TrayHandle {
             signals: Arc::clone(&signals),
             repaint_ctx: Arc::clone(&repaint_ctx),
+            icon_tx,
+            icon_path: Mutex::new(icon_path.clone()),
+            #[cfg(target_os = "windows")]
+            thread_id: Arc::clone(&thread_id),
         };
 
         let state = TrayThreadState {
-            icon_bytes,
+            icon_path,
+            icon_rx,
             signals,
             repaint_ctx,
             init_tx,
+            #[cfg(target_os = "windows")]
+            thread_id,
         };
 
         (handle, state)
@@ -79,21 +211,65 @@ impl TrayHandle {
         self.signals.take_show_request()
     }
 
+    /// 与托盘单击相同的“恢复主窗口”请求，可交给其他线程（如通知点击回调）调用
+    pub fn show_requester(&self) -> impl Fn() + Send + 'static {
+        let signals = Arc::clone(&self.signals);
+        let repaint_ctx = Arc::clone(&self.repaint_ctx);
+        move || {
+            signals.request_show();
+            wake_main_window(&repaint_ctx);
+        }
+    }
+
     pub fn take_exit_request(&self) -> bool {
         self.signals.take_exit_request()
     }
+
+    /// 托盘菜单“重置窗口位置”：窗口跑到屏幕外够不着时的手动补救
+    pub fn take_reset_position_request(&self) -> bool {
+        self.signals.take_reset_position_request()
+    }
+
+    pub fn set_activation(&self, activation: TrayActivation) {
+        self.signals
+            .double_click_only
+            .store(activation == TrayActivation::DoubleClick, Ordering::Release);
+    }
+
+    /// 更换托盘图标（`None` 恢复内嵌图标），在托盘线程中加载并应用
+    pub fn set_icon_path(&self, path: Option<&str>) {
+        let Ok(mut current) = self.icon_path.lock() else {
+            return;
+        };
+        if current.as_deref() == path {
+            return;
+        }
+        *current = path.map(str::to_string);
+        if self.icon_tx.send(current.clone()).is_err() {
+            log::warn!("托盘线程已退出，无法更换图标");
+        }
+
+        #[cfg(target_os = "windows")]
+        unsafe {
+            use windows_sys::Win32::UI::WindowsAndMessaging::PostThreadMessageW;
+            PostThreadMessageW(self.thread_id.load(Ordering::Acquire), WM_RELOAD_ICON, 0, 0);
+        }
+    }
 }
 
 /// 托盘线程状态，持有初始化托盘所需的全部数据。
 ///
-/// 此结构体是 `Send`（`Arc` 字段均为 `Send + Sync`，`&'static [u8]` 也是 `Send`），
+/// 此结构体是 `Send`（`Arc` 字段均为 `Send + Sync`，`Receiver` 也是 `Send`），
 /// 可安全地移入 `std::thread::spawn` 闭包。
 pub struct TrayThreadState {
-    icon_bytes: &'static [u8],
+    icon_path: Option<String>,
+    icon_rx: mpsc::Receiver<Option<String>>,
     signals: Arc<TraySignals>,
     repaint_ctx: Arc<Mutex<Option<egui::Context>>>,
     /// 初始化完成后立即通过此 channel 通知主线程，然后继续运行消息泵。
     init_tx: std::sync::mpsc::SyncSender<bool>,
+    #[cfg(target_os = "windows")]
+    thread_id: Arc<std::sync::atomic::AtomicU32>,
 }
 
 impl TrayThreadState {
@@ -104,20 +280,25 @@ impl TrayThreadState {
     pub fn run(self) {
         #[cfg(target_os = "windows")]
         {
-            let init_ok = self.init_tray_windows();
+            // 先记录线程 ID，保证主线程拿到句柄时已可唤醒消息泵
+            self.thread_id.store(
+                unsafe { windows_sys::Win32::System::Threading::GetCurrentThreadId() },
+                Ordering::Release,
+            );
+            let tray_icon = self.init_tray_windows();
             // ★ 关键：初始化完成后立即通知主线程，不等消息泵退出
-            let _ = self.init_tx.send(init_ok);
-            if init_ok {
-                self.run_message_pump_windows();
+            let _ = self.init_tx.send(tray_icon.is_some());
+            if let Some(tray_icon) = tray_icon {
+                self.run_message_pump_windows(&tray_icon);
             }
         }
 
         #[cfg(any(target_os = "linux", target_os = "macos"))]
         {
-            let init_ok = self.init_tray_unix();
-            let _ = self.init_tx.send(init_ok);
-            if init_ok {
-                self.run_message_pump_unix();
+            let tray_icon = self.init_tray_unix();
+            let _ = self.init_tx.send(tray_icon.is_some());
+            if let Some(tray_icon) = tray_icon {
+                self.run_message_pump_unix(&tray_icon);
             }
         }
 
@@ -129,32 +310,34 @@ impl TrayThreadState {
     }
 
     #[cfg(target_os = "windows")]
-    fn init_tray_windows(&self) -> bool {
+    fn init_tray_windows(&self) -> Option<tray_icon::TrayIcon> {
         use anyhow::Context as _;
         use tray_icon::menu::{Menu, MenuEvent, MenuId, MenuItem, PredefinedMenuItem};
-        use tray_icon::{
-            Icon, MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent, TrayIconId,
-        };
+        use tray_icon::{TrayIcon, TrayIconBuilder, TrayIconEvent, TrayIconId};
 
         const SHOW_MENU_ID: &str = "wc_notice.tray.show";
+        const RESET_POSITION_MENU_ID: &str = "wc_notice.tray.reset_position";
         const EXIT_MENU_ID: &str = "wc_notice.tray.exit";
 
-        let result: anyhow::Result<()> = (|| {
-            let image = image::load_from_memory(self.icon_bytes)
-                .context("读取托盘图标失败")?
-                .to_rgba8();
-            let (width, height) = image.dimensions();
-            let icon = Icon::from_rgba(image.into_raw(), width, height)
-                .map_err(|e| anyhow::anyhow!("托盘图标解码失败: {e}"))?;
+        let result: anyhow::Result<TrayIcon> = (|| {
+            let icon = load_icon(self.icon_path.as_deref())?;
 
             let tray_menu = Menu::new();
             let show_id = MenuId::new(SHOW_MENU_ID);
+            let reset_position_id = MenuId::new(RESET_POSITION_MENU_ID);
             let exit_id = MenuId::new(EXIT_MENU_ID);
             let show_item = MenuItem::with_id(show_id.clone(), "显示主界面", true, None);
+            let reset_position_item =
+                MenuItem::with_id(reset_position_id.clone(), "重置窗口位置", true, None);
             let exit_item = MenuItem::with_id(exit_id.clone(), "退出", true, None);
 
             tray_menu
-                .append_items(&[&show_item, &PredefinedMenuItem::separator(), &exit_item])
+                .append_items(&[
+                    &show_item,
+                    &reset_position_item,
+                    &PredefinedMenuItem::separator(),
+                    &exit_item,
+                ])
                 .context("初始化托盘菜单失败")?;
 
             let signals_for_menu = Arc::clone(&self.signals);
@@ -165,6 +348,9 @@ impl TrayThreadState {
                 if event.id == show_id_for_menu {
                     signals_for_menu.request_show();
                     wake_main_window(&repaint_ctx_for_menu);
+                } else if event.id == reset_position_id {
+                    signals_for_menu.request_reset_position();
+                    wake_main_window(&repaint_ctx_for_menu);
                 } else if event.id == exit_id_for_menu {
                     signals_for_menu.request_exit();
                     wake_main_window(&repaint_ctx_for_menu);
@@ -180,26 +366,14 @@ impl TrayThreadState {
                     return;
                 }
 
-                let should_restore = matches!(
-                    &event,
-                    TrayIconEvent::DoubleClick {
-                        button: MouseButton::Left,
-                        ..
-                    } | TrayIconEvent::Click {
-                        button: MouseButton::Left,
-                        button_state: MouseButtonState::Up,
-                        ..
-                    }
-                );
-
-                if should_restore {
+                if signals_for_click.is_restore_click(&event) {
                     signals_for_click.request_show();
                     wake_main_window(&repaint_ctx_for_click);
                 }
             }));
 
-            // 注意：_tray_icon 必须保持存活，否则托盘图标会消失。
-            // 用 Box::leak 将其泄漏到 'static，确保在消息泵线程中永久存活。
+            // 注意：tray_icon 必须保持存活，否则托盘图标会消失，
+            // 由 run() 在消息泵运行期间持有。
             let tray_icon = TrayIconBuilder::new()
                 .with_id(tray_id)
                 .with_icon(icon)
@@ -209,32 +383,43 @@ impl TrayThreadState {
                 .build()
                 .context("创建托盘图标失败")?;
 
-            Box::leak(Box::new(tray_icon));
-
-            Ok(())
+            Ok(tray_icon)
         })();
 
         match result {
-            Ok(()) => {
+            Ok(tray_icon) => {
                 log::info!("托盘图标初始化成功");
-                true
+                Some(tray_icon)
             }
             Err(e) => {
                 log::warn!("托盘初始化失败，将不启用托盘功能: {e}");
-                false
+                None
             }
         }
     }
 
+    /// 运行 Win32 消息泵。
+    ///
+    /// Explorer 重启后会向所有顶层窗口广播 `TaskbarCreated`；tray-icon 在其隐藏窗口的
+    /// 窗口过程中收到该消息后会自动重新注册托盘图标（启动时 Explorer 尚未就绪也一样），
+    /// 前提是本线程的消息泵持续运行，因此这里无需自行重建 `TrayIcon`。
     #[cfg(target_os = "windows")]
-    fn run_message_pump_windows(&self) {
-        log::info!("托盘消息泵线程启动");
+    fn run_message_pump_windows(&self, tray_icon: &tray_icon::TrayIcon) {
+        log::info!("托盘消息泵线程启动（Explorer 重启后托盘图标会自动恢复）");
         unsafe {
             use windows_sys::Win32::UI::WindowsAndMessaging::{
                 DispatchMessageW, GetMessageW, MSG, TranslateMessage,
             };
             let mut msg: MSG = std::mem::zeroed();
             while GetMessageW(&mut msg, std::ptr::null_mut(), 0, 0) > 0 {
+                // 线程消息没有目标窗口，不经过 DispatchMessageW
+                if msg.hwnd.is_null() && msg.message == WM_RELOAD_ICON {
+                    // 连续多次更换只应用最后一次
+                    if let Some(path) = self.icon_rx.try_iter().last() {
+                        apply_icon(tray_icon, path.as_deref());
+                    }
+                    continue;
+                }
                 TranslateMessage(&msg);
                 DispatchMessageW(&msg);
             }
@@ -246,32 +431,34 @@ impl TrayThreadState {
     /// tray-icon 在这两个平台上使用 GTK（Linux）或 NSStatusItem（macOS），
     /// 不需要独立的 Win32 消息泵，事件由 tray-icon 内部机制分发。
     #[cfg(any(target_os = "linux", target_os = "macos"))]
-    fn init_tray_unix(&self) -> bool {
+    fn init_tray_unix(&self) -> Option<tray_icon::TrayIcon> {
         use anyhow::Context as _;
         use tray_icon::menu::{Menu, MenuEvent, MenuId, MenuItem, PredefinedMenuItem};
-        use tray_icon::{
-            Icon, MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent, TrayIconId,
-        };
+        use tray_icon::{TrayIcon, TrayIconBuilder, TrayIconEvent, TrayIconId};
 
         const SHOW_MENU_ID: &str = "wc_notice.tray.show";
+        const RESET_POSITION_MENU_ID: &str = "wc_notice.tray.reset_position";
         const EXIT_MENU_ID: &str = "wc_notice.tray.exit";
 
-        let result: anyhow::Result<()> = (|| {
-            let image = image::load_from_memory(self.icon_bytes)
-                .context("读取托盘图标失败")?
-                .to_rgba8();
-            let (width, height) = image.dimensions();
-            let icon = Icon::from_rgba(image.into_raw(), width, height)
-                .map_err(|e| anyhow::anyhow!("托盘图标解码失败: {e}"))?;
+        let result: anyhow::Result<TrayIcon> = (|| {
+            let icon = load_icon(self.icon_path.as_deref())?;
 
             let tray_menu = Menu::new();
             let show_id = MenuId::new(SHOW_MENU_ID);
+            let reset_position_id = MenuId::new(RESET_POSITION_MENU_ID);
             let exit_id = MenuId::new(EXIT_MENU_ID);
             let show_item = MenuItem::with_id(show_id.clone(), "显示主界面", true, None);
+            let reset_position_item =
+                MenuItem::with_id(reset_position_id.clone(), "重置窗口位置", true, None);
             let exit_item = MenuItem::with_id(exit_id.clone(), "退出", true, None);
 
             tray_menu
-                .append_items(&[&show_item, &PredefinedMenuItem::separator(), &exit_item])
+                .append_items(&[
+                    &show_item,
+                    &reset_position_item,
+                    &PredefinedMenuItem::separator(),
+                    &exit_item,
+                ])
                 .context("初始化托盘菜单失败")?;
 
             let signals_for_menu = Arc::clone(&self.signals);
@@ -282,6 +469,9 @@ impl TrayThreadState {
                 if event.id == show_id_for_menu {
                     signals_for_menu.request_show();
                     wake_main_window(&repaint_ctx_for_menu);
+                } else if event.id == reset_position_id {
+                    signals_for_menu.request_reset_position();
+                    wake_main_window(&repaint_ctx_for_menu);
                 } else if event.id == exit_id_for_menu {
                     signals_for_menu.request_exit();
                     wake_main_window(&repaint_ctx_for_menu);
@@ -296,18 +486,7 @@ impl TrayThreadState {
                 if event.id() != &tray_id_for_click {
                     return;
                 }
-                let should_restore = matches!(
-                    &event,
-                    TrayIconEvent::DoubleClick {
-                        button: MouseButton::Left,
-                        ..
-                    } | TrayIconEvent::Click {
-                        button: MouseButton::Left,
-                        button_state: MouseButtonState::Up,
-                        ..
-                    }
-                );
-                if should_restore {
+                if signals_for_click.is_restore_click(&event) {
                     signals_for_click.request_show();
                     wake_main_window(&repaint_ctx_for_click);
                 }
@@ -322,18 +501,17 @@ impl TrayThreadState {
                 .build()
                 .context("创建托盘图标失败")?;
 
-            Box::leak(Box::new(tray_icon));
-            Ok(())
+            Ok(tray_icon)
         })();
 
         match result {
-            Ok(()) => {
+            Ok(tray_icon) => {
                 log::info!("托盘图标初始化成功");
-                true
+                Some(tray_icon)
             }
             Err(e) => {
                 log::warn!("托盘初始化失败，将不启用托盘功能: {e}");
-                false
+                None
             }
         }
     }
@@ -341,15 +519,26 @@ impl TrayThreadState {
     /// Linux / macOS 消息泵：tray-icon 在这两个平台上依赖主线程事件循环，
     /// 但由于 eframe 已经在主线程运行事件循环，托盘事件会通过 tray-icon 的
     /// 内部回调机制触发，不需要额外的消息泵循环。
-    /// 此处用简单的 sleep 循环保持线程存活（托盘图标已 leak，不会被 drop）。
+    /// 此处阻塞等待更换图标的请求以保持线程（及其持有的托盘图标）存活，
+    /// 主线程的 `TrayHandle` 释放（程序退出）时随之结束。
     #[cfg(any(target_os = "linux", target_os = "macos"))]
-    fn run_message_pump_unix(&self) {
+    fn run_message_pump_unix(&self, tray_icon: &tray_icon::TrayIcon) {
         log::info!("托盘线程保活循环启动");
-        // 托盘图标已通过 Box::leak 保持存活，此线程只需保持运行即可。
-        // 实际事件分发由 tray-icon 内部机制处理。
-        loop {
-            std::thread::sleep(std::time::Duration::from_secs(60));
+        for path in self.icon_rx.iter() {
+            apply_icon(tray_icon, path.as_deref());
         }
+        log::info!("托盘线程退出");
+    }
+}
+
+#[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+fn apply_icon(tray_icon: &tray_icon::TrayIcon, path: Option<&str>) {
+    match load_icon(path) {
+        Ok(icon) => match tray_icon.set_icon(Some(icon)) {
+            Ok(()) => log::info!("托盘图标已更换"),
+            Err(e) => log::warn!("更换托盘图标失败: {e}"),
+        },
+        Err(e) => log::warn!("更换托盘图标失败: {e}"),
     }
 }
 